@@ -6,6 +6,9 @@ use crate::abci::messages::{
 };
 use crate::block::{BlockExecutionContext, BlockInfo};
 use crate::execution::fee_pools::epoch::EpochInfo;
+use rs_drive::drive::defaults::{
+    CONTRACT_DELETION_ELEMENTS_PER_BLOCK, INDEX_BACKFILL_DOCUMENTS_PER_BLOCK,
+};
 use rs_drive::grovedb::TransactionArg;
 
 use crate::error::execution::ExecutionError;
@@ -66,6 +69,18 @@ impl TenderdashAbci for Platform {
                 )))?
         };
 
+        // Fees of the new protocol version are charged from the next epoch on
+        if let Some(protocol_version) = request.protocol_version {
+            if protocol_version != self.drive.get_protocol_version(transaction)? {
+                self.drive
+                    .update_protocol_version(protocol_version, transaction)?;
+            }
+        }
+
+        // Fees of the block are calculated with the fee schedule and the multiplier
+        // of the current epoch, they are only read once per block
+        self.drive.cache_current_epoch_fees(transaction)?;
+
         // Init block execution context
         let block_info = BlockInfo::from_block_begin_request(&request);
 
@@ -100,14 +115,38 @@ impl TenderdashAbci for Platform {
             }
         };
 
+        // Delete part of the contracts being deleted, their storage is refunded to the
+        // contract owners so the refunds are taken from the storage pools with the block's.
+        // Like the backfills below, the deletion can't fail the block.
+        let mut block_fees = request.fees;
+        if let Ok((_, contract_deletions_fees)) = self
+            .drive
+            .process_contract_deletions(CONTRACT_DELETION_ELEMENTS_PER_BLOCK, transaction)
+        {
+            block_fees
+                .refunds_by_epoch
+                .extend(contract_deletions_fees.refunds_by_epoch);
+        }
+
         // Process fees
         let process_block_fees_result = self.process_block_fees(
             &block_execution_context.block_info,
             &block_execution_context.epoch_info,
-            &request.fees,
+            &block_fees,
             transaction,
         )?;
 
+        // Write documents stored before an index was added into that index.
+        // The backfill is free: the index entries are the ones the documents would have
+        // had if the index existed when they were stored, and the contract owner can't be
+        // charged for documents of other identities. Only a bounded number of documents is
+        // written per block so the cost the network absorbs stays small.
+        // A backfill that fails is persisted as failed by drive, any other error only
+        // delays the backfills to the next block so it must not fail the block.
+        let _ = self
+            .drive
+            .process_index_backfills(INDEX_BACKFILL_DOCUMENTS_PER_BLOCK, transaction);
+
         Ok(
             BlockEndResponse::from_epoch_info_and_process_block_fees_result(
                 &block_execution_context.epoch_info,
@@ -204,6 +243,7 @@ mod tests {
                         previous_block_time_ms,
                         proposer_pro_tx_hash: proposers
                             [block_height as usize % (proposers_count as usize)],
+                        protocol_version: None,
                     };
 
                     platform
@@ -220,6 +260,7 @@ mod tests {
                         fees: FeesAggregate {
                             processing_fees: 1600,
                             storage_fees: storage_fees_per_block,
+                            refunds_by_epoch: vec![],
                         },
                     };
 
@@ -345,6 +386,7 @@ mod tests {
                         previous_block_time_ms,
                         proposer_pro_tx_hash: proposers
                             [block_height as usize % (proposers_count as usize)],
+                        protocol_version: None,
                     };
 
                     platform
@@ -361,6 +403,7 @@ mod tests {
                         fees: FeesAggregate {
                             processing_fees: 1600,
                             storage_fees: storage_fees_per_block,
+                            refunds_by_epoch: vec![],
                         },
                     };
 
@@ -19,6 +19,8 @@ pub struct BlockBeginRequest {
     pub block_time_ms: u64,
     pub previous_block_time_ms: Option<u64>,
     pub proposer_pro_tx_hash: [u8; 32],
+    /// Set when the block activates a new protocol version
+    pub protocol_version: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -31,14 +33,15 @@ pub struct BlockEndRequest {
     pub fees: FeesAggregate,
 }
 
-pub type EpochRefund = (u16, u64);
+pub use rs_drive::fee::EpochRefund;
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FeesAggregate {
     pub processing_fees: u64,
     pub storage_fees: u64,
-    // pub refunds_by_epoch: Vec<EpochRefund>,
+    #[serde(default)]
+    pub refunds_by_epoch: Vec<EpochRefund>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -3,7 +3,7 @@ use std::collections::BTreeMap;
 use ciborium::value::Value;
 use rs_drive::dpp::identity::Identity;
 
-use rs_drive::common::helpers::identities::create_test_identity;
+use rs_drive::common::helpers::identities::create_test_identity_with_key_data;
 use rs_drive::contract::document::Document;
 use rs_drive::contract::Contract;
 use rs_drive::dpp::data_contract::extra::DriveContractExt;
@@ -63,6 +63,7 @@ fn create_test_mn_share_document(
             false,
             0f64,
             true,
+            false,
             transaction,
         )
         .expect("expected to insert a document successfully");
@@ -82,7 +83,7 @@ pub fn create_test_masternode_share_identities_and_documents(
         .iter()
         .map(|mn_identity| {
             let id: [u8; 32] = rand::random();
-            let identity = create_test_identity(drive, id, transaction);
+            let identity = create_test_identity_with_key_data(drive, id, id.to_vec(), transaction);
             let document = create_test_mn_share_document(
                 drive,
                 contract,
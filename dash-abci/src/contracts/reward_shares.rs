@@ -56,6 +56,7 @@ impl Platform {
                 contract_cbor.clone(),
                 0f64,
                 true,
+                false,
                 StorageFlags { epoch: 0 },
                 transaction,
             )
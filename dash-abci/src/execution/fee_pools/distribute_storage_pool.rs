@@ -1,3 +1,6 @@
+use std::collections::BTreeMap;
+
+use crate::abci::messages::EpochRefund;
 use crate::error::execution::ExecutionError;
 use crate::error::Error;
 use crate::execution::fee_pools::constants;
@@ -5,6 +8,7 @@ use crate::platform::Platform;
 use rs_drive::drive::batch::GroveDbOpBatch;
 use rs_drive::drive::fee_pools::epochs::constants::{EPOCHS_PER_YEAR, PERPETUAL_STORAGE_YEARS};
 use rs_drive::fee_pools::epochs::Epoch;
+use rs_drive::fee_pools::update_storage_fee_distribution_pool_operation;
 use rs_drive::grovedb::TransactionArg;
 use rs_drive::{error, grovedb};
 use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
@@ -12,6 +16,22 @@ use rust_decimal::Decimal;
 
 pub type StorageDistributionLeftoverCredits = u64;
 
+/// The share of the distributed storage fees each epoch of the year gets
+fn epoch_storage_fee_share_for_year(storage_fees: Decimal, year: u16) -> Result<u64, Error> {
+    let distribution_for_that_year_ratio = constants::FEE_DISTRIBUTION_TABLE[year as usize];
+
+    let year_fee_share = storage_fees * distribution_for_that_year_ratio;
+
+    let epoch_fee_share_dec = year_fee_share / Decimal::from(EPOCHS_PER_YEAR);
+
+    epoch_fee_share_dec
+        .floor()
+        .to_u64()
+        .ok_or(Error::Execution(ExecutionError::Overflow(
+            "storage distribution fees are not fitting in a u64",
+        )))
+}
+
 impl Platform {
     /// returns the leftovers
     pub fn add_distribute_storage_fee_distribution_pool_to_epochs_operations(
@@ -36,21 +56,9 @@ impl Platform {
                 ExecutionError::Overflow("storage distribution fees are not fitting in a u64"),
             ))?;
 
-        let epochs_per_year = Decimal::from(EPOCHS_PER_YEAR);
-
         for year in 0..PERPETUAL_STORAGE_YEARS {
-            let distribution_for_that_year_ratio = constants::FEE_DISTRIBUTION_TABLE[year as usize];
-
-            let year_fee_share = storage_distribution_fees * distribution_for_that_year_ratio;
-
-            let epoch_fee_share_dec = year_fee_share / epochs_per_year;
-
-            let epoch_fee_share = epoch_fee_share_dec
-                .floor()
-                .to_u64()
-                .ok_or(Error::Execution(ExecutionError::Overflow(
-                    "storage distribution fees are not fitting in a u64",
-                )))?;
+            let epoch_fee_share =
+                epoch_storage_fee_share_for_year(storage_distribution_fees, year)?;
 
             let year_start_epoch_index = current_epoch_index + EPOCHS_PER_YEAR * year;
 
@@ -86,6 +94,93 @@ impl Platform {
 
         Ok(storage_distribution_leftover_credits)
     }
+
+    /// Removes refunded storage fees from the pools holding them. Fees paid in the current
+    /// epoch are still in the storage fee distribution pool, older fees were distributed
+    /// to the epochs following the one they were paid in, and are only taken back from
+    /// the epochs that were not paid out yet.
+    /// Returns the credits removed from the storage fee distribution pool.
+    pub fn add_subtract_refunds_from_storage_pools_operations(
+        &self,
+        refunds_by_epoch: &[EpochRefund],
+        current_epoch_index: u16,
+        transaction: TransactionArg,
+        batch: &mut GroveDbOpBatch,
+    ) -> Result<u64, Error> {
+        let mut distribution_pool_refunds = 0u64;
+        let mut epoch_pool_refunds: BTreeMap<u16, u64> = BTreeMap::new();
+
+        for (paid_epoch_index, refund) in refunds_by_epoch {
+            if *paid_epoch_index >= current_epoch_index {
+                distribution_pool_refunds =
+                    distribution_pool_refunds
+                        .checked_add(*refund)
+                        .ok_or(Error::Execution(ExecutionError::Overflow(
+                            "storage refunds are not fitting in a u64",
+                        )))?;
+                continue;
+            }
+
+            let refund = Decimal::from_u64(*refund).ok_or(Error::Execution(
+                ExecutionError::Overflow("storage refunds are not fitting in a u64"),
+            ))?;
+
+            // the fees were distributed on the change to the next epoch
+            let distribution_start_epoch_index = paid_epoch_index + 1;
+
+            for year in 0..PERPETUAL_STORAGE_YEARS {
+                let epoch_refund_share = epoch_storage_fee_share_for_year(refund, year)?;
+
+                let year_start_epoch_index =
+                    distribution_start_epoch_index + EPOCHS_PER_YEAR * year;
+
+                for index in year_start_epoch_index..year_start_epoch_index + EPOCHS_PER_YEAR {
+                    // paid out epochs can't give their credits back
+                    if index < current_epoch_index {
+                        continue;
+                    }
+
+                    let epoch_pool_refund = epoch_pool_refunds.entry(index).or_default();
+                    *epoch_pool_refund = epoch_pool_refund.checked_add(epoch_refund_share).ok_or(
+                        Error::Execution(ExecutionError::Overflow(
+                            "storage refunds are not fitting in a u64",
+                        )),
+                    )?;
+                }
+            }
+        }
+
+        if distribution_pool_refunds > 0 {
+            let storage_distribution_fees = self
+                .drive
+                .get_aggregate_storage_fees_from_distribution_pool(transaction)?;
+
+            // the pool could hold less than the refund due to rounding of the distribution
+            batch.push(update_storage_fee_distribution_pool_operation(
+                storage_distribution_fees.saturating_sub(distribution_pool_refunds),
+            ));
+        }
+
+        for (index, epoch_pool_refund) in epoch_pool_refunds {
+            let epoch_tree = Epoch::new(index);
+
+            let epoch_pool_storage_credits = self
+                .drive
+                .get_epoch_storage_credits_for_distribution(&epoch_tree, transaction)
+                .or_else(|e| match e {
+                    error::Error::GroveDB(grovedb::Error::PathNotFound(_)) => Ok(0u64),
+                    _ => Err(e),
+                })?;
+
+            batch.push(
+                epoch_tree.update_storage_credits_for_distribution_operation(
+                    epoch_pool_storage_credits.saturating_sub(epoch_pool_refund),
+                ),
+            );
+        }
+
+        Ok(distribution_pool_refunds)
+    }
 }
 
 #[cfg(test)]
@@ -354,6 +449,92 @@ mod tests {
         }
     }
 
+    mod add_subtract_refunds_from_storage_pools_operations {
+        use crate::common::helpers::setup::setup_platform_with_initial_state_structure;
+        use rs_drive::common::helpers::epoch::get_storage_credits_for_distribution_for_epochs_in_range;
+        use rs_drive::drive::batch::GroveDbOpBatch;
+        use rs_drive::fee_pools::epochs::Epoch;
+        use rs_drive::fee_pools::update_storage_fee_distribution_pool_operation;
+
+        #[test]
+        fn test_refunds_are_subtracted_from_pools() {
+            let platform = setup_platform_with_initial_state_structure();
+            let transaction = platform.drive.grove.start_transaction();
+
+            let storage_pool = 1000000;
+            let epoch_index = 1;
+
+            let mut batch = GroveDbOpBatch::new();
+
+            // init additional epochs pools as it will be done in epoch_change
+            for i in 1000..=1000 + epoch_index {
+                let epoch = Epoch::new(i);
+                epoch.add_init_empty_operations(&mut batch);
+            }
+
+            batch.push(update_storage_fee_distribution_pool_operation(storage_pool));
+
+            platform
+                .drive
+                .grove_apply_batch(batch, false, Some(&transaction))
+                .expect("should apply batch");
+
+            // distribute fees paid in epoch 0
+            let mut batch = GroveDbOpBatch::new();
+
+            platform
+                .add_distribute_storage_fee_distribution_pool_to_epochs_operations(
+                    epoch_index,
+                    Some(&transaction),
+                    &mut batch,
+                )
+                .expect("should distribute storage fee pool");
+
+            // fees paid in the current epoch
+            batch.push(update_storage_fee_distribution_pool_operation(500));
+
+            platform
+                .drive
+                .grove_apply_batch(batch, false, Some(&transaction))
+                .expect("should apply batch");
+
+            let mut batch = GroveDbOpBatch::new();
+
+            let distribution_pool_refunds = platform
+                .add_subtract_refunds_from_storage_pools_operations(
+                    &[(0, storage_pool), (epoch_index, 200)],
+                    epoch_index,
+                    Some(&transaction),
+                    &mut batch,
+                )
+                .expect("should subtract refunds");
+
+            platform
+                .drive
+                .grove_apply_batch(batch, false, Some(&transaction))
+                .expect("should apply batch");
+
+            assert_eq!(distribution_pool_refunds, 200);
+
+            let stored_storage_fee = platform
+                .drive
+                .get_aggregate_storage_fees_from_distribution_pool(Some(&transaction))
+                .expect("should get storage fee pool");
+
+            assert_eq!(stored_storage_fee, 300);
+
+            let storage_fees = get_storage_credits_for_distribution_for_epochs_in_range(
+                &platform.drive,
+                epoch_index..epoch_index + 1000,
+                Some(&transaction),
+            );
+
+            let reference_fees: Vec<u64> = (0..1000).map(|_| 0u64).collect();
+
+            assert_eq!(storage_fees, reference_fees);
+        }
+    }
+
     mod update_storage_fee_distribution_pool {
         use crate::common::helpers::setup::{
             setup_platform, setup_platform_with_initial_state_structure,
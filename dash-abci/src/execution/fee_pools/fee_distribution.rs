@@ -305,13 +305,8 @@ impl Platform {
     ) -> Result<(), Error> {
         // We don't need additional verification, since we ensure an identity
         // existence in the data contract triggers in DPP
-        let (mut identity, storage_flags) = self.drive.fetch_identity(id, transaction)?;
-
-        // TODO balance should be a u64
-        identity.balance += reward as i64;
-
         self.drive
-            .add_insert_identity_operations(identity, storage_flags, batch)
+            .add_to_identity_balance_operations(id, reward, transaction, batch)
             .map_err(Error::Drive)
     }
 
@@ -1263,6 +1258,7 @@ mod tests {
                     &FeesAggregate {
                         processing_fees,
                         storage_fees,
+                        refunds_by_epoch: vec![],
                     },
                     None,
                     Some(&transaction),
@@ -1320,6 +1316,7 @@ mod tests {
                     &FeesAggregate {
                         processing_fees,
                         storage_fees,
+                        refunds_by_epoch: vec![],
                     },
                     None,
                     Some(&transaction),
@@ -1,7 +1,6 @@
 use crate::abci::messages::FeesAggregate;
 use crate::block::BlockInfo;
 use crate::error::Error;
-use crate::execution::fee_pools::constants::DEFAULT_ORIGINAL_FEE_MULTIPLIER;
 use crate::execution::fee_pools::distribute_storage_pool::StorageDistributionLeftoverCredits;
 use crate::execution::fee_pools::epoch::EpochInfo;
 use crate::execution::fee_pools::fee_distribution::{FeesInPools, ProposersPayouts};
@@ -11,6 +10,7 @@ use rs_drive::drive::fee_pools::epochs::constants::{
     GENESIS_EPOCH_INDEX, PERPETUAL_STORAGE_EPOCHS,
 };
 use rs_drive::fee_pools::epochs::Epoch;
+use rs_drive::fee_pools::update_current_epoch_index_operation;
 use rs_drive::grovedb::TransactionArg;
 use std::option::Option::None;
 
@@ -55,13 +55,24 @@ impl Platform {
         // init current epoch pool for processing
         let current_epoch = Epoch::new(epoch_info.current_epoch_index);
 
+        // the multiplier set by governance is persisted for the whole epoch
+        let fee_multiplier = self.drive.get_fee_multiplier(transaction)?;
+
         current_epoch.add_init_current_operations(
-            DEFAULT_ORIGINAL_FEE_MULTIPLIER, // TODO use a data contract to choose the fee multiplier
+            fee_multiplier,
             block_info.block_height,
             block_info.block_time_ms,
             batch,
         );
 
+        // the protocol version is persisted for the whole epoch, as is its fee schedule
+        let protocol_version = self.drive.get_protocol_version(transaction)?;
+
+        batch.push(current_epoch.update_protocol_version_operation(protocol_version));
+
+        // fees are charged with the multiplier and fee schedule of the current epoch
+        batch.push(update_current_epoch_index_operation(current_epoch.index));
+
         // Nothing to distribute on genesis epoch start
         if current_epoch.index == GENESIS_EPOCH_INDEX {
             return Ok(None);
@@ -133,7 +144,7 @@ impl Platform {
                 &mut batch,
             )?;
 
-        let fees_in_pools = self.add_distribute_block_fees_into_pools_operations(
+        let mut fees_in_pools = self.add_distribute_block_fees_into_pools_operations(
             &current_epoch,
             block_fees,
             // Add leftovers after storage fee pool distribution to the current block storage fees
@@ -144,6 +155,28 @@ impl Platform {
 
         self.drive.grove_apply_batch(batch, false, transaction)?;
 
+        // Refunds are subtracted from the pools updated by the batch above
+        if !block_fees.refunds_by_epoch.is_empty() {
+            let mut refunds_batch = GroveDbOpBatch::new();
+
+            let storage_distribution_pool_refunds = self
+                .add_subtract_refunds_from_storage_pools_operations(
+                    &block_fees.refunds_by_epoch,
+                    epoch_info.current_epoch_index,
+                    transaction,
+                    &mut refunds_batch,
+                )?;
+
+            if !refunds_batch.is_empty() {
+                self.drive
+                    .grove_apply_batch(refunds_batch, false, transaction)?;
+            }
+
+            fees_in_pools.storage_fees = fees_in_pools
+                .storage_fees
+                .saturating_sub(storage_distribution_pool_refunds);
+        }
+
         Ok(ProcessedBlockFeesResult {
             fees_in_pools,
             payouts,
@@ -185,6 +218,7 @@ mod tests {
                     let block_fees = FeesAggregate {
                         processing_fees: 1000,
                         storage_fees: 1000000000,
+                        refunds_by_epoch: vec![],
                     };
 
                     let mut batch = GroveDbOpBatch::new();
@@ -257,6 +291,39 @@ mod tests {
 
                 assert_eq!(epoch_start_block_height, block_height);
 
+                // fee multiplier should be persisted for the epoch
+                let epoch_fee_multiplier = platform
+                    .drive
+                    .get_epoch_fee_multiplier(&current_epoch, transaction)
+                    .expect("should get epoch fee multiplier");
+
+                let fee_multiplier = platform
+                    .drive
+                    .get_fee_multiplier(transaction)
+                    .expect("should get fee multiplier");
+
+                assert_eq!(epoch_fee_multiplier, fee_multiplier);
+
+                let current_epoch_index = platform
+                    .drive
+                    .get_current_epoch_index(transaction)
+                    .expect("should get current epoch index");
+
+                assert_eq!(current_epoch_index, Some(epoch_index));
+
+                // protocol version should be persisted for the epoch
+                let epoch_protocol_version = platform
+                    .drive
+                    .get_epoch_protocol_version(&current_epoch, transaction)
+                    .expect("should get epoch protocol version");
+
+                let protocol_version = platform
+                    .drive
+                    .get_protocol_version(transaction)
+                    .expect("should get protocol version");
+
+                assert_eq!(epoch_protocol_version, protocol_version);
+
                 // storage fee should be distributed
                 assert_eq!(distribute_storage_pool_result.is_some(), should_distribute);
 
@@ -390,6 +457,7 @@ mod tests {
                 let block_fees = FeesAggregate {
                     processing_fees: 1000,
                     storage_fees: 10000,
+                    refunds_by_epoch: vec![],
                 };
 
                 let distribute_storage_pool_result = platform
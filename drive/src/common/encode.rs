@@ -1,3 +1,4 @@
+use crate::error::drive::DriveError;
 use crate::error::Error;
 use byteorder::{BigEndian, WriteBytesExt};
 
@@ -89,3 +90,20 @@ pub fn encode_float(val: f64) -> Result<Vec<u8>, Error> {
 
     Ok(wtr)
 }
+
+pub fn decode_float(encoded: &[u8]) -> Result<f64, Error> {
+    let mut bytes: [u8; 8] = encoded.try_into().map_err(|_| {
+        Error::Drive(DriveError::CorruptedSerialization(
+            "encoded float must be 8 bytes",
+        ))
+    })?;
+
+    // Positive values had their sign bit flipped, negative values had all their bits flipped
+    if bytes[0] & 0b1000_0000 != 0 {
+        bytes[0] ^= 0b1000_0000;
+    } else {
+        bytes = bytes.map(|byte| !byte);
+    }
+
+    Ok(f64::from_be_bytes(bytes))
+}
@@ -7,10 +7,20 @@ use dpp::identity::{Identity, IdentityPublicKey, KeyType};
 use grovedb::TransactionArg;
 
 pub fn create_test_identity(drive: &Drive, id: [u8; 32], transaction: TransactionArg) -> Identity {
+    create_test_identity_with_key_data(drive, id, vec![0, 1, 2, 3], transaction)
+}
+
+/// Public key hashes are unique, identities created together need different key data
+pub fn create_test_identity_with_key_data(
+    drive: &Drive,
+    id: [u8; 32],
+    key_data: Vec<u8>,
+    transaction: TransactionArg,
+) -> Identity {
     let identity_key = IdentityPublicKey {
         id: 1,
         key_type: KeyType::ECDSA_SECP256K1,
-        data: vec![0, 1, 2, 3],
+        data: key_data,
         purpose: dpp::identity::Purpose::AUTHENTICATION,
         security_level: dpp::identity::SecurityLevel::MASTER,
         read_only: false,
@@ -27,7 +37,13 @@ pub fn create_test_identity(drive: &Drive, id: [u8; 32], transaction: Transactio
     };
 
     drive
-        .insert_identity(identity.clone(), true, StorageFlags::default(), transaction)
+        .insert_identity(
+            identity.clone(),
+            true,
+            false,
+            StorageFlags::default(),
+            transaction,
+        )
         .expect("should insert identity");
 
     identity
@@ -81,7 +97,12 @@ pub fn create_test_masternode_identities(
     for _ in 0..count {
         let proposer_pro_tx_hash: [u8; 32] = rand::random();
 
-        create_test_identity(drive, proposer_pro_tx_hash, transaction);
+        create_test_identity_with_key_data(
+            drive,
+            proposer_pro_tx_hash,
+            proposer_pro_tx_hash.to_vec(),
+            transaction,
+        );
 
         identity_ids.push(proposer_pro_tx_hash);
     }
@@ -38,6 +38,7 @@ pub fn setup_contract(
             contract_id,
             0f64,
             true,
+            false,
             StorageFlags::default(),
             transaction,
         )
@@ -59,6 +60,7 @@ pub fn setup_contract_from_hex(
             None,
             0f64,
             true,
+            false,
             StorageFlags::default(),
             transaction,
         )
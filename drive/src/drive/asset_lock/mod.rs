@@ -0,0 +1,293 @@
+use grovedb::{Element, PathQuery, Query, SizedQuery, TransactionArg};
+
+use crate::drive::batch::GroveDbOpBatch;
+use crate::drive::flags::StorageFlags;
+use crate::drive::object_size_info::KeyValueInfo::KeyRefRequest;
+use crate::drive::{Drive, RootTree};
+use crate::error::drive::DriveError;
+use crate::error::identity::IdentityError;
+use crate::error::Error;
+use crate::fee::op::DriveOperation;
+
+/// Transaction hash (32 bytes) followed by the output index (4 bytes)
+pub const OUTPOINT_SIZE: usize = 36;
+
+const SPENT_ASSET_LOCK_OUTPOINT_VALUE_SIZE: usize = 10;
+
+pub(crate) fn spent_asset_lock_transactions_path() -> [&'static [u8]; 1] {
+    [Into::<&[u8; 1]>::into(RootTree::SpentAssetLockTransactions)]
+}
+
+pub(crate) fn spent_asset_lock_transactions_vec_path() -> Vec<Vec<u8>> {
+    vec![vec![RootTree::SpentAssetLockTransactions as u8]]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpentAssetLockOutpoint {
+    pub block_height: u64,
+    pub epoch_index: u16,
+}
+
+impl SpentAssetLockOutpoint {
+    fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(SPENT_ASSET_LOCK_OUTPOINT_VALUE_SIZE);
+        bytes.extend_from_slice(&self.block_height.to_be_bytes());
+        bytes.extend_from_slice(&self.epoch_index.to_be_bytes());
+        bytes
+    }
+
+    fn from_element(element: Element) -> Result<Self, Error> {
+        if let Element::Item(bytes, _) = element {
+            if bytes.len() != SPENT_ASSET_LOCK_OUTPOINT_VALUE_SIZE {
+                return Err(Error::Drive(DriveError::CorruptedSpentAssetLockOutpoint(
+                    "spent asset lock outpoint item has an invalid length",
+                )));
+            }
+
+            let (block_height_bytes, epoch_index_bytes) = bytes.split_at(8);
+
+            Ok(SpentAssetLockOutpoint {
+                block_height: u64::from_be_bytes(block_height_bytes.try_into().map_err(|_| {
+                    Error::Drive(DriveError::CorruptedSpentAssetLockOutpoint(
+                        "spent asset lock outpoint block height has an invalid length",
+                    ))
+                })?),
+                epoch_index: u16::from_be_bytes(epoch_index_bytes.try_into().map_err(|_| {
+                    Error::Drive(DriveError::CorruptedSpentAssetLockOutpoint(
+                        "spent asset lock outpoint epoch index has an invalid length",
+                    ))
+                })?),
+            })
+        } else {
+            Err(Error::Drive(DriveError::CorruptedSpentAssetLockOutpoint(
+                "spent asset lock outpoint must be an item",
+            )))
+        }
+    }
+}
+
+impl Drive {
+    pub fn has_asset_lock_outpoint_already_been_spent(
+        &self,
+        outpoint: &[u8; OUTPOINT_SIZE],
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<bool, Error> {
+        self.grove_has_raw(
+            spent_asset_lock_transactions_path(),
+            outpoint,
+            true,
+            transaction,
+            drive_operations,
+        )
+    }
+
+    /// Adds the operations marking the asset lock outpoint as spent to the batch.
+    /// Fails if the outpoint was spent in a previous block or is already spent by this batch.
+    pub fn add_spent_asset_lock_outpoint_operations(
+        &self,
+        outpoint: &[u8; OUTPOINT_SIZE],
+        block_height: u64,
+        epoch_index: u16,
+        transaction: TransactionArg,
+        batch: &mut GroveDbOpBatch,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<(), Error> {
+        let path = spent_asset_lock_transactions_vec_path();
+
+        let is_spent_in_batch = batch
+            .operations
+            .iter()
+            .any(|op| op.path == path && op.key.as_slice() == outpoint.as_slice());
+
+        if is_spent_in_batch
+            || self.has_asset_lock_outpoint_already_been_spent(
+                outpoint,
+                transaction,
+                drive_operations,
+            )?
+        {
+            return Err(Error::Identity(
+                IdentityError::AssetLockOutpointAlreadySpent(
+                    "asset lock outpoint has already been spent",
+                ),
+            ));
+        }
+
+        let spent_outpoint = SpentAssetLockOutpoint {
+            block_height,
+            epoch_index,
+        };
+
+        let storage_flags = StorageFlags { epoch: epoch_index };
+
+        batch.add_insert(
+            path,
+            outpoint.to_vec(),
+            Element::Item(spent_outpoint.to_bytes(), storage_flags.to_element_flags()),
+        );
+
+        Ok(())
+    }
+
+    pub fn fetch_spent_asset_lock_outpoint(
+        &self,
+        outpoint: &[u8; OUTPOINT_SIZE],
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<Option<SpentAssetLockOutpoint>, Error> {
+        match self.grove_get(
+            spent_asset_lock_transactions_path(),
+            KeyRefRequest(outpoint),
+            transaction,
+            drive_operations,
+        ) {
+            Ok(element) => element
+                .map(SpentAssetLockOutpoint::from_element)
+                .transpose(),
+            Err(Error::GroveDB(grovedb::Error::PathKeyNotFound(_))) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn prove_spent_asset_lock_outpoint(
+        &self,
+        outpoint: &[u8; OUTPOINT_SIZE],
+        transaction: TransactionArg,
+    ) -> Result<Vec<u8>, Error> {
+        let path_query = spent_asset_lock_outpoint_path_query(outpoint);
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.grove_get_proved_path_query(&path_query, transaction, &mut drive_operations)
+    }
+}
+
+fn spent_asset_lock_outpoint_path_query(outpoint: &[u8; OUTPOINT_SIZE]) -> PathQuery {
+    let mut query = Query::new();
+    query.insert_key(outpoint.to_vec());
+    PathQuery::new(
+        spent_asset_lock_transactions_vec_path(),
+        SizedQuery::new(query, Some(1), None),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::helpers::setup::setup_drive_with_initial_state_structure;
+    use crate::drive::asset_lock::{
+        spent_asset_lock_outpoint_path_query, SpentAssetLockOutpoint, OUTPOINT_SIZE,
+    };
+    use crate::drive::batch::GroveDbOpBatch;
+    use crate::error::identity::IdentityError;
+    use crate::error::Error;
+    use crate::fee::op::DriveOperation;
+    use grovedb::GroveDb;
+
+    #[test]
+    fn test_spent_asset_lock_outpoint_can_not_be_spent_twice() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let transaction = drive.grove.start_transaction();
+
+        let outpoint = [3u8; OUTPOINT_SIZE];
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        assert!(!drive
+            .has_asset_lock_outpoint_already_been_spent(
+                &outpoint,
+                Some(&transaction),
+                &mut drive_operations,
+            )
+            .expect("should check outpoint"));
+
+        let mut batch = GroveDbOpBatch::new();
+
+        drive
+            .add_spent_asset_lock_outpoint_operations(
+                &outpoint,
+                10,
+                2,
+                Some(&transaction),
+                &mut batch,
+                &mut drive_operations,
+            )
+            .expect("should add spent outpoint operations");
+
+        let result = drive.add_spent_asset_lock_outpoint_operations(
+            &outpoint,
+            10,
+            2,
+            Some(&transaction),
+            &mut batch,
+            &mut drive_operations,
+        );
+
+        assert!(matches!(
+            result,
+            Err(Error::Identity(
+                IdentityError::AssetLockOutpointAlreadySpent(_)
+            ))
+        ));
+
+        drive
+            .grove_apply_batch(batch, false, Some(&transaction))
+            .expect("should apply batch");
+
+        assert!(drive
+            .has_asset_lock_outpoint_already_been_spent(
+                &outpoint,
+                Some(&transaction),
+                &mut drive_operations,
+            )
+            .expect("should check outpoint"));
+
+        let mut fetch_operations: Vec<DriveOperation> = vec![];
+
+        let spent_outpoint = drive
+            .fetch_spent_asset_lock_outpoint(&outpoint, Some(&transaction), &mut fetch_operations)
+            .expect("should fetch spent outpoint");
+
+        // the replay protection read is charged
+        assert_eq!(fetch_operations.len(), 1);
+
+        assert_eq!(
+            spent_outpoint,
+            Some(SpentAssetLockOutpoint {
+                block_height: 10,
+                epoch_index: 2,
+            })
+        );
+
+        let mut batch = GroveDbOpBatch::new();
+
+        let result = drive.add_spent_asset_lock_outpoint_operations(
+            &outpoint,
+            11,
+            2,
+            Some(&transaction),
+            &mut batch,
+            &mut drive_operations,
+        );
+
+        assert!(matches!(
+            result,
+            Err(Error::Identity(
+                IdentityError::AssetLockOutpointAlreadySpent(_)
+            ))
+        ));
+
+        let proof = drive
+            .prove_spent_asset_lock_outpoint(&outpoint, Some(&transaction))
+            .expect("should prove spent outpoint");
+
+        let (_, proved_key_values) = GroveDb::verify_query(
+            proof.as_slice(),
+            &spent_asset_lock_outpoint_path_query(&outpoint),
+        )
+        .expect("should verify proof");
+
+        assert_eq!(proved_key_values.len(), 1);
+    }
+}
@@ -19,6 +19,10 @@ impl GroveDbOpBatch {
         self.operations.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
     pub fn push(&mut self, op: GroveDbOp) {
         self.operations.push(op);
     }
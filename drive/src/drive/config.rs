@@ -1,3 +1,4 @@
+use crate::fee::fee_schedule::{default_fee_schedules, FeeSchedules};
 use DriveEncoding::DriveProtobuf;
 
 pub const DEFAULT_GROVE_BATCHING_ENABLED: bool = true;
@@ -15,6 +16,7 @@ pub struct DriveConfig {
     pub has_raw_enabled: bool,
     pub default_genesis_time: Option<u64>,
     pub encoding: DriveEncoding,
+    pub fee_schedules: FeeSchedules,
 }
 
 impl Default for DriveConfig {
@@ -26,6 +28,7 @@ impl Default for DriveConfig {
             has_raw_enabled: DEFAULT_GROVE_HAS_RAW_ENABLED,
             default_genesis_time: None,
             encoding: DriveProtobuf,
+            fee_schedules: default_fee_schedules(),
         }
     }
 }
@@ -0,0 +1,683 @@
+use costs::CostContext;
+use dpp::data_contract::extra::encode_float;
+use dpp::data_contract::extra::DriveContractExt;
+use grovedb::query_result_type::QueryResultType::QueryKeyElementPairResultType;
+use grovedb::{Element, PathQuery, Query, SizedQuery, TransactionArg};
+
+use crate::common::encode::decode_float;
+use crate::drive::batch::GroveDbOpBatch;
+use crate::drive::contract::contract_root_path;
+use crate::drive::document::index_backfill::{index_backfills_key, index_backfills_path};
+use crate::drive::flags::StorageFlags;
+use crate::drive::object_size_info::KeyValueInfo::KeyRefRequest;
+use crate::drive::object_size_info::PathKeyElementInfo::PathFixedSizeKeyElement;
+use crate::drive::{Drive, RootTree};
+use crate::error::drive::DriveError;
+use crate::error::Error;
+use crate::fee::op::DriveOperation;
+use crate::fee::op::DriveOperation::CalculatedCostOperation;
+use crate::fee::result::FeeResult;
+
+/// The block time the contract was archived at is stored under this key of the contract
+/// root tree, next to the contract [0] and its documents [1]
+const CONTRACT_ARCHIVAL_TIME_KEY: [u8; 1] = [2];
+
+/// The contracts being deleted are kept in their own tree of the misc root tree
+pub(crate) fn contract_deletions_path() -> [&'static [u8]; 2] {
+    [Into::<&[u8; 1]>::into(RootTree::Misc), &[1]]
+}
+
+pub fn add_init_contract_deletions_structure_operations(batch: &mut GroveDbOpBatch) {
+    batch.add_insert_empty_tree(vec![vec![RootTree::Misc as u8]], vec![1]);
+}
+
+impl Drive {
+    /// Returns the block time the contract was archived at, if it was archived
+    pub fn fetch_contract_archival_time(
+        &self,
+        contract_id: &[u8],
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<Option<f64>, Error> {
+        let CostContext { value, cost } = self.grove.get(
+            contract_root_path(contract_id),
+            &CONTRACT_ARCHIVAL_TIME_KEY,
+            transaction,
+        );
+        drive_operations.push(CalculatedCostOperation(cost));
+        match value {
+            Ok(Element::Item(encoded_time, _)) => Ok(Some(decode_float(&encoded_time)?)),
+            Ok(_) => Err(Error::Drive(DriveError::CorruptedContractPath(
+                "contract archival time should be an item",
+            ))),
+            Err(grovedb::Error::PathKeyNotFound(_)) | Err(grovedb::Error::PathNotFound(_)) => {
+                Ok(None)
+            }
+            Err(e) => Err(Error::GroveDB(e)),
+        }
+    }
+
+    /// Archives the contract, it can then no longer be queried or have documents added.
+    /// The contract stays in the state until it is deleted.
+    pub fn archive_contract(
+        &self,
+        contract_id: [u8; 32],
+        block_time: f64,
+        apply: bool,
+        storage_flags: StorageFlags,
+        transaction: TransactionArg,
+    ) -> Result<FeeResult, Error> {
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+        let mut batch_operations: Vec<DriveOperation> = vec![];
+
+        let stored_contract = self.grove_get(
+            contract_root_path(&contract_id),
+            KeyRefRequest(&[0]),
+            transaction,
+            &mut batch_operations,
+        );
+        match stored_contract {
+            Ok(None)
+            | Err(Error::GroveDB(grovedb::Error::PathKeyNotFound(_)))
+            | Err(Error::GroveDB(grovedb::Error::PathNotFound(_))) => {
+                return Err(Error::Drive(DriveError::ArchivingContractThatDoesNotExist(
+                    "contract being archived does not exist",
+                )))
+            }
+            _ => {
+                stored_contract?;
+            }
+        }
+
+        if self
+            .fetch_contract_archival_time(&contract_id, transaction, &mut batch_operations)?
+            .is_some()
+        {
+            return Err(Error::Drive(DriveError::ContractArchived(
+                "contract is already archived",
+            )));
+        }
+
+        self.batch_insert(
+            PathFixedSizeKeyElement((
+                contract_root_path(&contract_id),
+                CONTRACT_ARCHIVAL_TIME_KEY.as_slice(),
+                Element::Item(encode_float(block_time)?, storage_flags.to_element_flags()),
+            )),
+            &mut batch_operations,
+        )?;
+
+        self.apply_batch_drive_operations(
+            apply,
+            transaction,
+            batch_operations,
+            &mut drive_operations,
+        )?;
+
+        self.calculate_fee(None, Some(drive_operations), false, transaction)
+    }
+
+    /// Returns the owner of the contract if the contract is being deleted
+    pub fn fetch_contract_deletion_owner_id(
+        &self,
+        contract_id: &[u8],
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let CostContext { value, cost } =
+            self.grove
+                .get(contract_deletions_path(), contract_id, transaction);
+        drive_operations.push(CalculatedCostOperation(cost));
+        match value {
+            Ok(Element::Item(owner_id, _)) => Ok(Some(owner_id)),
+            Ok(_) => Err(Error::Drive(DriveError::CorruptedContractDeletion(
+                "contract deletion should be an item",
+            ))),
+            Err(grovedb::Error::PathKeyNotFound(_)) | Err(grovedb::Error::PathNotFound(_)) => {
+                Ok(None)
+            }
+            Err(e) => Err(Error::GroveDB(e)),
+        }
+    }
+
+    /// Starts deleting an archived contract, its documents and index trees are then
+    /// removed a bounded number of elements at a time by `process_contract_deletions`
+    pub fn delete_contract(
+        &self,
+        contract_id: [u8; 32],
+        apply: bool,
+        transaction: TransactionArg,
+    ) -> Result<FeeResult, Error> {
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+        self.delete_contract_operations(contract_id, apply, transaction, &mut drive_operations)?;
+        self.calculate_fee(None, Some(drive_operations), false, transaction)
+    }
+
+    /// Adds the operations marking the contract as being deleted and deleting the
+    /// backfills of its added indices
+    pub fn delete_contract_operations(
+        &self,
+        contract_id: [u8; 32],
+        apply: bool,
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<(), Error> {
+        let mut batch_operations: Vec<DriveOperation> = vec![];
+
+        if self
+            .fetch_contract_deletion_owner_id(&contract_id, transaction, &mut batch_operations)?
+            .is_some()
+        {
+            return Err(Error::Drive(DriveError::ContractDeletionInProgress(
+                "contract is already being deleted",
+            )));
+        }
+
+        let contract = self
+            .get_contract(contract_id, transaction, &mut batch_operations)
+            .map_err(|e| match e {
+                Error::GroveDB(grovedb::Error::PathKeyNotFound(_))
+                | Error::GroveDB(grovedb::Error::PathNotFound(_)) => {
+                    Error::Drive(DriveError::DeletingContractThatDoesNotExist(
+                        "contract being deleted does not exist",
+                    ))
+                }
+                _ => e,
+            })?
+            .ok_or(Error::Drive(DriveError::DeletingContractThatDoesNotExist(
+                "contract being deleted does not exist",
+            )))?;
+
+        // documents can't be changed or queried while the contract is being deleted
+        if self
+            .fetch_contract_archival_time(&contract_id, transaction, &mut batch_operations)?
+            .is_none()
+        {
+            return Err(Error::Drive(DriveError::DeletingContractThatIsNotArchived(
+                "contracts must be archived before being deleted",
+            )));
+        }
+
+        // the backfills of indices added to the contract would otherwise outlive it,
+        // there is one at most per document type
+        let mut index_backfills_budget = u16::MAX;
+        let mut index_backfills_query = Query::new();
+        for document_type_name in contract.document_types().keys() {
+            index_backfills_query.insert_key(index_backfills_key(
+                contract.id.as_bytes(),
+                document_type_name.as_str(),
+            ));
+        }
+        self.add_delete_elements_operations(
+            index_backfills_path()
+                .into_iter()
+                .map(|key| key.to_vec())
+                .collect(),
+            index_backfills_query,
+            &mut index_backfills_budget,
+            transaction,
+            &mut batch_operations,
+        )?;
+
+        // the owner is kept to refund the storage of the contract to
+        self.batch_insert(
+            PathFixedSizeKeyElement((
+                contract_deletions_path(),
+                contract_id.as_slice(),
+                Element::Item(
+                    contract.owner_id.as_bytes().to_vec(),
+                    StorageFlags::default().to_element_flags(),
+                ),
+            )),
+            &mut batch_operations,
+        )?;
+
+        self.apply_batch_drive_operations(apply, transaction, batch_operations, drive_operations)
+    }
+
+    /// Deletes up to max_elements elements of the contracts being deleted, subtrees are
+    /// emptied before being deleted. This is meant to be called once per block, the
+    /// contract id can be used again once everything of the contract was deleted.
+    /// The removed storage is refunded to the owners of the contracts.
+    /// Returns the number of elements deleted and the fees of deleting them.
+    pub fn process_contract_deletions(
+        &self,
+        max_elements: u16,
+        transaction: TransactionArg,
+    ) -> Result<(u16, FeeResult), Error> {
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        let mut query = Query::new();
+        query.insert_all();
+        let path_query = PathQuery::new(
+            contract_deletions_path()
+                .into_iter()
+                .map(|key| key.to_vec())
+                .collect(),
+            SizedQuery::new(query, None, None),
+        );
+
+        let (contract_deletions, _) = self.grove_get_raw_path_query(
+            &path_query,
+            transaction,
+            QueryKeyElementPairResultType,
+            &mut drive_operations,
+        )?;
+
+        let mut remaining_elements = max_elements;
+        let mut fee_result = FeeResult::default();
+
+        for (contract_id, element) in contract_deletions.to_key_elements() {
+            if remaining_elements == 0 {
+                break;
+            }
+
+            let owner_id = match element {
+                Element::Item(owner_id, _) => owner_id,
+                _ => {
+                    return Err(Error::Drive(DriveError::CorruptedContractDeletion(
+                        "contract deletion should be an item",
+                    )))
+                }
+            };
+
+            let mut batch_operations: Vec<DriveOperation> = vec![];
+
+            let mut contract_query = Query::new();
+            contract_query.insert_key(contract_id.clone());
+            let deleted = self.add_delete_elements_operations(
+                vec![vec![RootTree::ContractDocuments as u8]],
+                contract_query,
+                &mut remaining_elements,
+                transaction,
+                &mut batch_operations,
+            )?;
+
+            if deleted {
+                self.batch_delete(
+                    contract_deletions_path(),
+                    contract_id.as_slice(),
+                    true, // not a tree, irrelevant
+                    transaction,
+                    &mut batch_operations,
+                )?;
+            }
+
+            let mut contract_operations: Vec<DriveOperation> = vec![];
+            self.apply_batch_drive_operations(
+                true,
+                transaction,
+                batch_operations,
+                &mut contract_operations,
+            )?;
+
+            if deleted {
+                if let Ok(contract_id) = <[u8; 32]>::try_from(contract_id.as_slice()) {
+                    self.cache
+                        .borrow()
+                        .cached_contracts
+                        .invalidate(&contract_id);
+                }
+            }
+
+            let contract_fee_result =
+                self.calculate_fee(None, Some(contract_operations), false, transaction)?;
+
+            // an owner that no longer exists can't be refunded, the credits then stay
+            // in the storage pools
+            let refunded_credits: u64 = contract_fee_result
+                .refunds_by_epoch
+                .iter()
+                .map(|(_, credits)| credits)
+                .sum();
+            let owner_id: [u8; 32] = owner_id.as_slice().try_into().map_err(|_| {
+                Error::Drive(DriveError::CorruptedContractDeletion(
+                    "contract deletion owner should be an identity id",
+                ))
+            })?;
+            let refunded = refunded_credits > 0
+                && self
+                    .fetch_identity_balance(owner_id, transaction)?
+                    .is_some();
+            if refunded {
+                let mut refund_batch = GroveDbOpBatch::new();
+                self.add_to_identity_balance_operations(
+                    &owner_id,
+                    refunded_credits,
+                    transaction,
+                    &mut refund_batch,
+                )?;
+                self.grove_apply_batch(refund_batch, false, transaction)?;
+            }
+
+            fee_result.storage_fee += contract_fee_result.storage_fee;
+            fee_result.processing_fee += contract_fee_result.processing_fee;
+            if refunded {
+                for (epoch, credits) in contract_fee_result.refunds_by_epoch {
+                    match fee_result
+                        .refunds_by_epoch
+                        .iter_mut()
+                        .find(|(refund_epoch, _)| *refund_epoch == epoch)
+                    {
+                        Some((_, refund_credits)) => *refund_credits += credits,
+                        None => fee_result.refunds_by_epoch.push((epoch, credits)),
+                    }
+                }
+            }
+        }
+
+        fee_result.refunds_by_epoch.sort_by_key(|(epoch, _)| *epoch);
+
+        let read_fee_result =
+            self.calculate_fee(None, Some(drive_operations), false, transaction)?;
+        fee_result.processing_fee += read_fee_result.processing_fee;
+
+        Ok((max_elements - remaining_elements, fee_result))
+    }
+
+    /// Deletes up to budget of the elements matched by the query along with everything
+    /// below them, subtrees are emptied before being deleted.
+    /// Returns whether all the matched elements were deleted.
+    fn add_delete_elements_operations(
+        &self,
+        path: Vec<Vec<u8>>,
+        query: Query,
+        budget: &mut u16,
+        transaction: TransactionArg,
+        batch_operations: &mut Vec<DriveOperation>,
+    ) -> Result<bool, Error> {
+        // one element more than the budget is read to know if elements are left
+        let path_query = PathQuery::new(
+            path.clone(),
+            SizedQuery::new(query, Some(budget.saturating_add(1)), None),
+        );
+
+        let query_result = self.grove_get_raw_path_query(
+            &path_query,
+            transaction,
+            QueryKeyElementPairResultType,
+            batch_operations,
+        );
+        let key_elements = match query_result {
+            Err(Error::GroveDB(grovedb::Error::PathKeyNotFound(_)))
+            | Err(Error::GroveDB(grovedb::Error::PathNotFound(_))) => return Ok(true),
+            _ => query_result?.0.to_key_elements(),
+        };
+
+        for (key, element) in key_elements {
+            if *budget == 0 {
+                return Ok(false);
+            }
+
+            if let Element::Tree(..) = element {
+                // the subtree is emptied first, its deletions come before the tree's own
+                let mut subtree_path = path.clone();
+                subtree_path.push(key.clone());
+                let mut subtree_query = Query::new();
+                subtree_query.insert_all();
+                let emptied = self.add_delete_elements_operations(
+                    subtree_path,
+                    subtree_query,
+                    budget,
+                    transaction,
+                    batch_operations,
+                )?;
+                if !emptied || *budget == 0 {
+                    return Ok(false);
+                }
+            }
+
+            // the removed storage is refunded to the epoch it was paid in
+            batch_operations.push(DriveOperation::for_removed_key_element(&key, &element)?);
+
+            self.batch_delete(
+                path.iter().map(|path_key| path_key.as_slice()),
+                key.as_slice(),
+                false,
+                transaction,
+                batch_operations,
+            )?;
+
+            *budget -= 1;
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use crate::common::helpers::identities::create_test_identity;
+    use crate::common::{json_document_to_cbor, value_to_cbor};
+    use crate::contract::{Contract, CreateRandomDocument};
+    use crate::drive::flags::StorageFlags;
+    use crate::drive::object_size_info::DocumentAndContractInfo;
+    use crate::drive::object_size_info::DocumentInfo::DocumentAndSerialization;
+    use crate::drive::Drive;
+    use crate::error::drive::DriveError;
+    use crate::error::query::QueryError;
+    use crate::error::Error;
+    use crate::query::DriveQuery;
+
+    use dpp::data_contract::extra::DriveContractExt;
+
+    #[test]
+    fn test_archive_and_delete_contract() {
+        let tmp_dir = TempDir::new().unwrap();
+        let drive: Drive = Drive::open(tmp_dir, None).expect("expected to open Drive successfully");
+
+        drive
+            .create_initial_state_structure(None)
+            .expect("expected to create root tree successfully");
+
+        let contract_cbor = json_document_to_cbor(
+            "tests/supporting_files/contract/family/family-contract.json",
+            Some(1),
+        );
+        let contract = <Contract as DriveContractExt>::from_cbor(&contract_cbor, None)
+            .expect("expected to deserialize the contract");
+        drive
+            .apply_contract(
+                &contract,
+                contract_cbor.clone(),
+                0f64,
+                true,
+                false,
+                StorageFlags { epoch: 0 },
+                None,
+            )
+            .expect("expected to apply contract successfully");
+
+        let document_type = contract
+            .document_type_for_name("person")
+            .expect("expected to get document type");
+        let documents = document_type.random_filled_documents(6, Some(5));
+        let add_document = |index: usize, storage_flags: &StorageFlags| {
+            let document = &documents[index];
+            drive.add_document_for_contract(
+                DocumentAndContractInfo {
+                    document_info: DocumentAndSerialization((
+                        document,
+                        document.to_cbor().as_slice(),
+                        storage_flags,
+                    )),
+                    contract: &contract,
+                    document_type,
+                    owner_id: None,
+                },
+                false,
+                0f64,
+                true,
+                false,
+                None,
+            )
+        };
+        // documents stored in two epochs
+        for index in 0..5 {
+            let storage_flags = StorageFlags {
+                epoch: (index % 2) as u16,
+            };
+            add_document(index, &storage_flags).expect("expected to insert a document");
+        }
+
+        let query_cbor = value_to_cbor(serde_json::json!({}), None);
+        let query = DriveQuery::from_cbor(query_cbor.as_slice(), &contract, document_type)
+            .expect("query should be built");
+        let (documents_found, _, _) = query
+            .execute_no_proof(&drive, None)
+            .expect("expected to query documents");
+        assert_eq!(documents_found.len(), 5);
+
+        drive
+            .archive_contract(
+                contract.id.buffer,
+                10f64,
+                true,
+                StorageFlags { epoch: 1 },
+                None,
+            )
+            .expect("expected to archive the contract");
+
+        assert_eq!(
+            drive
+                .fetch_contract_archival_time(contract.id.as_bytes(), None, &mut vec![])
+                .expect("expected to fetch the archival time"),
+            Some(10f64)
+        );
+        assert!(matches!(
+            query.execute_no_proof(&drive, None),
+            Err(Error::Query(QueryError::ContractArchived(_)))
+        ));
+        assert!(matches!(
+            add_document(5, &StorageFlags { epoch: 1 }),
+            Err(Error::Drive(DriveError::ContractArchived(_)))
+        ));
+        let archived_document = &documents[0];
+        assert!(matches!(
+            drive.update_document_for_contract(
+                archived_document,
+                archived_document.to_cbor().as_slice(),
+                &contract,
+                "person",
+                None,
+                11f64,
+                true,
+                false,
+                StorageFlags { epoch: 1 },
+                None,
+            ),
+            Err(Error::Drive(DriveError::ContractArchived(_)))
+        ));
+        assert!(matches!(
+            drive.delete_document_for_contract(
+                archived_document.id.as_slice(),
+                &contract,
+                "person",
+                None,
+                true,
+                false,
+                None,
+            ),
+            Err(Error::Drive(DriveError::ContractArchived(_)))
+        ));
+        assert!(matches!(
+            drive.archive_contract(
+                contract.id.buffer,
+                11f64,
+                true,
+                StorageFlags { epoch: 1 },
+                None
+            ),
+            Err(Error::Drive(DriveError::ContractArchived(_)))
+        ));
+
+        // the owner is refunded the storage of the deleted contract
+        create_test_identity(&drive, contract.owner_id.buffer, None);
+
+        drive
+            .delete_contract(contract.id.buffer, true, None)
+            .expect("expected to start deleting the contract");
+        assert!(matches!(
+            drive.delete_contract(contract.id.buffer, true, None),
+            Err(Error::Drive(DriveError::ContractDeletionInProgress(_)))
+        ));
+
+        // the contract is deleted a bounded number of elements per block
+        let (elements_deleted, first_fee_result) = drive
+            .process_contract_deletions(3, None)
+            .expect("expected to process contract deletions");
+        assert_eq!(elements_deleted, 3);
+        assert!(first_fee_result.processing_fee > 0);
+
+        // the contract id can't be used while the contract is being deleted
+        assert!(matches!(
+            drive.apply_contract(
+                &contract,
+                contract_cbor.clone(),
+                15f64,
+                true,
+                false,
+                StorageFlags { epoch: 2 },
+                None,
+            ),
+            Err(Error::Drive(DriveError::ContractDeletionInProgress(_)))
+        ));
+
+        let (elements_deleted, second_fee_result) = drive
+            .process_contract_deletions(u16::MAX, None)
+            .expect("expected to process contract deletions");
+        assert!(elements_deleted > 0);
+
+        let refunds: Vec<(u16, u64)> = first_fee_result
+            .refunds_by_epoch
+            .into_iter()
+            .chain(second_fee_result.refunds_by_epoch)
+            .collect();
+        assert!(refunds.iter().any(|(epoch, _)| *epoch == 0));
+        assert!(refunds.iter().any(|(epoch, _)| *epoch == 1));
+        assert_eq!(
+            drive
+                .fetch_identity_balance(contract.owner_id.buffer, None)
+                .expect("expected to fetch the owner balance"),
+            Some(refunds.iter().map(|(_, credits)| credits).sum())
+        );
+        assert!(drive
+            .fetch_contract_deletion_owner_id(contract.id.as_bytes(), None, &mut vec![])
+            .expect("expected to fetch the contract deletion")
+            .is_none());
+
+        let (elements_deleted, _) = drive
+            .process_contract_deletions(u16::MAX, None)
+            .expect("expected to process contract deletions");
+        assert_eq!(elements_deleted, 0);
+
+        assert!(drive
+            .get_contract(contract.id.buffer, None, &mut vec![])
+            .is_err());
+        assert!(matches!(
+            drive.delete_contract(contract.id.buffer, true, None),
+            Err(Error::Drive(DriveError::DeletingContractThatDoesNotExist(
+                _
+            )))
+        ));
+
+        // nothing of the contract is left, it can be created again
+        drive
+            .apply_contract(
+                &contract,
+                contract_cbor,
+                20f64,
+                true,
+                false,
+                StorageFlags { epoch: 2 },
+                None,
+            )
+            .expect("expected to apply contract again");
+        let (documents_found, _, _) = query
+            .execute_no_proof(&drive, None)
+            .expect("expected to query documents");
+        assert!(documents_found.is_empty());
+    }
+}
@@ -6,9 +6,11 @@ use std::sync::Arc;
 use costs::CostContext;
 use dpp::data_contract::extra::encode_float;
 use dpp::data_contract::extra::DriveContractExt;
-use grovedb::{Element, TransactionArg};
+use grovedb::query_result_type::QueryResultType::QueryKeyElementPairResultType;
+use grovedb::{Element, GroveDb, PathQuery, Query, SizedQuery, TransactionArg};
 
-use crate::contract::Contract;
+use crate::common::encode::decode_float;
+use crate::contract::{Contract, DocumentType, Index};
 use crate::drive::batch::GroveDbOpBatch;
 use crate::drive::flags::StorageFlags;
 use crate::drive::object_size_info::KeyInfo::{KeyRef, KeySize};
@@ -20,9 +22,13 @@ use crate::drive::object_size_info::PathKeyInfo::PathFixedSizeKeyRef;
 use crate::drive::{contract_documents_path, defaults, Drive, DriveCache, RootTree};
 use crate::error::drive::DriveError;
 use crate::error::Error;
-use crate::fee::calculate_fee;
 use crate::fee::op::DriveOperation;
 use crate::fee::op::DriveOperation::ContractFetch;
+use crate::fee::result::FeeResult;
+
+mod archive;
+
+pub use archive::add_init_contract_deletions_structure_operations;
 
 fn contract_root_path(contract_id: &[u8]) -> [&[u8]; 2] {
     [
@@ -51,6 +57,99 @@ fn contract_keeping_history_storage_time_reference_path(
     ]
 }
 
+fn contract_path_query(contract_id: &[u8]) -> PathQuery {
+    let mut query = Query::new();
+    query.insert_key(vec![0]);
+
+    // If the contract keeps history [0] is a tree, the latest version is the
+    // last one by encoded time
+    let mut history_query = Query::new_with_direction(false);
+    history_query.insert_range_after(vec![0]..);
+    query.set_subquery(history_query);
+
+    PathQuery::new(
+        contract_root_path(contract_id)
+            .into_iter()
+            .map(|key| key.to_vec())
+            .collect(),
+        SizedQuery::new(query, Some(1), None),
+    )
+}
+
+fn contract_history_path_query(
+    contract_id: &[u8],
+    start_time: f64,
+    limit: Option<u16>,
+) -> Result<PathQuery, Error> {
+    // [0] is a reference to the latest version and sorts before any encoded time
+    let mut query = Query::new();
+    query.insert_range_from(encode_float(start_time)?..);
+
+    Ok(PathQuery::new(
+        contract_keeping_history_storage_path(contract_id)
+            .into_iter()
+            .map(|key| key.to_vec())
+            .collect(),
+        SizedQuery::new(query, limit, None),
+    ))
+}
+
+fn contract_at_time_path_query(contract_id: &[u8], block_time: f64) -> Result<PathQuery, Error> {
+    let mut query = Query::new_with_direction(false);
+    query.insert_range_after_to_inclusive(vec![0]..=encode_float(block_time)?);
+
+    Ok(PathQuery::new(
+        contract_keeping_history_storage_path(contract_id)
+            .into_iter()
+            .map(|key| key.to_vec())
+            .collect(),
+        SizedQuery::new(query, Some(1), None),
+    ))
+}
+
+fn contract_from_proved_value(value: &[u8]) -> Result<Contract, Error> {
+    if let Element::Item(contract_bytes, _) = Element::deserialize(value).map_err(Error::GroveDB)? {
+        Ok(<Contract as DriveContractExt>::from_cbor(
+            &contract_bytes,
+            None,
+        )?)
+    } else {
+        Err(Error::Drive(DriveError::CorruptedContractPath(
+            "contract path did not refer to a contract element",
+        )))
+    }
+}
+
+fn contract_version_from_element(
+    encoded_time: &[u8],
+    element: Element,
+) -> Result<(f64, Contract), Error> {
+    if let Element::Item(contract_bytes, _) = element {
+        Ok((
+            decode_float(encoded_time)?,
+            <Contract as DriveContractExt>::from_cbor(&contract_bytes, None)?,
+        ))
+    } else {
+        Err(Error::Drive(DriveError::CorruptedContractPath(
+            "contract history should only have items",
+        )))
+    }
+}
+
+fn contract_versions_from_proved_key_values(
+    proved_key_values: Vec<(Vec<u8>, Vec<u8>)>,
+) -> Result<Vec<(f64, Contract)>, Error> {
+    proved_key_values
+        .iter()
+        .map(|(encoded_time, value)| {
+            Ok((
+                decode_float(encoded_time)?,
+                contract_from_proved_value(value)?,
+            ))
+        })
+        .collect()
+}
+
 pub fn add_init_contracts_structure_operations(batch: &mut GroveDbOpBatch) {
     batch.add_insert_empty_tree(vec![], vec![RootTree::ContractDocuments as u8]);
 }
@@ -67,14 +166,6 @@ impl Drive {
         let contract_root_path = contract_root_path(contract.id.as_bytes());
         if contract.keeps_history() {
             let element_flags = contract_element.get_flags().clone();
-            let storage_flags = StorageFlags::from_element_flags(element_flags.clone())?;
-
-            self.batch_insert_empty_tree(
-                contract_root_path,
-                KeyRef(&[0]),
-                Some(&storage_flags),
-                insert_operations,
-            )?;
             let encoded_time = encode_float(block_time)?;
             let contract_keeping_history_storage_path =
                 contract_keeping_history_storage_path(contract.id.as_bytes());
@@ -123,7 +214,7 @@ impl Drive {
         Ok(())
     }
 
-    fn insert_contract(
+    pub(crate) fn insert_contract(
         &self,
         contract_element: Element,
         contract: &Contract,
@@ -142,6 +233,16 @@ impl Drive {
             &mut batch_operations,
         )?;
 
+        if contract.keeps_history() {
+            // the versions are stored under [0], only created once so updates keep them
+            self.batch_insert_empty_tree(
+                contract_root_path(contract.id.as_bytes()),
+                KeyRef(&[0]),
+                Some(&storage_flags),
+                &mut batch_operations,
+            )?;
+        }
+
         self.add_contract_to_storage(
             contract_element,
             contract,
@@ -208,6 +309,93 @@ impl Drive {
         self.apply_batch_drive_operations(apply, transaction, batch_operations, drive_operations)
     }
 
+    /// Checks that the stored documents can still be read with the updated document type
+    /// and that its indices stay valid for them
+    fn validate_document_type_update(
+        &self,
+        contract: &Contract,
+        document_type: &DocumentType,
+        original_document_type: &DocumentType,
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<(), Error> {
+        for (property_name, original_property) in original_document_type.properties.iter() {
+            match document_type.properties.get(property_name) {
+                None => {
+                    return Err(Error::Drive(DriveError::RemovingDocumentTypeProperty(
+                        "contract can not remove a property of a document type",
+                    )))
+                }
+                Some(property) => {
+                    // whether a property is required is part of its serialization
+                    if property.document_type != original_property.document_type
+                        || property.required != original_property.required
+                    {
+                        return Err(Error::Drive(DriveError::ChangingDocumentTypePropertyType(
+                            "contract can not change the type of a property of a document type",
+                        )));
+                    }
+                }
+            }
+        }
+
+        for original_index in original_document_type.indices.iter() {
+            if document_type.indices.contains(original_index) {
+                continue;
+            }
+            let has_same_properties = |index: &Index| {
+                index
+                    .properties
+                    .iter()
+                    .map(|property| &property.name)
+                    .eq(original_index
+                        .properties
+                        .iter()
+                        .map(|property| &property.name))
+            };
+            if document_type.indices.iter().any(has_same_properties) {
+                return Err(Error::Drive(DriveError::ChangingDocumentTypeIndex(
+                    "contract can not change an index of a document type",
+                )));
+            }
+            if original_index.unique {
+                return Err(Error::Drive(DriveError::RemovingDocumentTypeUniqueIndex(
+                    "contract can not remove a unique index of a document type",
+                )));
+            }
+            return Err(Error::Drive(DriveError::RemovingDocumentTypeIndex(
+                "contract can not remove an index of a document type",
+            )));
+        }
+
+        let adds_required_property =
+            document_type
+                .properties
+                .iter()
+                .any(|(property_name, property)| {
+                    property.required
+                        && !original_document_type
+                            .properties
+                            .contains_key(property_name)
+                });
+        if adds_required_property
+            && self.document_type_has_documents(
+                contract,
+                original_document_type,
+                transaction,
+                drive_operations,
+            )?
+        {
+            return Err(Error::Drive(
+                DriveError::AddingRequiredPropertyToDocumentTypeWithDocuments(
+                    "stored documents do not have the added required property",
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
     fn update_contract(
         &self,
         contract_element: Element,
@@ -270,6 +458,21 @@ impl Drive {
 
         let storage_flags = StorageFlags::from_element_flags(element_flags)?;
 
+        for (type_key, original_document_type) in original_contract.document_types() {
+            if !contract.document_types().contains_key(type_key)
+                && self.document_type_has_documents(
+                    original_contract,
+                    original_document_type,
+                    transaction,
+                    &mut batch_operations,
+                )?
+            {
+                return Err(Error::Drive(DriveError::RemovingDocumentTypeWithDocuments(
+                    "contract can not remove a document type that has documents",
+                )));
+            }
+        }
+
         let contract_documents_path = contract_documents_path(contract.id.as_bytes());
         for (type_key, document_type) in contract.document_types() {
             let original_document_type = &original_contract.document_types().get(type_key);
@@ -287,6 +490,14 @@ impl Drive {
                     )));
                 }
 
+                self.validate_document_type_update(
+                    contract,
+                    document_type,
+                    original_document_type,
+                    transaction,
+                    &mut batch_operations,
+                )?;
+
                 let type_path = [
                     contract_documents_path[0],
                     contract_documents_path[1],
@@ -310,6 +521,21 @@ impl Drive {
                         index_cache.insert(index_bytes);
                     }
                 }
+
+                // documents stored before an index was added are written to it over time
+                let added_indices: Vec<&Index> = document_type
+                    .indices
+                    .iter()
+                    .filter(|index| !original_document_type.indices.contains(index))
+                    .collect();
+                self.add_index_backfills_operations(
+                    contract,
+                    document_type,
+                    added_indices,
+                    &storage_flags,
+                    transaction,
+                    &mut batch_operations,
+                )?;
             } else {
                 // We can just insert this directly because the original document type already exists
                 self.batch_insert_empty_tree(
@@ -361,9 +587,10 @@ impl Drive {
         contract_id: Option<[u8; 32]>,
         block_time: f64,
         apply: bool,
+        with_fee_breakdown: bool,
         storage_flags: StorageFlags,
         transaction: TransactionArg,
-    ) -> Result<(i64, u64), Error> {
+    ) -> Result<FeeResult, Error> {
         // first we need to deserialize the contract
         let contract = <Contract as DriveContractExt>::from_cbor(&contract_cbor, contract_id)?;
 
@@ -372,6 +599,7 @@ impl Drive {
             contract_cbor,
             block_time,
             apply,
+            with_fee_breakdown,
             storage_flags,
             transaction,
         )
@@ -419,7 +647,16 @@ impl Drive {
         let CostContext { value, cost: _ } =
             self.grove
                 .get(contract_root_path(&contract_id), &[0], transaction);
-        let stored_element = value.map_err(Error::GroveDB)?;
+        let mut stored_element = value.map_err(Error::GroveDB)?;
+        if let Element::Tree(..) = stored_element {
+            // the contract keeps history, [0] refers to its latest version
+            let CostContext { value, cost: _ } = self.grove.get(
+                contract_keeping_history_storage_path(&contract_id),
+                &[0],
+                transaction,
+            );
+            stored_element = value.map_err(Error::GroveDB)?;
+        }
         if let Element::Item(stored_contract_bytes, element_flag) = stored_element {
             let contract = Arc::new(<Contract as DriveContractExt>::from_cbor(
                 &stored_contract_bytes,
@@ -438,17 +675,201 @@ impl Drive {
         }
     }
 
+    pub fn prove_contract(
+        &self,
+        contract_id: [u8; 32],
+        transaction: TransactionArg,
+    ) -> Result<Vec<u8>, Error> {
+        let path_query = contract_path_query(&contract_id);
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.grove_get_proved_path_query(&path_query, transaction, &mut drive_operations)
+    }
+
+    pub fn verify_contract(
+        proof: &[u8],
+        contract_id: [u8; 32],
+    ) -> Result<([u8; 32], Option<Contract>), Error> {
+        let (root_hash, proved_key_values) =
+            GroveDb::verify_query(proof, &contract_path_query(&contract_id))
+                .map_err(Error::GroveDB)?;
+
+        let contract = proved_key_values
+            .first()
+            .map(|(_, value)| contract_from_proved_value(value))
+            .transpose()?;
+
+        Ok((root_hash, contract))
+    }
+
+    fn fetch_contract_versions_for_path_query(
+        &self,
+        path_query: &PathQuery,
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<Vec<(f64, Contract)>, Error> {
+        let query_result = self.grove_get_raw_path_query(
+            path_query,
+            transaction,
+            QueryKeyElementPairResultType,
+            drive_operations,
+        );
+        match query_result {
+            Err(Error::GroveDB(grovedb::Error::PathKeyNotFound(_)))
+            | Err(Error::GroveDB(grovedb::Error::PathNotFound(_))) => Ok(vec![]),
+            _ => {
+                let (key_elements, _) = query_result?;
+                key_elements
+                    .to_key_elements()
+                    .into_iter()
+                    .map(|(encoded_time, element)| {
+                        contract_version_from_element(&encoded_time, element)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Returns the versions of a contract that keeps history stored from the start time,
+    /// oldest first, with the block time of each version
+    pub fn fetch_contract_history(
+        &self,
+        contract_id: [u8; 32],
+        start_time: f64,
+        limit: Option<u16>,
+        transaction: TransactionArg,
+    ) -> Result<(Vec<(f64, Contract)>, u64), Error> {
+        let path_query = contract_history_path_query(&contract_id, start_time, limit)?;
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        let contracts = self.fetch_contract_versions_for_path_query(
+            &path_query,
+            transaction,
+            &mut drive_operations,
+        )?;
+
+        let cost = self
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
+
+        Ok((contracts, cost))
+    }
+
+    pub fn prove_contract_history(
+        &self,
+        contract_id: [u8; 32],
+        start_time: f64,
+        limit: Option<u16>,
+        transaction: TransactionArg,
+    ) -> Result<Vec<u8>, Error> {
+        let path_query = contract_history_path_query(&contract_id, start_time, limit)?;
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.grove_get_proved_path_query(&path_query, transaction, &mut drive_operations)
+    }
+
+    pub fn verify_contract_history(
+        proof: &[u8],
+        contract_id: [u8; 32],
+        start_time: f64,
+        limit: Option<u16>,
+    ) -> Result<([u8; 32], Vec<(f64, Contract)>), Error> {
+        let path_query = contract_history_path_query(&contract_id, start_time, limit)?;
+
+        let (root_hash, proved_key_values) =
+            GroveDb::verify_query(proof, &path_query).map_err(Error::GroveDB)?;
+
+        Ok((
+            root_hash,
+            contract_versions_from_proved_key_values(proved_key_values)?,
+        ))
+    }
+
+    /// Returns the version of a contract that keeps history which was active at the
+    /// block time, along with the block time it was stored at
+    pub fn fetch_contract_at_time(
+        &self,
+        contract_id: [u8; 32],
+        block_time: f64,
+        transaction: TransactionArg,
+    ) -> Result<(Option<(f64, Contract)>, u64), Error> {
+        let path_query = contract_at_time_path_query(&contract_id, block_time)?;
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        let contract = self
+            .fetch_contract_versions_for_path_query(
+                &path_query,
+                transaction,
+                &mut drive_operations,
+            )?
+            .pop();
+
+        let cost = self
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
+
+        Ok((contract, cost))
+    }
+
+    pub fn prove_contract_at_time(
+        &self,
+        contract_id: [u8; 32],
+        block_time: f64,
+        transaction: TransactionArg,
+    ) -> Result<Vec<u8>, Error> {
+        let path_query = contract_at_time_path_query(&contract_id, block_time)?;
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.grove_get_proved_path_query(&path_query, transaction, &mut drive_operations)
+    }
+
+    pub fn verify_contract_at_time(
+        proof: &[u8],
+        contract_id: [u8; 32],
+        block_time: f64,
+    ) -> Result<([u8; 32], Option<(f64, Contract)>), Error> {
+        let path_query = contract_at_time_path_query(&contract_id, block_time)?;
+
+        let (root_hash, proved_key_values) =
+            GroveDb::verify_query(proof, &path_query).map_err(Error::GroveDB)?;
+
+        Ok((
+            root_hash,
+            contract_versions_from_proved_key_values(proved_key_values)?.pop(),
+        ))
+    }
+
     pub fn apply_contract(
         &self,
         contract: &Contract,
         contract_serialization: Vec<u8>,
         block_time: f64,
         apply: bool,
+        with_fee_breakdown: bool,
         storage_flags: StorageFlags,
         transaction: TransactionArg,
-    ) -> Result<(i64, u64), Error> {
+    ) -> Result<FeeResult, Error> {
         let mut drive_operations: Vec<DriveOperation> = vec![];
 
+        // the contract id can't be used before everything of the deleted contract is gone
+        if self
+            .fetch_contract_deletion_owner_id(
+                contract.id.as_bytes(),
+                transaction,
+                &mut drive_operations,
+            )?
+            .is_some()
+        {
+            return Err(Error::Drive(DriveError::ContractDeletionInProgress(
+                "the contract is being deleted",
+            )));
+        }
+
         // overlying structure
         let mut already_exists = false;
         let mut original_contract_stored_data = vec![];
@@ -466,6 +887,23 @@ impl Drive {
                         original_contract_stored_data = stored_contract_bytes;
                     }
                 }
+                Element::Tree(..) => {
+                    // the contract keeps history, [0] refers to its latest version
+                    if let Some(Element::Item(stored_contract_bytes, _)) = self.grove_get(
+                        contract_keeping_history_storage_path(contract.id.as_bytes()),
+                        KeyRefRequest(&[0]),
+                        transaction,
+                        &mut drive_operations,
+                    )? {
+                        if contract_serialization != stored_contract_bytes {
+                            original_contract_stored_data = stored_contract_bytes;
+                        }
+                    } else {
+                        return Err(Error::Drive(DriveError::CorruptedContractPath(
+                            "contract history did not refer to a contract element",
+                        )));
+                    }
+                }
                 _ => {
                     already_exists = false;
                 }
@@ -476,6 +914,18 @@ impl Drive {
             Element::Item(contract_serialization, storage_flags.to_element_flags());
 
         if already_exists {
+            if self
+                .fetch_contract_archival_time(
+                    contract.id.as_bytes(),
+                    transaction,
+                    &mut drive_operations,
+                )?
+                .is_some()
+            {
+                return Err(Error::Drive(DriveError::ContractArchived(
+                    "archived contracts can not be updated",
+                )));
+            }
             if !original_contract_stored_data.is_empty() {
                 let original_contract = <Contract as DriveContractExt>::from_cbor(
                     &original_contract_stored_data,
@@ -502,8 +952,12 @@ impl Drive {
                 &mut drive_operations,
             )?;
         }
-        let fees = calculate_fee(None, Some(drive_operations))?;
-        Ok(fees)
+        self.calculate_fee(
+            None,
+            Some(drive_operations),
+            with_fee_breakdown,
+            transaction,
+        )
     }
 }
 
@@ -515,7 +969,7 @@ mod tests {
     use tempfile::TempDir;
 
     use super::*;
-    use crate::common::json_document_to_cbor;
+    use crate::common::{json_document_to_cbor, value_to_cbor};
     use crate::contract::Contract;
     use crate::drive::flags::StorageFlags;
     use crate::drive::object_size_info::{DocumentAndContractInfo, DocumentInfo};
@@ -541,6 +995,7 @@ mod tests {
                 contract_cbor.clone(),
                 0f64,
                 true,
+                false,
                 StorageFlags { epoch: 0 },
                 None,
             )
@@ -569,6 +1024,7 @@ mod tests {
                 contract_cbor.clone(),
                 0f64,
                 true,
+                false,
                 StorageFlags { epoch: 0 },
                 None,
             )
@@ -594,6 +1050,7 @@ mod tests {
                 None,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -607,6 +1064,7 @@ mod tests {
                 None,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -645,6 +1103,7 @@ mod tests {
                 false,
                 0f64,
                 false,
+                false,
                 None,
             )
             .expect("expected to insert a document successfully");
@@ -682,8 +1141,303 @@ mod tests {
                 false,
                 0f64,
                 false,
+                false,
+                None,
+            )
+            .expect("expected to insert a document successfully");
+    }
+
+    #[test]
+    fn test_prove_and_verify_contract() {
+        let (drive, contract, _contract_cbor) = setup_reference_contract();
+
+        let root_hash = drive
+            .grove
+            .root_hash(None)
+            .unwrap()
+            .expect("should get root hash");
+
+        let proof = drive
+            .prove_contract(contract.id.buffer, None)
+            .expect("should prove contract");
+
+        let (proof_root_hash, proved_contract) =
+            Drive::verify_contract(proof.as_slice(), contract.id.buffer)
+                .expect("should verify contract");
+
+        assert_eq!(proof_root_hash, root_hash);
+        assert_eq!(
+            proved_contract.expect("contract should be proved").id,
+            contract.id
+        );
+    }
+
+    #[test]
+    fn test_fetch_and_prove_contract_history() {
+        let tmp_dir = TempDir::new().unwrap();
+        let drive: Drive = Drive::open(tmp_dir, None).expect("expected to open Drive successfully");
+
+        drive
+            .create_initial_state_structure(None)
+            .expect("expected to create root tree successfully");
+
+        let contract_path =
+            "tests/supporting_files/contract/family/family-contract-keeps-history.json";
+        let mut contract_value: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(contract_path).expect("expected to read the contract"),
+        )
+        .expect("expected a valid json");
+
+        for (block_time, version) in [(0f64, 1), (1000f64, 2)] {
+            contract_value["version"] = serde_json::json!(version);
+            let contract_cbor = value_to_cbor(contract_value.clone(), Some(1));
+            let contract = <Contract as DriveContractExt>::from_cbor(&contract_cbor, None)
+                .expect("expected to deserialize the contract");
+            drive
+                .apply_contract(
+                    &contract,
+                    contract_cbor,
+                    block_time,
+                    true,
+                    false,
+                    StorageFlags { epoch: 0 },
+                    None,
+                )
+                .expect("expected to apply contract successfully");
+        }
+
+        let contract_id = bs58::decode("94zNLp7A1ZcYG3Egqf2YmQk4DQr9P8D543GwXyCJRz4")
+            .into_vec()
+            .expect("expected to decode the contract id")
+            .try_into()
+            .expect("expected a 32 byte contract id");
+
+        let (contracts, _) = drive
+            .fetch_contract_history(contract_id, 0f64, None, None)
+            .expect("expected to fetch the contract history");
+        let versions: Vec<(f64, u32)> = contracts
+            .iter()
+            .map(|(block_time, contract)| (*block_time, contract.version))
+            .collect();
+        assert_eq!(versions, vec![(0f64, 1), (1000f64, 2)]);
+
+        let (contracts_after_start, _) = drive
+            .fetch_contract_history(contract_id, 500f64, None, None)
+            .expect("expected to fetch the contract history");
+        assert_eq!(contracts_after_start.len(), 1);
+
+        let (contract_at_time, _) = drive
+            .fetch_contract_at_time(contract_id, 500f64, None)
+            .expect("expected to fetch the contract at the block time");
+        let (block_time, contract) = contract_at_time.expect("expected a contract version");
+        assert_eq!(block_time, 0f64);
+        assert_eq!(contract.version, 1);
+
+        let root_hash = drive
+            .grove
+            .root_hash(None)
+            .unwrap()
+            .expect("should get root hash");
+
+        let proof = drive
+            .prove_contract_history(contract_id, 0f64, None, None)
+            .expect("should prove the contract history");
+        let (proof_root_hash, proved_contracts) =
+            Drive::verify_contract_history(proof.as_slice(), contract_id, 0f64, None)
+                .expect("should verify the contract history");
+        assert_eq!(proof_root_hash, root_hash);
+        assert_eq!(proved_contracts.len(), 2);
+
+        let proof = drive
+            .prove_contract_at_time(contract_id, 500f64, None)
+            .expect("should prove the contract at the block time");
+        let (proof_root_hash, proved_contract) =
+            Drive::verify_contract_at_time(proof.as_slice(), contract_id, 500f64)
+                .expect("should verify the contract at the block time");
+        assert_eq!(proof_root_hash, root_hash);
+        assert_eq!(
+            proved_contract.map(|(block_time, contract)| (block_time, contract.version)),
+            Some((0f64, 1))
+        );
+    }
+
+    fn family_contract_cbor(person_document_type: serde_json::Value) -> Vec<u8> {
+        value_to_cbor(
+            serde_json::json!({
+                "$id": "94zNLp7A1ZcYG3Egqf2YmQk4DQr9P8D543GwXyCJRz4",
+                "ownerId": "AcYUCSvAmUwryNsQqkqqD1o3BnFuzepGtR3Mhh2swLk6",
+                "$schema": "http://json-schema.org/draft-07/schema",
+                "version": 1,
+                "documents": {
+                    "person": person_document_type
+                }
+            }),
+            Some(1),
+        )
+    }
+
+    #[test]
+    fn test_update_contract_with_incompatible_document_type() {
+        let tmp_dir = TempDir::new().unwrap();
+        let drive: Drive = Drive::open(tmp_dir, None).expect("expected to open Drive successfully");
+
+        drive
+            .create_initial_state_structure(None)
+            .expect("expected to create root tree successfully");
+
+        let person = serde_json::json!({
+            "indices": [
+                {"properties": [{"firstName": "asc"}]},
+                {"properties": [{"firstName": "asc"}, {"lastName": "asc"}], "unique": true}
+            ],
+            "properties": {
+                "firstName": {"type": "string", "maxLength": 50},
+                "lastName": {"type": "string", "maxLength": 50},
+                "age": {"type": "integer"}
+            },
+            "additionalProperties": false
+        });
+
+        let contract_cbor = family_contract_cbor(person.clone());
+        let contract = <Contract as DriveContractExt>::from_cbor(&contract_cbor, None)
+            .expect("expected to deserialize the contract");
+        drive
+            .apply_contract_cbor(
+                contract_cbor,
+                None,
+                0f64,
+                true,
+                false,
+                StorageFlags::default(),
+                None,
+            )
+            .expect("expected to apply contract successfully");
+
+        let document_type = contract
+            .document_type_for_name("person")
+            .expect("expected to get document type");
+        let document = document_type.random_filled_document(Some(5));
+        let storage_flags = StorageFlags { epoch: 0 };
+        drive
+            .add_document_for_contract(
+                DocumentAndContractInfo {
+                    document_info: DocumentInfo::DocumentAndSerialization((
+                        &document,
+                        document.to_cbor().as_slice(),
+                        &storage_flags,
+                    )),
+                    contract: &contract,
+                    document_type,
+                    owner_id: None,
+                },
+                false,
+                0f64,
+                true,
+                false,
                 None,
             )
             .expect("expected to insert a document successfully");
+
+        let apply_person = |person: serde_json::Value| {
+            drive.apply_contract_cbor(
+                family_contract_cbor(person),
+                None,
+                0f64,
+                true,
+                false,
+                StorageFlags::default(),
+                None,
+            )
+        };
+
+        let mut removed_property = person.clone();
+        removed_property["properties"]
+            .as_object_mut()
+            .unwrap()
+            .remove("age");
+        assert!(matches!(
+            apply_person(removed_property),
+            Err(Error::Drive(DriveError::RemovingDocumentTypeProperty(_)))
+        ));
+
+        let mut retyped_property = person.clone();
+        retyped_property["properties"]["age"] =
+            serde_json::json!({"type": "string", "maxLength": 50});
+        assert!(matches!(
+            apply_person(retyped_property),
+            Err(Error::Drive(DriveError::ChangingDocumentTypePropertyType(
+                _
+            )))
+        ));
+
+        let mut added_required_property = person.clone();
+        added_required_property["properties"]["nickname"] =
+            serde_json::json!({"type": "string", "maxLength": 50});
+        added_required_property["required"] = serde_json::json!(["nickname"]);
+        assert!(matches!(
+            apply_person(added_required_property),
+            Err(Error::Drive(
+                DriveError::AddingRequiredPropertyToDocumentTypeWithDocuments(_)
+            ))
+        ));
+
+        let mut changed_index = person.clone();
+        changed_index["indices"][0] = serde_json::json!({"properties": [{"firstName": "desc"}]});
+        assert!(matches!(
+            apply_person(changed_index),
+            Err(Error::Drive(DriveError::ChangingDocumentTypeIndex(_)))
+        ));
+
+        let mut removed_unique_index = person.clone();
+        removed_unique_index["indices"]
+            .as_array_mut()
+            .unwrap()
+            .remove(1);
+        assert!(matches!(
+            apply_person(removed_unique_index),
+            Err(Error::Drive(DriveError::RemovingDocumentTypeUniqueIndex(_)))
+        ));
+
+        let mut removed_index = person.clone();
+        removed_index["indices"].as_array_mut().unwrap().remove(0);
+        assert!(matches!(
+            apply_person(removed_index),
+            Err(Error::Drive(DriveError::RemovingDocumentTypeIndex(_)))
+        ));
+
+        let mut pet = person.clone();
+        pet["indices"] = serde_json::json!([]);
+        let removed_document_type = value_to_cbor(
+            serde_json::json!({
+                "$id": "94zNLp7A1ZcYG3Egqf2YmQk4DQr9P8D543GwXyCJRz4",
+                "ownerId": "AcYUCSvAmUwryNsQqkqqD1o3BnFuzepGtR3Mhh2swLk6",
+                "$schema": "http://json-schema.org/draft-07/schema",
+                "version": 1,
+                "documents": {
+                    "pet": pet
+                }
+            }),
+            Some(1),
+        );
+        assert!(matches!(
+            drive.apply_contract_cbor(
+                removed_document_type,
+                None,
+                0f64,
+                true,
+                false,
+                StorageFlags::default(),
+                None,
+            ),
+            Err(Error::Drive(DriveError::RemovingDocumentTypeWithDocuments(
+                _
+            )))
+        ));
+
+        let mut added_optional_property = person;
+        added_optional_property["properties"]["nickname"] =
+            serde_json::json!({"type": "string", "maxLength": 50});
+        apply_person(added_optional_property)
+            .expect("expected to add an optional property to the document type");
     }
 }
@@ -11,3 +11,5 @@ pub const DEFAULT_FLOAT_SIZE: usize = 8;
 pub const EMPTY_TREE_STORAGE_SIZE: usize = 33;
 pub const MAX_INDEX_SIZE: usize = 255;
 pub const STORAGE_FLAGS_SIZE: usize = 2;
+pub const INDEX_BACKFILL_DOCUMENTS_PER_BLOCK: u16 = 1000;
+pub const CONTRACT_DELETION_ELEMENTS_PER_BLOCK: u16 = 1000;
@@ -1,15 +1,20 @@
 use grovedb::{Element, TransactionArg};
 
 use crate::contract::document::Document;
-use crate::contract::Contract;
-use crate::drive::defaults::CONTRACT_DOCUMENTS_PATH_HEIGHT;
+use crate::contract::{Contract, DocumentType};
+use crate::drive::defaults::{
+    BASE_CONTRACT_DOCUMENTS_PATH, BASE_CONTRACT_DOCUMENTS_PRIMARY_KEY_PATH,
+    CONTRACT_DOCUMENTS_PATH_HEIGHT, DEFAULT_HASH_SIZE, STORAGE_FLAGS_SIZE,
+};
 use crate::drive::document::{contract_document_type_path, contract_documents_primary_key_path};
+use crate::drive::object_size_info::DocumentInfo::DocumentSize;
 use crate::drive::object_size_info::KeyValueInfo::KeyRefRequest;
 use crate::drive::Drive;
 use crate::error::drive::DriveError;
 use crate::error::Error;
-use crate::fee::calculate_fee;
-use crate::fee::op::DriveOperation;
+use crate::fee::op::DriveOperation::CostCalculationDeleteOperation;
+use crate::fee::op::{DriveOperation, SizesOfDeleteOperation};
+use crate::fee::result::FeeResult;
 use dpp::data_contract::extra::DriveContractExt;
 
 impl Drive {
@@ -20,8 +25,9 @@ impl Drive {
         document_type_name: &str,
         owner_id: Option<&[u8]>,
         apply: bool,
+        with_fee_breakdown: bool,
         transaction: TransactionArg,
-    ) -> Result<(i64, u64), Error> {
+    ) -> Result<FeeResult, Error> {
         let mut drive_operations: Vec<DriveOperation> = vec![];
         self.delete_document_for_contract_operations(
             document_id,
@@ -32,8 +38,12 @@ impl Drive {
             transaction,
             &mut drive_operations,
         )?;
-        let fees = calculate_fee(None, Some(drive_operations))?;
-        Ok(fees)
+        self.calculate_fee(
+            None,
+            Some(drive_operations),
+            with_fee_breakdown,
+            transaction,
+        )
     }
 
     pub fn delete_document_for_contract_cbor(
@@ -43,8 +53,9 @@ impl Drive {
         document_type_name: &str,
         owner_id: Option<&[u8]>,
         apply: bool,
+        with_fee_breakdown: bool,
         transaction: TransactionArg,
-    ) -> Result<(i64, u64), Error> {
+    ) -> Result<FeeResult, Error> {
         let contract = <Contract as DriveContractExt>::from_cbor(contract_cbor, None)?;
         self.delete_document_for_contract(
             document_id,
@@ -52,6 +63,7 @@ impl Drive {
             document_type_name,
             owner_id,
             apply,
+            with_fee_breakdown,
             transaction,
         )
     }
@@ -75,6 +87,15 @@ impl Drive {
             )));
         }
 
+        if self
+            .fetch_contract_archival_time(contract.id.as_bytes(), transaction, drive_operations)?
+            .is_some()
+        {
+            return Err(Error::Drive(DriveError::ContractArchived(
+                "documents of archived contracts can not be deleted",
+            )));
+        }
+
         // first we need to construct the path for documents on the contract
         // the path is
         //  * Document and Contract root tree
@@ -97,7 +118,15 @@ impl Drive {
             )));
         }
 
-        let document_bytes: Vec<u8> = match document_element.unwrap() {
+        let document_element = document_element.unwrap();
+
+        // the removed storage is refunded to the epoch it was paid in
+        batch_operations.push(DriveOperation::for_removed_key_element(
+            document_id,
+            &document_element,
+        )?);
+
+        let document_bytes: Vec<u8> = match document_element {
             Element::Item(data, _) => data,
             _ => todo!(), // TODO: how should this be handled, possibility that document might not be in storage
         };
@@ -116,9 +145,17 @@ impl Drive {
         let contract_document_type_path =
             contract_document_type_path(contract.id.as_bytes(), document_type_name);
 
+        let indices = self.document_type_indices_for_document(
+            contract,
+            document_type,
+            Some(document_id),
+            transaction,
+            &mut batch_operations,
+        )?;
+
         // fourth we need delete all references to the document
         // to do this we need to go through each index
-        for index in &document_type.indices {
+        for index in indices {
             // at this point the contract path is to the contract documents
             // for each index the top index component will already have been added
             // when the contract itself was created
@@ -172,36 +209,104 @@ impl Drive {
 
             // unique indexes will be stored under key "0"
             // non unique indices should have a tree at key "0" that has all elements based off of primary key
-            if !index.unique {
+            let reference_key: &[u8] = if !index.unique {
                 index_path.push(vec![0]);
-
-                let index_path_slices: Vec<&[u8]> =
-                    index_path.iter().map(|x| x.as_slice()).collect();
-
-                // here we should return an error if the element already exists
-                self.batch_delete_up_tree_while_empty(
-                    index_path_slices,
-                    document_id,
-                    Some(CONTRACT_DOCUMENTS_PATH_HEIGHT),
-                    transaction,
-                    &mut batch_operations,
-                )?;
+                document_id
             } else {
-                let index_path_slices: Vec<&[u8]> =
-                    index_path.iter().map(|x| x.as_slice()).collect();
-
-                // here we should return an error if the element already exists
-                self.batch_delete_up_tree_while_empty(
-                    index_path_slices,
-                    &[0],
-                    Some(CONTRACT_DOCUMENTS_PATH_HEIGHT),
-                    transaction,
-                    &mut batch_operations,
-                )?;
-            }
+                &[0]
+            };
+
+            let index_path_slices: Vec<&[u8]> = index_path.iter().map(|x| x.as_slice()).collect();
+
+            // the reference keeps the storage flags of the document version that added it
+            self.add_storage_removal_operation(
+                index_path_slices.clone(),
+                reference_key,
+                transaction,
+                &mut batch_operations,
+            )?;
+
+            // here we should return an error if the element already exists
+            self.batch_delete_up_tree_while_empty(
+                index_path_slices,
+                reference_key,
+                Some(CONTRACT_DOCUMENTS_PATH_HEIGHT),
+                transaction,
+                &mut batch_operations,
+            )?;
         }
         self.apply_batch_drive_operations(apply, transaction, batch_operations, drive_operations)
     }
+
+    /// Adds the costs of deleting the largest document of the document type, nothing is
+    /// read from the state
+    pub(crate) fn add_worst_case_delete_document_operations(
+        &self,
+        document_type: &DocumentType,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<(), Error> {
+        if !document_type.documents_mutable {
+            return Err(Error::Drive(DriveError::UpdatingReadOnlyImmutableDocument(
+                "documents for this contract are not mutable",
+            )));
+        }
+
+        let document_info = DocumentSize(document_type.max_size());
+        // references are estimated as large as the document, like on insertion
+        let element_max_size =
+            Element::required_item_space(document_type.max_size(), STORAGE_FLAGS_SIZE);
+
+        // the document is fetched for its refund and its index values
+        let primary_key_path_size =
+            BASE_CONTRACT_DOCUMENTS_PRIMARY_KEY_PATH + document_type.name.len();
+        drive_operations.push(DriveOperation::for_query_path_key_value_size(
+            primary_key_path_size as u32,
+            DEFAULT_HASH_SIZE as u32,
+            element_max_size as u32,
+        ));
+        drive_operations.push(CostCalculationDeleteOperation(
+            SizesOfDeleteOperation::for_key_value_size(
+                primary_key_path_size as u32,
+                DEFAULT_HASH_SIZE as u16,
+                element_max_size as u32,
+                1,
+            ),
+        ));
+
+        // indices still being backfilled might hold the document as well
+        for index in document_type.indices.iter() {
+            let mut index_path_size = BASE_CONTRACT_DOCUMENTS_PATH + document_type.name.len();
+            for index_property in index.properties.iter() {
+                let field_max_size = document_info
+                    .get_raw_for_document_type(&index_property.name, document_type, None)?
+                    .map(|key_info| key_info.len())
+                    .unwrap_or_default();
+                index_path_size += index_property.name.len() + field_max_size;
+            }
+
+            // references of non unique indices, and of unique ones with null values, are
+            // keyed by the document id in a tree at [0]
+            index_path_size += 1;
+            // each property has a tree for its name and one for its value, all of them
+            // could be left empty and deleted up to the document type tree
+            let trees_count = index.properties.len() * 2 + 2;
+
+            drive_operations.push(DriveOperation::for_query_path_key_value_size(
+                index_path_size as u32,
+                DEFAULT_HASH_SIZE as u32,
+                element_max_size as u32,
+            ));
+            drive_operations.push(CostCalculationDeleteOperation(
+                SizesOfDeleteOperation::for_key_value_size(
+                    index_path_size as u32,
+                    DEFAULT_HASH_SIZE as u16,
+                    element_max_size as u32,
+                    u8::try_from(trees_count).unwrap_or(u8::MAX),
+                ),
+            ));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -272,6 +377,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 None,
             )
             .expect("expected to insert a document successfully");
@@ -302,6 +408,7 @@ mod tests {
                 "person",
                 Some(&random_owner_id),
                 true,
+                false,
                 None,
             )
             .expect("expected to be able to delete the document");
@@ -363,6 +470,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to insert a document successfully");
@@ -401,6 +509,7 @@ mod tests {
                 "person",
                 Some(&random_owner_id),
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to be able to delete the document");
@@ -470,6 +579,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to insert a document successfully");
@@ -506,6 +616,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to insert a document successfully");
@@ -539,6 +650,7 @@ mod tests {
                 "person",
                 Some(&random_owner_id),
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to be able to delete the document");
@@ -572,6 +684,7 @@ mod tests {
                 "person",
                 Some(&random_owner_id),
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to be able to delete the document");
@@ -643,6 +756,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to insert a document successfully");
@@ -679,6 +793,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to insert a document successfully");
@@ -712,6 +827,7 @@ mod tests {
                 "person",
                 Some(&random_owner_id),
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to be able to delete the document");
@@ -747,6 +863,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to insert a document successfully");
@@ -768,6 +885,7 @@ mod tests {
                 "person",
                 Some(&random_owner_id),
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to be able to delete the document");
@@ -783,6 +901,7 @@ mod tests {
                 "person",
                 Some(&random_owner_id),
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to be able to delete the document");
@@ -823,6 +942,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -839,6 +959,7 @@ mod tests {
                 "profile",
                 Some(&random_owner_id),
                 true,
+                false,
                 None,
             )
             .expect("expected to be able to delete the document");
@@ -877,6 +998,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -893,6 +1015,7 @@ mod tests {
                 "profile",
                 Some(&random_owner_id),
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to be able to delete the document");
@@ -952,6 +1075,7 @@ mod tests {
                         false,
                         0f64,
                         true,
+                        false,
                         Some(&db_transaction),
                     )
                     .expect("expected to insert a document successfully");
@@ -996,6 +1120,7 @@ mod tests {
                 "niceDocument",
                 Some(&documents.get(0).unwrap().owner_id),
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to be able to delete the document");
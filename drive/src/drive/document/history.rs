@@ -0,0 +1,322 @@
+use std::ops::RangeInclusive;
+
+use dpp::data_contract::extra::encode_float;
+use grovedb::query_result_type::QueryResultType::QueryKeyElementPairResultType;
+use grovedb::{Element, GroveDb, PathQuery, Query, SizedQuery, TransactionArg};
+
+use crate::common::encode::decode_float;
+use crate::contract::document::Document;
+use crate::contract::Contract;
+use crate::drive::document::contract_documents_keeping_history_primary_key_path_for_document_id;
+use crate::drive::Drive;
+use crate::error::drive::DriveError;
+use crate::error::query::QueryError;
+use crate::error::Error;
+use crate::fee::op::DriveOperation;
+use dpp::data_contract::extra::DriveContractExt;
+
+fn document_history_path(
+    contract_id: &[u8],
+    document_type_name: &str,
+    document_id: &[u8],
+) -> Vec<Vec<u8>> {
+    contract_documents_keeping_history_primary_key_path_for_document_id(
+        contract_id,
+        document_type_name,
+        document_id,
+    )
+    .into_iter()
+    .map(|key| key.to_vec())
+    .collect()
+}
+
+/// The latest revision stored at or before the block time
+fn document_as_of_path_query(
+    contract_id: &[u8],
+    document_type_name: &str,
+    document_id: &[u8],
+    block_time: f64,
+) -> Result<PathQuery, Error> {
+    // [0] is a reference to the latest revision
+    let mut query = Query::new_with_direction(false);
+    query.insert_range_after_to_inclusive(vec![0]..=encode_float(block_time)?);
+
+    Ok(PathQuery::new(
+        document_history_path(contract_id, document_type_name, document_id),
+        SizedQuery::new(query, Some(1), None),
+    ))
+}
+
+/// The revisions stored in the time range, both ends included
+fn document_history_path_query(
+    contract_id: &[u8],
+    document_type_name: &str,
+    document_id: &[u8],
+    time_range: RangeInclusive<f64>,
+    limit: Option<u16>,
+) -> Result<PathQuery, Error> {
+    if time_range.start() > time_range.end() {
+        return Err(Error::Query(QueryError::InvalidBetweenClause(
+            "history start time must not be after the end time",
+        )));
+    }
+
+    let mut query = Query::new();
+    query.insert_range_inclusive(
+        encode_float(*time_range.start())?..=encode_float(*time_range.end())?,
+    );
+
+    Ok(PathQuery::new(
+        document_history_path(contract_id, document_type_name, document_id),
+        SizedQuery::new(query, limit, None),
+    ))
+}
+
+fn document_revision_from_element(
+    encoded_time: &[u8],
+    element: Element,
+) -> Result<(f64, Document), Error> {
+    if let Element::Item(document_cbor, _) = element {
+        Ok((
+            decode_float(encoded_time)?,
+            Document::from_cbor(document_cbor.as_slice(), None, None)?,
+        ))
+    } else {
+        Err(Error::Drive(DriveError::CorruptedDocumentPath(
+            "document history should only have items",
+        )))
+    }
+}
+
+fn document_revisions_from_proved_key_values(
+    proved_key_values: Vec<(Vec<u8>, Vec<u8>)>,
+) -> Result<Vec<(f64, Document)>, Error> {
+    proved_key_values
+        .into_iter()
+        .map(|(encoded_time, value)| {
+            document_revision_from_element(
+                &encoded_time,
+                Element::deserialize(&value).map_err(Error::GroveDB)?,
+            )
+        })
+        .collect()
+}
+
+impl Drive {
+    fn check_document_type_keeps_history(
+        contract: &Contract,
+        document_type_name: &str,
+    ) -> Result<(), Error> {
+        if !contract
+            .document_type_for_name(document_type_name)?
+            .documents_keep_history
+        {
+            return Err(Error::Query(QueryError::InvalidDocumentType(
+                "document type does not keep history",
+            )));
+        }
+        Ok(())
+    }
+
+    fn fetch_document_revisions_for_path_query(
+        &self,
+        path_query: &PathQuery,
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<Vec<(f64, Document)>, Error> {
+        let query_result = self.grove_get_raw_path_query(
+            path_query,
+            transaction,
+            QueryKeyElementPairResultType,
+            drive_operations,
+        );
+        match query_result {
+            Err(Error::GroveDB(grovedb::Error::PathKeyNotFound(_)))
+            | Err(Error::GroveDB(grovedb::Error::PathNotFound(_))) => Ok(vec![]),
+            _ => {
+                let (key_elements, _) = query_result?;
+                key_elements
+                    .to_key_elements()
+                    .into_iter()
+                    .map(|(encoded_time, element)| {
+                        document_revision_from_element(&encoded_time, element)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Returns the document as it was at the block time along with the time of that revision
+    pub fn fetch_document_as_of(
+        &self,
+        contract: &Contract,
+        document_type_name: &str,
+        document_id: [u8; 32],
+        block_time: f64,
+        transaction: TransactionArg,
+    ) -> Result<(Option<(f64, Document)>, u64), Error> {
+        Self::check_document_type_keeps_history(contract, document_type_name)?;
+
+        let path_query = document_as_of_path_query(
+            contract.id.as_bytes(),
+            document_type_name,
+            &document_id,
+            block_time,
+        )?;
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        let revision = self
+            .fetch_document_revisions_for_path_query(
+                &path_query,
+                transaction,
+                &mut drive_operations,
+            )?
+            .pop();
+
+        let cost = self
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
+
+        Ok((revision, cost))
+    }
+
+    pub fn prove_document_as_of(
+        &self,
+        contract: &Contract,
+        document_type_name: &str,
+        document_id: [u8; 32],
+        block_time: f64,
+        transaction: TransactionArg,
+    ) -> Result<(Vec<u8>, u64), Error> {
+        Self::check_document_type_keeps_history(contract, document_type_name)?;
+
+        let path_query = document_as_of_path_query(
+            contract.id.as_bytes(),
+            document_type_name,
+            &document_id,
+            block_time,
+        )?;
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        let proof =
+            self.grove_get_proved_path_query(&path_query, transaction, &mut drive_operations)?;
+
+        let cost = self
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
+
+        Ok((proof, cost))
+    }
+
+    pub fn verify_document_as_of(
+        proof: &[u8],
+        contract_id: [u8; 32],
+        document_type_name: &str,
+        document_id: [u8; 32],
+        block_time: f64,
+    ) -> Result<([u8; 32], Option<(f64, Document)>), Error> {
+        let path_query =
+            document_as_of_path_query(&contract_id, document_type_name, &document_id, block_time)?;
+
+        let (root_hash, proved_key_values) =
+            GroveDb::verify_query(proof, &path_query).map_err(Error::GroveDB)?;
+
+        let revision = document_revisions_from_proved_key_values(proved_key_values)?.pop();
+
+        Ok((root_hash, revision))
+    }
+
+    /// Returns the revisions of the document stored in the time range, oldest first,
+    /// with the block time of each revision
+    pub fn fetch_document_history(
+        &self,
+        contract: &Contract,
+        document_type_name: &str,
+        document_id: [u8; 32],
+        time_range: RangeInclusive<f64>,
+        limit: Option<u16>,
+        transaction: TransactionArg,
+    ) -> Result<(Vec<(f64, Document)>, u64), Error> {
+        Self::check_document_type_keeps_history(contract, document_type_name)?;
+
+        let path_query = document_history_path_query(
+            contract.id.as_bytes(),
+            document_type_name,
+            &document_id,
+            time_range,
+            limit,
+        )?;
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        let revisions = self.fetch_document_revisions_for_path_query(
+            &path_query,
+            transaction,
+            &mut drive_operations,
+        )?;
+
+        let cost = self
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
+
+        Ok((revisions, cost))
+    }
+
+    pub fn prove_document_history(
+        &self,
+        contract: &Contract,
+        document_type_name: &str,
+        document_id: [u8; 32],
+        time_range: RangeInclusive<f64>,
+        limit: Option<u16>,
+        transaction: TransactionArg,
+    ) -> Result<(Vec<u8>, u64), Error> {
+        Self::check_document_type_keeps_history(contract, document_type_name)?;
+
+        let path_query = document_history_path_query(
+            contract.id.as_bytes(),
+            document_type_name,
+            &document_id,
+            time_range,
+            limit,
+        )?;
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        let proof =
+            self.grove_get_proved_path_query(&path_query, transaction, &mut drive_operations)?;
+
+        let cost = self
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
+
+        Ok((proof, cost))
+    }
+
+    pub fn verify_document_history(
+        proof: &[u8],
+        contract_id: [u8; 32],
+        document_type_name: &str,
+        document_id: [u8; 32],
+        time_range: RangeInclusive<f64>,
+        limit: Option<u16>,
+    ) -> Result<([u8; 32], Vec<(f64, Document)>), Error> {
+        let path_query = document_history_path_query(
+            &contract_id,
+            document_type_name,
+            &document_id,
+            time_range,
+            limit,
+        )?;
+
+        let (root_hash, proved_key_values) =
+            GroveDb::verify_query(proof, &path_query).map_err(Error::GroveDB)?;
+
+        Ok((
+            root_hash,
+            document_revisions_from_proved_key_values(proved_key_values)?,
+        ))
+    }
+}
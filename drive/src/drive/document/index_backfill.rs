@@ -0,0 +1,979 @@
+use std::collections::BTreeMap;
+
+use ciborium::value::Value;
+use costs::CostContext;
+use grovedb::query_result_type::QueryResultType::QueryKeyElementPairResultType;
+use grovedb::{Element, PathQuery, Query, SizedQuery, TransactionArg};
+
+use crate::contract::document::Document;
+use crate::contract::{Contract, DocumentType, Index};
+use crate::drive::batch::GroveDbOpBatch;
+use crate::drive::document::{
+    contract_documents_keeping_history_primary_key_path_for_document_id,
+    contract_documents_primary_key_path,
+};
+use crate::drive::flags::StorageFlags;
+use crate::drive::object_size_info::DocumentAndContractInfo;
+use crate::drive::object_size_info::DocumentInfo::DocumentWithoutSerialization;
+use crate::drive::object_size_info::KeyValueInfo::KeyRefRequest;
+use crate::drive::object_size_info::PathKeyElementInfo::PathFixedSizeKeyElement;
+use crate::drive::{Drive, RootTree};
+use crate::error::drive::DriveError;
+use crate::error::query::QueryError;
+use crate::error::Error;
+use crate::fee::op::DriveOperation::{CalculatedCostOperation, CostCalculationQueryOperation};
+use crate::fee::op::{DriveOperation, SizesOfQueryOperation};
+use crate::fee::result::FeeResult;
+
+use dpp::data_contract::extra::DriveContractExt;
+
+/// The backfills are kept in their own tree of the misc root tree
+pub(crate) fn index_backfills_path() -> [&'static [u8]; 2] {
+    [Into::<&[u8; 1]>::into(RootTree::Misc), &[0]]
+}
+
+pub fn add_init_index_backfills_structure_operations(batch: &mut GroveDbOpBatch) {
+    batch.add_insert_empty_tree(vec![vec![RootTree::Misc as u8]], vec![0]);
+}
+
+pub(crate) fn index_backfills_key(contract_id: &[u8], document_type_name: &str) -> Vec<u8> {
+    [contract_id, document_type_name.as_bytes()].concat()
+}
+
+/// The progress of writing the documents that existed before an index was added
+/// to their document type into that index
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexBackfill {
+    /// The properties of the index being backfilled
+    pub index_properties: Vec<String>,
+    /// The last document written to the index, documents are backfilled by id
+    pub last_document_id: Option<Vec<u8>>,
+    /// Whether writing a document to the index failed, the backfill is then stopped
+    /// and the index can't be queried
+    pub failed: bool,
+}
+
+impl IndexBackfill {
+    fn for_index(index: &Index) -> Self {
+        IndexBackfill {
+            index_properties: index
+                .properties
+                .iter()
+                .map(|property| property.name.clone())
+                .collect(),
+            last_document_id: None,
+            failed: false,
+        }
+    }
+
+    fn into_failed(self) -> Self {
+        IndexBackfill {
+            failed: true,
+            ..self
+        }
+    }
+
+    fn is_for_index(&self, index: &Index) -> bool {
+        self.index_properties
+            .iter()
+            .eq(index.properties.iter().map(|property| &property.name))
+    }
+
+    /// Whether the document was already reached by the backfill
+    fn covers_document(&self, document_id: &[u8]) -> bool {
+        self.last_document_id
+            .as_ref()
+            .map_or(false, |last_document_id| {
+                document_id <= last_document_id.as_slice()
+            })
+    }
+
+    fn to_cbor_map(&self) -> BTreeMap<String, Value> {
+        BTreeMap::from([
+            (
+                "indexProperties".to_string(),
+                Value::Array(
+                    self.index_properties
+                        .iter()
+                        .map(|name| Value::Text(name.clone()))
+                        .collect(),
+                ),
+            ),
+            (
+                "lastDocumentId".to_string(),
+                self.last_document_id
+                    .as_ref()
+                    .map_or(Value::Null, |id| Value::Bytes(id.clone())),
+            ),
+            ("failed".to_string(), Value::Bool(self.failed)),
+        ])
+    }
+
+    fn from_cbor_map(mut index_backfill: BTreeMap<String, Value>) -> Result<Self, Error> {
+        let index_properties = match index_backfill.remove("indexProperties") {
+            Some(Value::Array(names)) => names
+                .into_iter()
+                .map(|name| match name {
+                    Value::Text(name) => Ok(name),
+                    _ => Err(Error::Drive(DriveError::CorruptedIndexBackfill(
+                        "index property names should be text",
+                    ))),
+                })
+                .collect::<Result<Vec<String>, Error>>()?,
+            _ => {
+                return Err(Error::Drive(DriveError::CorruptedIndexBackfill(
+                    "index properties should be an array",
+                )))
+            }
+        };
+
+        let last_document_id = match index_backfill.remove("lastDocumentId") {
+            Some(Value::Bytes(id)) => Some(id),
+            Some(Value::Null) => None,
+            _ => {
+                return Err(Error::Drive(DriveError::CorruptedIndexBackfill(
+                    "last document id should be bytes or null",
+                )))
+            }
+        };
+
+        let failed = match index_backfill.remove("failed") {
+            Some(Value::Bool(failed)) => failed,
+            _ => {
+                return Err(Error::Drive(DriveError::CorruptedIndexBackfill(
+                    "failed should be a bool",
+                )))
+            }
+        };
+
+        Ok(IndexBackfill {
+            index_properties,
+            last_document_id,
+            failed,
+        })
+    }
+}
+
+fn serialize_index_backfills(index_backfills: &[IndexBackfill]) -> Result<Vec<u8>, Error> {
+    let index_backfills: Vec<BTreeMap<String, Value>> = index_backfills
+        .iter()
+        .map(IndexBackfill::to_cbor_map)
+        .collect();
+    let mut buffer: Vec<u8> = Vec::new();
+    ciborium::ser::into_writer(&index_backfills, &mut buffer).map_err(|_| {
+        Error::Drive(DriveError::CorruptedSerialization(
+            "unable to serialize index backfills",
+        ))
+    })?;
+    Ok(buffer)
+}
+
+fn deserialize_index_backfills(bytes: &[u8]) -> Result<Vec<IndexBackfill>, Error> {
+    let index_backfills: Vec<BTreeMap<String, Value>> =
+        ciborium::de::from_reader(bytes).map_err(|_| {
+            Error::Drive(DriveError::CorruptedIndexBackfill(
+                "unable to decode index backfills",
+            ))
+        })?;
+    index_backfills
+        .into_iter()
+        .map(IndexBackfill::from_cbor_map)
+        .collect()
+}
+
+impl Drive {
+    /// Returns the indices of the document type that are still being backfilled
+    pub fn fetch_index_backfills(
+        &self,
+        contract_id: &[u8],
+        document_type_name: &str,
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<Vec<IndexBackfill>, Error> {
+        let CostContext { value, cost } = self.grove.get(
+            index_backfills_path(),
+            &index_backfills_key(contract_id, document_type_name),
+            transaction,
+        );
+        drive_operations.push(CalculatedCostOperation(cost));
+        match value {
+            Ok(Element::Item(index_backfills_bytes, _)) => {
+                deserialize_index_backfills(&index_backfills_bytes)
+            }
+            Ok(_) => Err(Error::Drive(DriveError::CorruptedIndexBackfill(
+                "index backfills should be an item",
+            ))),
+            Err(grovedb::Error::PathKeyNotFound(_)) | Err(grovedb::Error::PathNotFound(_)) => {
+                Ok(vec![])
+            }
+            Err(e) => Err(Error::GroveDB(e)),
+        }
+    }
+
+    /// The indices a document is written to or removed from, an index being backfilled
+    /// only has the documents the backfill already reached
+    pub(crate) fn document_type_indices_for_document<'a>(
+        &self,
+        contract: &Contract,
+        document_type: &'a DocumentType,
+        document_id: Option<&[u8]>,
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<Vec<&'a Index>, Error> {
+        let document_id = match document_id {
+            None => return Ok(document_type.indices.iter().collect()),
+            Some(document_id) => document_id,
+        };
+
+        let index_backfills = self.fetch_index_backfills(
+            contract.id.as_bytes(),
+            document_type.name.as_str(),
+            transaction,
+            drive_operations,
+        )?;
+
+        Ok(document_type
+            .indices
+            .iter()
+            .filter(|index| {
+                index_backfills
+                    .iter()
+                    .find(|index_backfill| index_backfill.is_for_index(index))
+                    .map_or(true, |index_backfill| {
+                        index_backfill.covers_document(document_id)
+                    })
+            })
+            .collect())
+    }
+
+    /// Queries must not use an index before all the documents were written to it
+    pub(crate) fn check_index_is_backfilled(
+        &self,
+        contract_id: &[u8],
+        document_type_name: &str,
+        index: &Index,
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<(), Error> {
+        let index_backfills = self.fetch_index_backfills(
+            contract_id,
+            document_type_name,
+            transaction,
+            drive_operations,
+        )?;
+        match index_backfills
+            .iter()
+            .find(|index_backfill| index_backfill.is_for_index(index))
+        {
+            None => Ok(()),
+            Some(IndexBackfill { failed: true, .. }) => {
+                Err(Error::Query(QueryError::IndexBackfillFailed(
+                    "the index could not be backfilled with existing documents",
+                )))
+            }
+            Some(_) => Err(Error::Query(QueryError::IndexBackfillInProgress(
+                "the index is still being backfilled with existing documents",
+            ))),
+        }
+    }
+
+    fn fetch_documents_for_index_backfill(
+        &self,
+        contract: &Contract,
+        document_type: &DocumentType,
+        after_document_id: Option<&[u8]>,
+        limit: u16,
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<Vec<(Document, StorageFlags)>, Error> {
+        let mut query = Query::new();
+        match after_document_id {
+            None => query.insert_all(),
+            Some(document_id) => query.insert_range_after(document_id.to_vec()..),
+        }
+
+        let path_query = PathQuery::new(
+            contract_documents_primary_key_path(
+                contract.id.as_bytes(),
+                document_type.name.as_str(),
+            )
+            .into_iter()
+            .map(|key| key.to_vec())
+            .collect(),
+            SizedQuery::new(query, Some(limit), None),
+        );
+
+        let (key_elements, _) = self.grove_get_raw_path_query(
+            &path_query,
+            transaction,
+            QueryKeyElementPairResultType,
+            drive_operations,
+        )?;
+
+        key_elements
+            .to_key_elements()
+            .into_iter()
+            .map(|(document_id, element)| {
+                let element = if document_type.documents_keep_history {
+                    // [0] refers to the latest revision
+                    self.grove_get(
+                        contract_documents_keeping_history_primary_key_path_for_document_id(
+                            contract.id.as_bytes(),
+                            document_type.name.as_str(),
+                            document_id.as_slice(),
+                        ),
+                        KeyRefRequest(&[0]),
+                        transaction,
+                        drive_operations,
+                    )?
+                    .ok_or(Error::Drive(
+                        DriveError::CorruptedDocumentPath(
+                            "document history has no latest revision",
+                        ),
+                    ))?
+                } else {
+                    element
+                };
+                if let Element::Item(document_cbor, element_flags) = element {
+                    Ok((
+                        Document::from_cbor(document_cbor.as_slice(), None, None)?,
+                        StorageFlags::from_element_flags(element_flags)?,
+                    ))
+                } else {
+                    Err(Error::Drive(DriveError::CorruptedDocumentNotItem(
+                        "primary key tree should only have documents",
+                    )))
+                }
+            })
+            .collect()
+    }
+
+    pub(crate) fn document_type_has_documents(
+        &self,
+        contract: &Contract,
+        document_type: &DocumentType,
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<bool, Error> {
+        let mut query = Query::new();
+        query.insert_all();
+
+        let path_query = PathQuery::new(
+            contract_documents_primary_key_path(
+                contract.id.as_bytes(),
+                document_type.name.as_str(),
+            )
+            .into_iter()
+            .map(|key| key.to_vec())
+            .collect(),
+            SizedQuery::new(query, Some(1), None),
+        );
+
+        let (key_elements, _) = self.grove_get_raw_path_query(
+            &path_query,
+            transaction,
+            QueryKeyElementPairResultType,
+            drive_operations,
+        )?;
+
+        Ok(!key_elements.to_key_elements().is_empty())
+    }
+
+    /// Starts backfilling the indices added to a document type that already has documents
+    pub(crate) fn add_index_backfills_operations(
+        &self,
+        contract: &Contract,
+        document_type: &DocumentType,
+        added_indices: Vec<&Index>,
+        storage_flags: &StorageFlags,
+        transaction: TransactionArg,
+        batch_operations: &mut Vec<DriveOperation>,
+    ) -> Result<(), Error> {
+        if added_indices.is_empty()
+            || !self.document_type_has_documents(
+                contract,
+                document_type,
+                transaction,
+                batch_operations,
+            )?
+        {
+            return Ok(());
+        }
+
+        if added_indices.iter().any(|index| index.unique) {
+            return Err(Error::Drive(
+                DriveError::AddingUniqueIndexToDocumentTypeWithDocuments(
+                    "existing documents can not be checked for uniqueness",
+                ),
+            ));
+        }
+
+        let mut index_backfills = self.fetch_index_backfills(
+            contract.id.as_bytes(),
+            document_type.name.as_str(),
+            transaction,
+            batch_operations,
+        )?;
+        for index in added_indices {
+            if !index_backfills
+                .iter()
+                .any(|index_backfill| index_backfill.is_for_index(index))
+            {
+                index_backfills.push(IndexBackfill::for_index(index));
+            }
+        }
+
+        let index_backfills_key =
+            index_backfills_key(contract.id.as_bytes(), document_type.name.as_str());
+        self.batch_insert(
+            PathFixedSizeKeyElement((
+                index_backfills_path(),
+                index_backfills_key.as_slice(),
+                Element::Item(
+                    serialize_index_backfills(&index_backfills)?,
+                    storage_flags.to_element_flags(),
+                ),
+            )),
+            batch_operations,
+        )
+    }
+
+    /// The largest backfill a contract update can start for the document type, that is
+    /// one for each of its non unique indices, all of them already partly written, along
+    /// with the reads that find the type has documents and fetch its current backfills
+    pub(crate) fn add_worst_case_index_backfills_operations(
+        &self,
+        contract: &Contract,
+        document_type: &DocumentType,
+        storage_flags: &StorageFlags,
+        batch_operations: &mut Vec<DriveOperation>,
+    ) -> Result<(), Error> {
+        let index_backfills: Vec<IndexBackfill> = document_type
+            .indices
+            .iter()
+            .filter(|index| !index.unique)
+            .map(|index| IndexBackfill {
+                last_document_id: Some(vec![u8::MAX; 32]),
+                ..IndexBackfill::for_index(index)
+            })
+            .collect();
+        if index_backfills.is_empty() {
+            return Ok(());
+        }
+
+        batch_operations.push(CostCalculationQueryOperation(
+            SizesOfQueryOperation::for_value_retrieval_in_path(
+                32,
+                contract_documents_primary_key_path(
+                    contract.id.as_bytes(),
+                    document_type.name.as_str(),
+                ),
+                document_type.max_size(),
+            ),
+        ));
+
+        let index_backfills_key =
+            index_backfills_key(contract.id.as_bytes(), document_type.name.as_str());
+        let index_backfills_bytes = serialize_index_backfills(&index_backfills)?;
+        batch_operations.push(CostCalculationQueryOperation(
+            SizesOfQueryOperation::for_value_retrieval_in_path(
+                index_backfills_key.len(),
+                index_backfills_path(),
+                index_backfills_bytes.len(),
+            ),
+        ));
+
+        self.batch_insert(
+            PathFixedSizeKeyElement((
+                index_backfills_path(),
+                index_backfills_key.as_slice(),
+                Element::Item(index_backfills_bytes, storage_flags.to_element_flags()),
+            )),
+            batch_operations,
+        )
+    }
+
+    /// Writes up to max_documents existing documents into the indices being backfilled.
+    /// This is meant to be called once per block, an index can be queried once all the
+    /// documents of its document type were written to it.
+    /// Returns the number of documents written and the fees of writing them.
+    pub fn process_index_backfills(
+        &self,
+        max_documents: u16,
+        transaction: TransactionArg,
+    ) -> Result<(u16, FeeResult), Error> {
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        let mut query = Query::new();
+        query.insert_all();
+        let path_query = PathQuery::new(
+            index_backfills_path()
+                .into_iter()
+                .map(|key| key.to_vec())
+                .collect(),
+            SizedQuery::new(query, None, None),
+        );
+
+        let query_result = self.grove_get_raw_path_query(
+            &path_query,
+            transaction,
+            QueryKeyElementPairResultType,
+            &mut drive_operations,
+        );
+        let index_backfills_by_document_type = match query_result {
+            Err(Error::GroveDB(grovedb::Error::PathKeyNotFound(_)))
+            | Err(Error::GroveDB(grovedb::Error::PathNotFound(_))) => {
+                let fees = self.calculate_fee(None, Some(drive_operations), false, transaction)?;
+                return Ok((0, fees));
+            }
+            _ => query_result?.0.to_key_elements(),
+        };
+
+        let mut documents_written: u16 = 0;
+
+        for (index_backfills_key, element) in index_backfills_by_document_type {
+            if documents_written >= max_documents {
+                break;
+            }
+
+            let (index_backfills_bytes, element_flags) = match element {
+                Element::Item(index_backfills_bytes, element_flags) => {
+                    (index_backfills_bytes, element_flags)
+                }
+                _ => {
+                    return Err(Error::Drive(DriveError::CorruptedIndexBackfill(
+                        "index backfills should be an item",
+                    )))
+                }
+            };
+
+            if index_backfills_key.len() <= 32 {
+                return Err(Error::Drive(DriveError::CorruptedIndexBackfill(
+                    "index backfills key should be a contract id and a document type name",
+                )));
+            }
+            let (contract_id, document_type_name) = index_backfills_key.split_at(32);
+            let contract_id: [u8; 32] = contract_id.try_into().map_err(|_| {
+                Error::Drive(DriveError::CorruptedIndexBackfill(
+                    "index backfills key should start with a contract id",
+                ))
+            })?;
+            let document_type_name = std::str::from_utf8(document_type_name).map_err(|_| {
+                Error::Drive(DriveError::CorruptedIndexBackfill(
+                    "index backfills key should end with a document type name",
+                ))
+            })?;
+
+            let index_backfills = deserialize_index_backfills(&index_backfills_bytes)?;
+            let remaining_index_backfills = self.process_document_type_index_backfills(
+                contract_id,
+                document_type_name,
+                index_backfills.clone(),
+                max_documents,
+                &mut documents_written,
+                transaction,
+                &mut drive_operations,
+            );
+            // failed backfills stay as they are
+            if remaining_index_backfills == index_backfills {
+                continue;
+            }
+
+            let mut batch_operations: Vec<DriveOperation> = vec![];
+            if remaining_index_backfills.is_empty() {
+                self.batch_delete(
+                    index_backfills_path(),
+                    index_backfills_key.as_slice(),
+                    true, // not a tree, irrelevant
+                    transaction,
+                    &mut batch_operations,
+                )?;
+            } else {
+                self.batch_insert(
+                    PathFixedSizeKeyElement((
+                        index_backfills_path(),
+                        index_backfills_key.as_slice(),
+                        Element::Item(
+                            serialize_index_backfills(&remaining_index_backfills)?,
+                            element_flags,
+                        ),
+                    )),
+                    &mut batch_operations,
+                )?;
+            }
+            self.apply_batch_drive_operations(
+                true,
+                transaction,
+                batch_operations,
+                &mut drive_operations,
+            )?;
+        }
+
+        let fees = self.calculate_fee(None, Some(drive_operations), false, transaction)?;
+        Ok((documents_written, fees))
+    }
+
+    /// Moves the backfills of a document type forward, a backfill that fails is kept
+    /// as failed instead of failing the block it is processed in.
+    /// Returns the backfills that are not finished.
+    fn process_document_type_index_backfills(
+        &self,
+        contract_id: [u8; 32],
+        document_type_name: &str,
+        index_backfills: Vec<IndexBackfill>,
+        max_documents: u16,
+        documents_written: &mut u16,
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Vec<IndexBackfill> {
+        // the contract is fetched as a cached one could predate the added index
+        let contract = match self.fetch_contract(contract_id, transaction, self.cache.borrow_mut())
+        {
+            Ok((Some(contract), _)) => contract,
+            _ => {
+                return index_backfills
+                    .into_iter()
+                    .map(IndexBackfill::into_failed)
+                    .collect()
+            }
+        };
+        let document_type = match contract.document_type_for_name(document_type_name) {
+            Ok(document_type) => document_type,
+            Err(_) => {
+                return index_backfills
+                    .into_iter()
+                    .map(IndexBackfill::into_failed)
+                    .collect()
+            }
+        };
+
+        let mut remaining_index_backfills: Vec<IndexBackfill> = vec![];
+
+        for mut index_backfill in index_backfills {
+            let limit = max_documents - *documents_written;
+            if limit == 0 || index_backfill.failed {
+                remaining_index_backfills.push(index_backfill);
+                continue;
+            }
+
+            let index = match document_type
+                .indices
+                .iter()
+                .find(|index| index_backfill.is_for_index(index))
+            {
+                Some(index) => index,
+                // the index was removed by a later contract update
+                None => continue,
+            };
+
+            match self.backfill_index(
+                &contract,
+                document_type,
+                index,
+                &mut index_backfill,
+                limit,
+                documents_written,
+                transaction,
+                drive_operations,
+            ) {
+                // fewer documents than asked for means the end of the primary key tree
+                Ok(documents_count) if documents_count < limit => {}
+                Ok(_) => remaining_index_backfills.push(index_backfill),
+                Err(_) => remaining_index_backfills.push(index_backfill.into_failed()),
+            }
+        }
+
+        remaining_index_backfills
+    }
+
+    /// Writes up to limit documents into the index, the backfill is moved past each
+    /// written document. Returns the number of documents read for the backfill.
+    fn backfill_index(
+        &self,
+        contract: &Contract,
+        document_type: &DocumentType,
+        index: &Index,
+        index_backfill: &mut IndexBackfill,
+        limit: u16,
+        documents_written: &mut u16,
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<u16, Error> {
+        let documents = self.fetch_documents_for_index_backfill(
+            contract,
+            document_type,
+            index_backfill.last_document_id.as_deref(),
+            limit,
+            transaction,
+            drive_operations,
+        )?;
+
+        for (document, storage_flags) in documents.iter() {
+            // each document is applied on its own as documents can share index trees
+            let mut batch_operations: Vec<DriveOperation> = vec![];
+            self.add_document_to_indices_operations(
+                &DocumentAndContractInfo {
+                    document_info: DocumentWithoutSerialization((document, storage_flags)),
+                    contract,
+                    document_type,
+                    owner_id: None,
+                },
+                vec![index],
+                true,
+                transaction,
+                &mut batch_operations,
+            )?;
+            self.apply_batch_drive_operations(
+                true,
+                transaction,
+                batch_operations,
+                drive_operations,
+            )?;
+            index_backfill.last_document_id = Some(document.id.to_vec());
+            *documents_written += 1;
+        }
+
+        Ok(documents.len() as u16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use grovedb::Element;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    use crate::common::value_to_cbor;
+    use crate::contract::document::Document;
+    use crate::contract::{Contract, CreateRandomDocument};
+    use crate::drive::document::index_backfill::{
+        index_backfills_key, index_backfills_path, serialize_index_backfills, IndexBackfill,
+    };
+    use crate::drive::flags::StorageFlags;
+    use crate::drive::object_size_info::DocumentAndContractInfo;
+    use crate::drive::object_size_info::DocumentInfo::DocumentAndSerialization;
+    use crate::drive::Drive;
+    use crate::error::drive::DriveError;
+    use crate::error::query::QueryError;
+    use crate::error::Error;
+    use crate::query::DriveQuery;
+
+    use dpp::data_contract::extra::DriveContractExt;
+
+    fn apply_family_contract(drive: &Drive, indices: serde_json::Value) -> Result<Contract, Error> {
+        let contract_value = json!({
+            "$id": "94zNLp7A1ZcYG3Egqf2YmQk4DQr9P8D543GwXyCJRz4",
+            "ownerId": "AcYUCSvAmUwryNsQqkqqD1o3BnFuzepGtR3Mhh2swLk6",
+            "$schema": "http://json-schema.org/draft-07/schema",
+            "version": 1,
+            "documents": {
+                "person": {
+                    "indices": indices,
+                    "properties": {
+                        "firstName": {
+                            "type": "string",
+                            "maxLength": 50
+                        },
+                        "lastName": {
+                            "type": "string",
+                            "maxLength": 50
+                        }
+                    },
+                    "additionalProperties": false
+                }
+            }
+        });
+        let contract_cbor = value_to_cbor(contract_value, Some(1));
+        let contract = <Contract as DriveContractExt>::from_cbor(&contract_cbor, None)
+            .expect("expected to deserialize the contract");
+        drive.apply_contract(
+            &contract,
+            contract_cbor,
+            0f64,
+            true,
+            false,
+            StorageFlags { epoch: 0 },
+            None,
+        )?;
+        Ok(contract)
+    }
+
+    #[test]
+    fn test_backfill_added_index() {
+        let tmp_dir = TempDir::new().unwrap();
+        let drive: Drive = Drive::open(tmp_dir, None).expect("expected to open Drive successfully");
+
+        drive
+            .create_initial_state_structure(None)
+            .expect("expected to create root tree successfully");
+
+        let first_name_index = json!({"properties": [{"firstName": "asc"}]});
+        let last_name_index = json!({"properties": [{"lastName": "asc"}]});
+
+        let contract = apply_family_contract(&drive, json!([first_name_index]))
+            .expect("expected to apply contract successfully");
+
+        let document_type = contract
+            .document_type_for_name("person")
+            .expect("expected to get document type");
+        let documents = document_type.random_filled_documents(10, Some(3));
+        let storage_flags = StorageFlags { epoch: 0 };
+        for document in documents.iter() {
+            drive
+                .add_document_for_contract(
+                    DocumentAndContractInfo {
+                        document_info: DocumentAndSerialization((
+                            document,
+                            document.to_cbor().as_slice(),
+                            &storage_flags,
+                        )),
+                        contract: &contract,
+                        document_type,
+                        owner_id: None,
+                    },
+                    false,
+                    0f64,
+                    true,
+                    false,
+                    None,
+                )
+                .expect("expected to insert a document successfully");
+        }
+
+        let contract = apply_family_contract(
+            &drive,
+            json!([first_name_index.clone(), last_name_index.clone()]),
+        )
+        .expect("expected to add an index");
+        let document_type = contract
+            .document_type_for_name("person")
+            .expect("expected to get document type");
+
+        let query_documents_by_last_name = |document: &Document| {
+            let query_value = json!({
+                "where": [
+                    ["lastName", "==", document.properties.get("lastName")],
+                ],
+            });
+            let query_cbor = value_to_cbor(query_value, None);
+            let query = DriveQuery::from_cbor(query_cbor.as_slice(), &contract, document_type)
+                .expect("query should be built");
+            query.execute_no_proof(&drive, None)
+        };
+
+        assert!(matches!(
+            query_documents_by_last_name(&documents[0]),
+            Err(Error::Query(QueryError::IndexBackfillInProgress(_)))
+        ));
+
+        let (documents_written, fees) = drive
+            .process_index_backfills(4, None)
+            .expect("expected to process index backfills");
+        assert_eq!(documents_written, 4);
+        assert!(fees.storage_fee > 0);
+        assert!(fees.processing_fee > 0);
+
+        let index_backfills = drive
+            .fetch_index_backfills(contract.id.as_bytes(), "person", None, &mut vec![])
+            .expect("expected to fetch index backfills");
+        assert_eq!(index_backfills.len(), 1);
+        assert_eq!(index_backfills[0].index_properties, vec!["lastName"]);
+        assert!(index_backfills[0].last_document_id.is_some());
+
+        assert!(matches!(
+            query_documents_by_last_name(&documents[0]),
+            Err(Error::Query(QueryError::IndexBackfillInProgress(_)))
+        ));
+
+        let (documents_written, _) = drive
+            .process_index_backfills(100, None)
+            .expect("expected to process index backfills");
+        assert_eq!(documents_written, 6);
+
+        let index_backfills = drive
+            .fetch_index_backfills(contract.id.as_bytes(), "person", None, &mut vec![])
+            .expect("expected to fetch index backfills");
+        assert!(index_backfills.is_empty());
+
+        for document in documents.iter() {
+            let (results, _, _) =
+                query_documents_by_last_name(document).expect("expected to query documents");
+            assert!(results.iter().any(|result| {
+                Document::from_cbor(result.as_slice(), None, None)
+                    .expect("expected to deserialize the document")
+                    .id
+                    == document.id
+            }));
+        }
+
+        // existing documents can't be checked against an added unique index
+        let unique_index = json!({
+            "properties": [{"firstName": "asc"}, {"lastName": "asc"}],
+            "unique": true
+        });
+        let result = apply_family_contract(
+            &drive,
+            json!([first_name_index, last_name_index, unique_index]),
+        );
+        assert!(matches!(
+            result,
+            Err(Error::Drive(
+                DriveError::AddingUniqueIndexToDocumentTypeWithDocuments(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_failed_backfill_is_kept() {
+        let tmp_dir = TempDir::new().unwrap();
+        let drive: Drive = Drive::open(tmp_dir, None).expect("expected to open Drive successfully");
+
+        drive
+            .create_initial_state_structure(None)
+            .expect("expected to create root tree successfully");
+
+        // a backfill whose contract does not exist can't go on
+        let contract_id = [7u8; 32];
+        let index_backfills = vec![IndexBackfill {
+            index_properties: vec!["lastName".to_string()],
+            last_document_id: None,
+            failed: false,
+        }];
+        drive
+            .grove
+            .insert(
+                index_backfills_path(),
+                index_backfills_key(&contract_id, "person").as_slice(),
+                Element::Item(
+                    serialize_index_backfills(&index_backfills)
+                        .expect("expected to serialize index backfills"),
+                    StorageFlags { epoch: 0 }.to_element_flags(),
+                ),
+                None,
+            )
+            .unwrap()
+            .expect("expected to insert index backfills");
+
+        let (documents_written, _) = drive
+            .process_index_backfills(10, None)
+            .expect("a failing backfill should not fail the processing");
+        assert_eq!(documents_written, 0);
+
+        let index_backfills = drive
+            .fetch_index_backfills(&contract_id, "person", None, &mut vec![])
+            .expect("expected to fetch index backfills");
+        assert_eq!(index_backfills.len(), 1);
+        assert!(index_backfills[0].failed);
+
+        // the failed backfill stays and is not retried
+        drive
+            .process_index_backfills(10, None)
+            .expect("a failed backfill should not fail the processing");
+        assert_eq!(
+            drive
+                .fetch_index_backfills(&contract_id, "person", None, &mut vec![])
+                .expect("expected to fetch index backfills"),
+            index_backfills
+        );
+    }
+}
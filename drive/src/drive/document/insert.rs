@@ -4,7 +4,7 @@ use std::collections::HashSet;
 use std::option::Option::None;
 
 use crate::contract::document::Document;
-use crate::contract::{Contract, DocumentType};
+use crate::contract::{Contract, DocumentType, Index};
 use crate::drive::defaults::{DEFAULT_HASH_SIZE, STORAGE_FLAGS_SIZE};
 use crate::drive::document::{
     contract_document_type_path,
@@ -28,8 +28,8 @@ use crate::drive::object_size_info::{DocumentAndContractInfo, PathInfo, PathKeyE
 use crate::drive::{defaults, Drive};
 use crate::error::drive::DriveError;
 use crate::error::Error;
-use crate::fee::calculate_fee;
 use crate::fee::op::DriveOperation;
+use crate::fee::result::FeeResult;
 
 use dpp::data_contract::extra::encode_float;
 use dpp::data_contract::extra::DriveContractExt;
@@ -244,9 +244,10 @@ impl Drive {
         override_document: bool,
         block_time: f64,
         apply: bool,
+        with_fee_breakdown: bool,
         storage_flags: StorageFlags,
         transaction: TransactionArg,
-    ) -> Result<(i64, u64), Error> {
+    ) -> Result<FeeResult, Error> {
         let contract = <Contract as DriveContractExt>::from_cbor(serialized_contract, None)?;
 
         let document = Document::from_cbor(serialized_document, None, owner_id)?;
@@ -266,6 +267,7 @@ impl Drive {
             override_document,
             block_time,
             apply,
+            with_fee_breakdown,
             transaction,
         )
     }
@@ -279,9 +281,10 @@ impl Drive {
         override_document: bool,
         block_time: f64,
         apply: bool,
+        with_fee_breakdown: bool,
         storage_flags: StorageFlags,
         transaction: TransactionArg,
-    ) -> Result<(i64, u64), Error> {
+    ) -> Result<FeeResult, Error> {
         let document = Document::from_cbor(serialized_document, None, owner_id)?;
 
         let document_info =
@@ -299,6 +302,7 @@ impl Drive {
             override_document,
             block_time,
             apply,
+            with_fee_breakdown,
             transaction,
         )
     }
@@ -309,8 +313,9 @@ impl Drive {
         override_document: bool,
         block_time: f64,
         apply: bool,
+        with_fee_breakdown: bool,
         transaction: TransactionArg,
-    ) -> Result<(i64, u64), Error> {
+    ) -> Result<FeeResult, Error> {
         let mut drive_operations: Vec<DriveOperation> = vec![];
         self.add_document_for_contract_operations(
             document_and_contract_info,
@@ -320,8 +325,12 @@ impl Drive {
             transaction,
             &mut drive_operations,
         )?;
-        let fees = calculate_fee(None, Some(drive_operations))?;
-        Ok(fees)
+        self.calculate_fee(
+            None,
+            Some(drive_operations),
+            with_fee_breakdown,
+            transaction,
+        )
     }
 
     pub(crate) fn add_document_for_contract_operations(
@@ -333,16 +342,20 @@ impl Drive {
         transaction: TransactionArg,
         drive_operations: &mut Vec<DriveOperation>,
     ) -> Result<(), Error> {
+        if self
+            .fetch_contract_archival_time(
+                document_and_contract_info.contract.id.as_bytes(),
+                transaction,
+                drive_operations,
+            )?
+            .is_some()
+        {
+            return Err(Error::Drive(DriveError::ContractArchived(
+                "documents can not be added to archived contracts",
+            )));
+        }
+
         let mut batch_operations: Vec<DriveOperation> = vec![];
-        // second we need to construct the path for documents on the contract
-        // the path is
-        //  * Document and Contract root tree
-        //  * Contract ID recovered from document
-        //  * 0 to signify Documents and not Contract
-        let contract_document_type_path = contract_document_type_path(
-            document_and_contract_info.contract.id.as_bytes(),
-            document_and_contract_info.document_type.name.as_str(),
-        );
 
         let primary_key_path = contract_documents_primary_key_path(
             document_and_contract_info.contract.id.as_bytes(),
@@ -381,12 +394,60 @@ impl Drive {
             )?;
         }
 
+        let document_id = match &document_and_contract_info.document_info {
+            DocumentAndSerialization((document, _, _))
+            | DocumentWithoutSerialization((document, _)) => Some(document.id.as_slice()),
+            DocumentSize(_) => None,
+        };
+        let indices = self.document_type_indices_for_document(
+            document_and_contract_info.contract,
+            document_and_contract_info.document_type,
+            document_id,
+            transaction,
+            &mut batch_operations,
+        )?;
+
+        self.add_document_to_indices_operations(
+            &document_and_contract_info,
+            indices,
+            apply,
+            transaction,
+            &mut batch_operations,
+        )?;
+
+        self.apply_batch_drive_operations(apply, transaction, batch_operations, drive_operations)
+    }
+
+    /// Adds a reference to the document in each of the indices
+    pub(crate) fn add_document_to_indices_operations(
+        &self,
+        document_and_contract_info: &DocumentAndContractInfo,
+        indices: Vec<&Index>,
+        apply: bool,
+        transaction: TransactionArg,
+        batch_operations: &mut Vec<DriveOperation>,
+    ) -> Result<(), Error> {
+        // we need to construct the path for documents on the contract
+        // the path is
+        //  * Document and Contract root tree
+        //  * Contract ID recovered from document
+        //  * 0 to signify Documents and not Contract
+        let contract_document_type_path = contract_document_type_path(
+            document_and_contract_info.contract.id.as_bytes(),
+            document_and_contract_info.document_type.name.as_str(),
+        );
+
+        let primary_key_path = contract_documents_primary_key_path(
+            document_and_contract_info.contract.id.as_bytes(),
+            document_and_contract_info.document_type.name.as_str(),
+        );
+
         let storage_flags = document_and_contract_info.document_info.get_storage_flags();
 
         let mut batch_insertion_cache: HashSet<Vec<Vec<u8>>> = HashSet::new();
 
         // fourth we need to store a reference to the document for each index
-        for index in &document_and_contract_info.document_type.indices {
+        for index in indices {
             // at this point the contract path is to the contract documents
             // for each index the top index component will already have been added
             // when the contract itself was created
@@ -422,7 +483,7 @@ impl Drive {
                     &storage_flags,
                     apply,
                     transaction,
-                    &mut batch_operations,
+                    batch_operations,
                 )?;
                 if inserted {
                     path_key_info.add_to_cache(&mut batch_insertion_cache);
@@ -471,7 +532,7 @@ impl Drive {
                         &storage_flags,
                         apply,
                         transaction,
-                        &mut batch_operations,
+                        batch_operations,
                     )?;
                     if inserted {
                         path_key_info.add_to_cache(&mut batch_insertion_cache);
@@ -494,7 +555,7 @@ impl Drive {
                         &storage_flags,
                         apply,
                         transaction,
-                        &mut batch_operations,
+                        batch_operations,
                     )?;
                     if inserted {
                         path_key_info.add_to_cache(&mut batch_insertion_cache);
@@ -539,7 +600,7 @@ impl Drive {
                     &storage_flags,
                     apply,
                     transaction,
-                    &mut batch_operations,
+                    batch_operations,
                 )?;
 
                 index_path_info.push(Key(vec![0]))?;
@@ -567,7 +628,7 @@ impl Drive {
                 )?;
 
                 // here we should return an error if the element already exists
-                self.batch_insert(path_key_element_info, &mut batch_operations)?;
+                self.batch_insert(path_key_element_info, batch_operations)?;
             } else {
                 let key_element_info = match &document_and_contract_info.document_info {
                     DocumentAndSerialization((document, _, storage_flags))
@@ -596,7 +657,7 @@ impl Drive {
                     path_key_element_info,
                     apply,
                     transaction,
-                    &mut batch_operations,
+                    batch_operations,
                 )?;
                 if !inserted {
                     return Err(Error::Drive(DriveError::CorruptedContractIndexes(
@@ -605,7 +666,7 @@ impl Drive {
                 }
             }
         }
-        self.apply_batch_drive_operations(apply, transaction, batch_operations, drive_operations)
+        Ok(())
     }
 }
 
@@ -624,7 +685,7 @@ mod tests {
     use crate::drive::object_size_info::DocumentAndContractInfo;
     use crate::drive::object_size_info::DocumentInfo::DocumentAndSerialization;
     use crate::drive::Drive;
-    use crate::fee::op::DriveOperation;
+    use crate::fee::op::{DriveOperation, DriveOperationKind};
 
     #[test]
     fn test_add_dashpay_documents_no_transaction() {
@@ -645,6 +706,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -659,6 +721,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -673,6 +736,7 @@ mod tests {
                 true,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -712,6 +776,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -726,6 +791,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -740,6 +806,7 @@ mod tests {
                 true,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -772,7 +839,11 @@ mod tests {
 
         let random_owner_id = rand::thread_rng().gen::<[u8; 32]>();
 
-        let (actual_storage_fee, actual_processing_fee) = drive
+        let FeeResult {
+            storage_fee: actual_storage_fee,
+            processing_fee: actual_processing_fee,
+            ..
+        } = drive
             .add_serialized_document_for_contract(
                 &dashpay_cr_serialized_document,
                 &contract,
@@ -781,6 +852,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -790,6 +862,96 @@ mod tests {
         assert_eq!(1, actual_processing_fee);
     }
 
+    #[test]
+    fn test_add_dashpay_document_fee_breakdown() {
+        let tmp_dir = TempDir::new().unwrap();
+        let drive: Drive = Drive::open(tmp_dir, None).expect("expected to open Drive successfully");
+
+        let db_transaction = drive.grove.start_transaction();
+
+        drive
+            .create_initial_state_structure(Some(&db_transaction))
+            .expect("expected to create root tree successfully");
+
+        let contract = setup_contract(
+            &drive,
+            "tests/supporting_files/contract/dashpay/dashpay-contract-all-mutable.json",
+            None,
+            Some(&db_transaction),
+        );
+
+        let dashpay_cr_serialized_document = json_document_to_cbor(
+            "tests/supporting_files/contract/dashpay/contact-request0.json",
+            Some(1),
+        );
+
+        let random_owner_id = rand::thread_rng().gen::<[u8; 32]>();
+
+        let estimated_fee_result = drive
+            .add_serialized_document_for_contract(
+                &dashpay_cr_serialized_document,
+                &contract,
+                "contactRequest",
+                Some(&random_owner_id),
+                false,
+                0f64,
+                false,
+                false,
+                StorageFlags::default(),
+                Some(&db_transaction),
+            )
+            .expect("expected to get back fee for document insertion successfully");
+
+        // the breakdown is only returned when asked for
+        assert!(estimated_fee_result.breakdown.is_none());
+
+        let estimated_breakdown = drive
+            .add_serialized_document_for_contract(
+                &dashpay_cr_serialized_document,
+                &contract,
+                "contactRequest",
+                Some(&random_owner_id),
+                false,
+                0f64,
+                false,
+                true,
+                StorageFlags::default(),
+                Some(&db_transaction),
+            )
+            .expect("expected to get back fee for document insertion successfully")
+            .breakdown
+            .expect("expected a breakdown");
+
+        // nothing is applied, the inserts are charged as their worst case
+        assert!(estimated_fee_result.storage_fee > 0);
+        assert!(estimated_fee_result.processing_fee > 0);
+        let estimated_inserts = &estimated_breakdown[DriveOperationKind::CalculatedCost];
+        assert!(estimated_inserts.seek_count > 0);
+        assert!(estimated_inserts.storage_written_bytes > 0);
+
+        let applied_breakdown = drive
+            .add_serialized_document_for_contract(
+                &dashpay_cr_serialized_document,
+                &contract,
+                "contactRequest",
+                Some(&random_owner_id),
+                false,
+                0f64,
+                true,
+                true,
+                StorageFlags::default(),
+                Some(&db_transaction),
+            )
+            .expect("expected to insert a document successfully")
+            .breakdown
+            .expect("expected a breakdown");
+
+        let applied_inserts = &applied_breakdown[DriveOperationKind::CalculatedCost];
+        assert!(applied_inserts.seek_count > 0);
+        assert!(applied_inserts.storage_written_bytes > 0);
+        assert!(applied_inserts.hash_node_calls > 0);
+    }
+
     #[ignore]
     #[test]
     fn test_unknown_state_cost_dashpay_fee_for_add_documents() {
@@ -815,7 +977,11 @@ mod tests {
         );
 
         let random_owner_id = rand::thread_rng().gen::<[u8; 32]>();
-        let (storage_fee, processing_fee) = drive
+        let FeeResult {
+            storage_fee,
+            processing_fee,
+            ..
+        } = drive
             .add_serialized_document_for_contract(
                 &dashpay_cr_serialized_document,
                 &contract,
@@ -824,12 +990,17 @@ mod tests {
                 false,
                 0f64,
                 false,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
             .expect("expected to get back fee for document insertion successfully");
 
-        let (actual_storage_fee, actual_processing_fee) = drive
+        let FeeResult {
+            storage_fee: actual_storage_fee,
+            processing_fee: actual_processing_fee,
+            ..
+        } = drive
             .add_serialized_document_for_contract(
                 &dashpay_cr_serialized_document,
                 &contract,
@@ -838,6 +1009,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -986,6 +1158,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to insert a document successfully");
@@ -1026,6 +1199,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -1039,6 +1213,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -1052,6 +1227,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -1082,6 +1258,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -1095,6 +1272,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -1122,6 +1300,7 @@ mod tests {
                 None,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -1140,6 +1319,7 @@ mod tests {
                 true,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -1166,6 +1346,7 @@ mod tests {
                 true,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -1,6 +1,8 @@
 use crate::drive::{defaults, RootTree};
 
 mod delete;
+mod history;
+pub mod index_backfill;
 mod insert;
 mod update;
 
@@ -108,6 +110,7 @@ pub(crate) mod tests {
                 None,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
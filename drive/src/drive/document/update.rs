@@ -19,8 +19,8 @@ use crate::drive::object_size_info::{DocumentAndContractInfo, PathKeyInfo};
 use crate::drive::Drive;
 use crate::error::drive::DriveError;
 use crate::error::Error;
-use crate::fee::calculate_fee;
 use crate::fee::op::DriveOperation;
+use crate::fee::result::FeeResult;
 
 use dpp::data_contract::extra::DriveContractExt;
 
@@ -33,9 +33,10 @@ impl Drive {
         owner_id: Option<&[u8]>,
         block_time: f64,
         apply: bool,
+        with_fee_breakdown: bool,
         storage_flags: StorageFlags,
         transaction: TransactionArg,
-    ) -> Result<(i64, u64), Error> {
+    ) -> Result<FeeResult, Error> {
         let contract = <Contract as DriveContractExt>::from_cbor(contract_cbor, None)?;
 
         let document = Document::from_cbor(serialized_document, None, owner_id)?;
@@ -48,6 +49,7 @@ impl Drive {
             owner_id,
             block_time,
             apply,
+            with_fee_breakdown,
             storage_flags,
             transaction,
         )
@@ -61,9 +63,10 @@ impl Drive {
         owner_id: Option<&[u8]>,
         block_time: f64,
         apply: bool,
+        with_fee_breakdown: bool,
         storage_flags: StorageFlags,
         transaction: TransactionArg,
-    ) -> Result<(i64, u64), Error> {
+    ) -> Result<FeeResult, Error> {
         let document = Document::from_cbor(serialized_document, None, owner_id)?;
 
         self.update_document_for_contract(
@@ -74,6 +77,7 @@ impl Drive {
             owner_id,
             block_time,
             apply,
+            with_fee_breakdown,
             storage_flags,
             transaction,
         )
@@ -88,9 +92,10 @@ impl Drive {
         owner_id: Option<&[u8]>,
         block_time: f64,
         apply: bool,
+        with_fee_breakdown: bool,
         storage_flags: StorageFlags,
         transaction: TransactionArg,
-    ) -> Result<(i64, u64), Error> {
+    ) -> Result<FeeResult, Error> {
         let mut drive_operations: Vec<DriveOperation> = vec![];
 
         let document_type = contract.document_type_for_name(document_type_name)?;
@@ -119,8 +124,12 @@ impl Drive {
             transaction,
             &mut drive_operations,
         )?;
-        let fees = calculate_fee(None, Some(drive_operations))?;
-        Ok(fees)
+        self.calculate_fee(
+            None,
+            Some(drive_operations),
+            with_fee_breakdown,
+            transaction,
+        )
     }
 
     pub(crate) fn update_document_for_contract_operations(
@@ -143,18 +152,38 @@ impl Drive {
             .document_info
             .is_document_and_serialization()
         {
-            // todo: right now let's say the worst case scenario for an update is that all the data must be added again
+            // the worst case scenario for an update is that all the data is removed and
+            // must be added again
+            if let DocumentSize(_) = document_and_contract_info.document_info {
+                self.add_worst_case_delete_document_operations(
+                    document_and_contract_info.document_type,
+                    drive_operations,
+                )?;
+            }
             self.add_document_for_contract_operations(
                 document_and_contract_info,
                 false,
                 block_time,
                 apply,
                 transaction,
-                &mut batch_operations,
+                drive_operations,
             )?;
             return Ok(());
         }
 
+        if self
+            .fetch_contract_archival_time(
+                document_and_contract_info.contract.id.as_bytes(),
+                transaction,
+                drive_operations,
+            )?
+            .is_some()
+        {
+            return Err(Error::Drive(DriveError::ContractArchived(
+                "documents of archived contracts can not be updated",
+            )));
+        }
+
         let contract = document_and_contract_info.contract;
         let document_type = document_and_contract_info.document_type;
         let owner_id = document_and_contract_info.owner_id;
@@ -212,6 +241,15 @@ impl Drive {
             }
             .unwrap();
 
+            if !document_type.documents_keep_history {
+                // the replaced document is refunded to the epoch it was paid in, documents
+                // keeping history keep their previous revisions
+                batch_operations.push(DriveOperation::for_removed_key_element(
+                    document.id.as_slice(),
+                    &old_document_element,
+                )?);
+            }
+
             // we need to store the document for it's primary key
             // we should be overriding if the document_type does not have history enabled
             self.add_document_to_primary_storage(
@@ -236,9 +274,17 @@ impl Drive {
                     )))
                 }?;
 
+            let indices = self.document_type_indices_for_document(
+                contract,
+                document_type,
+                Some(document.id.as_slice()),
+                transaction,
+                &mut batch_operations,
+            )?;
+
             let mut batch_insertion_cache: HashSet<Vec<Vec<u8>>> = HashSet::new();
             // fourth we need to store a reference to the document for each index
-            for index in &document_type.indices {
+            for index in indices {
                 // at this point the contract path is to the contract documents
                 // for each index the top index component will already have been added
                 // when the contract itself was created
@@ -394,33 +440,32 @@ impl Drive {
                     // we first need to delete the old values
                     // unique indexes will be stored under key "0"
                     // non unique indices should have a tree at key "0" that has all elements based off of primary key
-                    if !index.unique {
+                    let old_reference_key: &[u8] = if !index.unique {
                         old_index_path.push(vec![0]);
-
-                        let old_index_path_slices: Vec<&[u8]> =
-                            old_index_path.iter().map(|x| x.as_slice()).collect();
-
-                        // here we should return an error if the element already exists
-                        self.batch_delete_up_tree_while_empty(
-                            old_index_path_slices,
-                            document.id.as_slice(),
-                            Some(CONTRACT_DOCUMENTS_PATH_HEIGHT),
-                            transaction,
-                            &mut batch_operations,
-                        )?;
+                        document.id.as_slice()
                     } else {
-                        let old_index_path_slices: Vec<&[u8]> =
-                            old_index_path.iter().map(|x| x.as_slice()).collect();
-
-                        // here we should return an error if the element already exists
-                        self.batch_delete_up_tree_while_empty(
-                            old_index_path_slices,
-                            &[0],
-                            Some(CONTRACT_DOCUMENTS_PATH_HEIGHT),
-                            transaction,
-                            &mut batch_operations,
-                        )?;
-                    }
+                        &[0]
+                    };
+
+                    let old_index_path_slices: Vec<&[u8]> =
+                        old_index_path.iter().map(|x| x.as_slice()).collect();
+
+                    // the reference keeps the storage flags of the document version that added it
+                    self.add_storage_removal_operation(
+                        old_index_path_slices.clone(),
+                        old_reference_key,
+                        transaction,
+                        &mut batch_operations,
+                    )?;
+
+                    // here we should return an error if the element already exists
+                    self.batch_delete_up_tree_while_empty(
+                        old_index_path_slices,
+                        old_reference_key,
+                        Some(CONTRACT_DOCUMENTS_PATH_HEIGHT),
+                        transaction,
+                        &mut batch_operations,
+                    )?;
 
                     // unique indexes will be stored under key "0"
                     // non unique indices should have a tree at key "0" that has all elements based off of primary key
@@ -505,6 +550,7 @@ mod tests {
                 None,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -523,6 +569,7 @@ mod tests {
                 true,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -540,6 +587,7 @@ mod tests {
                 None,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -565,6 +613,7 @@ mod tests {
                 None,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -598,6 +647,7 @@ mod tests {
                 true,
                 0f64,
                 true,
+                false,
                 None,
             )
             .expect("should create alice profile");
@@ -623,6 +673,7 @@ mod tests {
                 None,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -635,6 +686,106 @@ mod tests {
         assert_eq!(results_no_transaction.len(), 1);
     }
 
+    #[test]
+    fn test_update_and_delete_document_refunds() {
+        let tmp_dir = TempDir::new().unwrap();
+        let drive: Drive = Drive::open(tmp_dir, None).expect("expected to open Drive successfully");
+
+        drive
+            .create_initial_state_structure(None)
+            .expect("expected to create root tree successfully");
+
+        let contract_cbor = hex::decode("01000000a5632469645820b0248cd9a27f86d05badf475dd9ff574d63219cd60c52e2be1e540c2fdd713336724736368656d61783468747470733a2f2f736368656d612e646173682e6f72672f6470702d302d342d302f6d6574612f646174612d636f6e7472616374676f776e6572496458204c9bf0db6ae315c85465e9ef26e6a006de9673731d08d14881945ddef1b5c5f26776657273696f6e0169646f63756d656e7473a267636f6e74616374a56474797065666f626a65637467696e646963657381a3646e616d656f6f6e7765724964546f55736572496466756e69717565f56a70726f7065727469657382a168246f776e6572496463617363a168746f557365724964636173636872657175697265648268746f557365724964697075626c69634b65796a70726f70657274696573a268746f557365724964a56474797065656172726179686d61784974656d731820686d696e4974656d73182069627974654172726179f570636f6e74656e744d656469615479706578216170706c69636174696f6e2f782e646173682e6470702e6964656e746966696572697075626c69634b6579a36474797065656172726179686d61784974656d73182169627974654172726179f5746164646974696f6e616c50726f70657274696573f46770726f66696c65a56474797065666f626a65637467696e646963657381a3646e616d65676f776e6572496466756e69717565f56a70726f7065727469657381a168246f776e6572496463617363687265717569726564826961766174617255726c6561626f75746a70726f70657274696573a26561626f7574a2647479706566737472696e67696d61784c656e67746818ff6961766174617255726ca3647479706566737472696e6766666f726d61746375726c696d61784c656e67746818ff746164646974696f6e616c50726f70657274696573f4").unwrap();
+
+        let contract = <Contract as DriveContractExt>::from_cbor(contract_cbor.as_slice(), None)
+            .expect("expected to create contract");
+        drive
+            .apply_contract_cbor(
+                contract_cbor.clone(),
+                None,
+                0f64,
+                true,
+                false,
+                StorageFlags::default(),
+                None,
+            )
+            .expect("expected to apply contract successfully");
+
+        let alice_profile_cbor = hex::decode("01000000a763246964582035edfec54aea574df968990abb47b39c206abe5c43a6157885f62958a1f1230c6524747970656770726f66696c656561626f75746a4920616d20416c69636568246f776e65724964582041d52f93f6f7c5af79ce994381c90df73cce2863d3850b9c05ef586ff0fe795f69247265766973696f6e016961766174617255726c7819687474703a2f2f746573742e636f6d2f616c6963652e6a70676f2464617461436f6e747261637449645820b0248cd9a27f86d05badf475dd9ff574d63219cd60c52e2be1e540c2fdd71333").unwrap();
+
+        let alice_profile = Document::from_cbor(alice_profile_cbor.as_slice(), None, None)
+            .expect("expected to get a document");
+
+        let document_type = contract
+            .document_type_for_name("profile")
+            .expect("expected to get a document type");
+
+        drive
+            .add_document_for_contract(
+                DocumentAndContractInfo {
+                    document_info: DocumentAndSerialization((
+                        &alice_profile,
+                        alice_profile_cbor.as_slice(),
+                        &StorageFlags { epoch: 0 },
+                    )),
+                    contract: &contract,
+                    document_type,
+                    owner_id: None,
+                },
+                false,
+                0f64,
+                true,
+                false,
+                None,
+            )
+            .expect("should create alice profile");
+
+        let updated_alice_profile_cbor = hex::decode("01000000a763246964582035edfec54aea574df968990abb47b39c206abe5c43a6157885f62958a1f1230c6524747970656770726f66696c656561626f75746a4920616d20416c69636568246f776e65724964582041d52f93f6f7c5af79ce994381c90df73cce2863d3850b9c05ef586ff0fe795f69247265766973696f6e026961766174617255726c781a687474703a2f2f746573742e636f6d2f616c696365322e6a70676f2464617461436f6e747261637449645820b0248cd9a27f86d05badf475dd9ff574d63219cd60c52e2be1e540c2fdd71333").unwrap();
+
+        // the replaced document was paid in epoch 0
+        let refunds = drive
+            .update_document_for_contract_cbor(
+                updated_alice_profile_cbor.as_slice(),
+                contract_cbor.as_slice(),
+                "profile",
+                None,
+                0f64,
+                true,
+                false,
+                StorageFlags { epoch: 1 },
+                None,
+            )
+            .expect("should update alice profile")
+            .refunds_by_epoch;
+
+        assert_eq!(refunds.len(), 1);
+        assert_eq!(refunds[0].0, 0);
+        assert_ne!(refunds[0].1, 0);
+
+        // the document was paid in epoch 1, its unchanged owner id index reference in epoch 0
+        let refunds = drive
+            .delete_document_for_contract(
+                alice_profile.id.as_slice(),
+                &contract,
+                "profile",
+                None,
+                true,
+                false,
+                None,
+            )
+            .expect("should delete alice profile")
+            .refunds_by_epoch;
+
+        assert_eq!(
+            refunds
+                .iter()
+                .map(|(epoch, _)| *epoch)
+                .collect::<Vec<u16>>(),
+            vec![0, 1]
+        );
+        assert!(refunds.iter().all(|(_, refund)| *refund > 0));
+    }
+
     #[test]
     fn test_create_and_update_document_in_different_transactions() {
         let tmp_dir = TempDir::new().unwrap();
@@ -656,6 +807,7 @@ mod tests {
                 None,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -689,6 +841,7 @@ mod tests {
                 true,
                 0f64,
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("should create alice profile");
@@ -728,6 +881,7 @@ mod tests {
                 None,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -767,6 +921,7 @@ mod tests {
                 None,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -800,6 +955,7 @@ mod tests {
                 true,
                 0f64,
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("should create alice profile");
@@ -834,6 +990,7 @@ mod tests {
                 "profile",
                 None,
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to delete document");
@@ -867,6 +1024,7 @@ mod tests {
                 None,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -923,6 +1081,7 @@ mod tests {
                 None,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -954,6 +1113,7 @@ mod tests {
                 true,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -984,6 +1144,7 @@ mod tests {
                 None,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 None,
             )
@@ -1002,6 +1163,7 @@ mod tests {
                 "indexedDocument",
                 None,
                 true,
+                false,
                 None,
             )
             .expect("should delete document");
@@ -1040,6 +1202,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -1053,6 +1216,7 @@ mod tests {
                 Some(&random_owner_id),
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -1067,6 +1231,7 @@ mod tests {
                 true,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -1111,6 +1276,7 @@ mod tests {
                 false,
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -1124,6 +1290,7 @@ mod tests {
                 Some(&random_owner_id),
                 0f64,
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&db_transaction),
             )
@@ -1176,6 +1343,7 @@ mod tests {
                 true,
                 block_time as f64,
                 true,
+                false,
                 transaction,
             )
             .expect("expected to add document");
@@ -1192,6 +1360,7 @@ mod tests {
             has_raw_enabled: using_has_raw,
             default_genesis_time: Some(0),
             encoding: DriveEncoding::DriveCbor,
+            ..Default::default()
         };
         let tmp_dir = TempDir::new().unwrap();
 
@@ -68,7 +68,7 @@ impl Drive {
         }
     }
 
-    pub(crate) fn get_epoch_fee_multiplier(
+    pub fn get_epoch_fee_multiplier(
         &self,
         epoch_tree: &Epoch,
         transaction: TransactionArg,
@@ -0,0 +1,353 @@
+use crate::drive::fee_pools::pools_path;
+use crate::drive::Drive;
+use crate::error::fee::FeeError;
+use crate::error::Error;
+use crate::fee::fee_schedule::FeeSchedule;
+use crate::fee_pools::epochs::Epoch;
+use crate::fee_pools::epochs_root_tree_key_constants::{
+    KEY_CURRENT_EPOCH_INDEX, KEY_FEE_MULTIPLIER,
+};
+use crate::fee_pools::{update_fee_multiplier_operation, DEFAULT_FEE_MULTIPLIER};
+use grovedb::{Element, TransactionArg};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+
+impl Drive {
+    /// Returns the fee multiplier set by governance, it is persisted into the next epoch
+    /// on epoch change
+    pub fn get_fee_multiplier(&self, transaction: TransactionArg) -> Result<f64, Error> {
+        let element = self
+            .grove
+            .get(pools_path(), KEY_FEE_MULTIPLIER.as_slice(), transaction)
+            .unwrap()
+            .map(Some)
+            .or_else(|e| match e {
+                // pools created before the multiplier was introduced
+                grovedb::Error::PathKeyNotFound(_) => Ok(None),
+                _ => Err(e),
+            })?;
+
+        match element {
+            None => Ok(DEFAULT_FEE_MULTIPLIER),
+            Some(Element::Item(item, _)) => Ok(f64::from_be_bytes(
+                item.as_slice().try_into().map_err(|_| {
+                    Error::Fee(FeeError::CorruptedMultiplierInvalidItemLength(
+                        "fee multiplier item have an invalid length",
+                    ))
+                })?,
+            )),
+            Some(_) => Err(Error::Fee(FeeError::CorruptedMultiplierNotItem(
+                "fee multiplier must be an item",
+            ))),
+        }
+    }
+
+    /// Returns the index of the current epoch, none before the first epoch started
+    pub fn get_current_epoch_index(
+        &self,
+        transaction: TransactionArg,
+    ) -> Result<Option<u16>, Error> {
+        let element = self
+            .grove
+            .get(
+                pools_path(),
+                KEY_CURRENT_EPOCH_INDEX.as_slice(),
+                transaction,
+            )
+            .unwrap()
+            .map(Some)
+            .or_else(|e| match e {
+                grovedb::Error::PathKeyNotFound(_) => Ok(None),
+                _ => Err(e),
+            })?;
+
+        match element {
+            None => Ok(None),
+            Some(Element::Item(item, _)) => Ok(Some(u16::from_be_bytes(
+                item.as_slice().try_into().map_err(|_| {
+                    Error::Fee(FeeError::CorruptedCurrentEpochIndexItemLength(
+                        "current epoch index item have an invalid length",
+                    ))
+                })?,
+            ))),
+            Some(_) => Err(Error::Fee(FeeError::CorruptedCurrentEpochIndexNotItem(
+                "current epoch index must be an item",
+            ))),
+        }
+    }
+
+    /// Returns the fee multiplier persisted for the current epoch, it is applied to
+    /// processing fees
+    pub fn get_current_epoch_fee_multiplier(
+        &self,
+        transaction: TransactionArg,
+    ) -> Result<f64, Error> {
+        match self.get_current_epoch_index(transaction)? {
+            None => Ok(DEFAULT_FEE_MULTIPLIER),
+            Some(epoch_index) => {
+                self.get_epoch_fee_multiplier(&Epoch::new(epoch_index), transaction)
+            }
+        }
+    }
+
+    /// Reads the fee schedule and the multiplier of the current epoch into the cache,
+    /// fees are calculated with them until they are cached again at the next block
+    pub fn cache_current_epoch_fees(&self, transaction: TransactionArg) -> Result<(), Error> {
+        let (fee_schedule, fee_multiplier) = self.read_current_epoch_fees(transaction)?;
+
+        let mut cache = self.cache.borrow_mut();
+        cache.current_epoch_fee_schedule = Some(fee_schedule);
+        cache.current_epoch_fee_multiplier = Some(fee_multiplier);
+
+        Ok(())
+    }
+
+    /// Returns the fee schedule and the multiplier of the current epoch, from the cache
+    /// if they were cached
+    pub fn get_current_epoch_fees(
+        &self,
+        transaction: TransactionArg,
+    ) -> Result<(FeeSchedule, Decimal), Error> {
+        let cache = self.cache.borrow();
+        match (
+            &cache.current_epoch_fee_schedule,
+            cache.current_epoch_fee_multiplier,
+        ) {
+            (Some(fee_schedule), Some(fee_multiplier)) => {
+                Ok((fee_schedule.clone(), fee_multiplier))
+            }
+            _ => {
+                drop(cache);
+                self.read_current_epoch_fees(transaction)
+            }
+        }
+    }
+
+    fn read_current_epoch_fees(
+        &self,
+        transaction: TransactionArg,
+    ) -> Result<(FeeSchedule, Decimal), Error> {
+        let fee_schedule = self.get_fee_schedule(transaction)?.clone();
+        let fee_multiplier = Decimal::from_f64(self.get_current_epoch_fee_multiplier(transaction)?)
+            .ok_or(Error::Fee(FeeError::InvalidFeeMultiplier(
+                "fee multiplier must be a decimal number",
+            )))?;
+
+        Ok((fee_schedule, fee_multiplier))
+    }
+
+    /// Sets the fee multiplier, fees are only charged with it from the next epoch on
+    pub fn update_fee_multiplier(
+        &self,
+        multiplier: f64,
+        transaction: TransactionArg,
+    ) -> Result<(), Error> {
+        if !multiplier.is_finite() || multiplier <= 0f64 {
+            return Err(Error::Fee(FeeError::InvalidFeeMultiplier(
+                "fee multiplier must be a positive number",
+            )));
+        }
+
+        self.grove_apply_operation(
+            update_fee_multiplier_operation(multiplier),
+            false,
+            transaction,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod get_fee_multiplier {
+        use crate::common::helpers::setup::{
+            setup_drive, setup_drive_with_initial_state_structure,
+        };
+        use crate::drive::fee_pools::pools_path;
+        use crate::error;
+        use crate::error::fee::FeeError;
+        use crate::fee_pools::epochs_root_tree_key_constants::KEY_FEE_MULTIPLIER;
+        use crate::fee_pools::DEFAULT_FEE_MULTIPLIER;
+        use grovedb::Element;
+
+        #[test]
+        fn test_error_if_fee_pools_tree_is_not_initiated() {
+            let drive = setup_drive(None);
+            let transaction = drive.grove.start_transaction();
+
+            match drive.get_fee_multiplier(Some(&transaction)) {
+                Ok(_) => assert!(
+                    false,
+                    "should not be able to get fee multiplier if fee pools tree is not initialized"
+                ),
+                Err(e) => match e {
+                    error::Error::GroveDB(grovedb::Error::PathNotFound(_)) => assert!(true),
+                    _ => assert!(false, "invalid error type"),
+                },
+            }
+        }
+
+        #[test]
+        fn test_default_value_is_set() {
+            let drive = setup_drive_with_initial_state_structure();
+            let transaction = drive.grove.start_transaction();
+
+            let fee_multiplier = drive
+                .get_fee_multiplier(Some(&transaction))
+                .expect("should get fee multiplier");
+
+            assert_eq!(fee_multiplier, DEFAULT_FEE_MULTIPLIER);
+        }
+
+        #[test]
+        fn test_error_if_value_has_invalid_length() {
+            let drive = setup_drive_with_initial_state_structure();
+            let transaction = drive.grove.start_transaction();
+
+            drive
+                .grove
+                .insert(
+                    pools_path(),
+                    KEY_FEE_MULTIPLIER.as_slice(),
+                    Element::Item(u128::MAX.to_be_bytes().to_vec(), None),
+                    Some(&transaction),
+                )
+                .unwrap()
+                .expect("should insert invalid data");
+
+            match drive.get_fee_multiplier(Some(&transaction)) {
+                Ok(_) => assert!(false, "must be an error"),
+                Err(e) => match e {
+                    error::Error::Fee(FeeError::CorruptedMultiplierInvalidItemLength(_)) => {
+                        assert!(true)
+                    }
+                    _ => assert!(false, "invalid error type"),
+                },
+            }
+        }
+    }
+
+    mod get_current_epoch_fee_multiplier {
+        use crate::common::helpers::setup::setup_drive_with_initial_state_structure;
+        use crate::drive::batch::GroveDbOpBatch;
+        use crate::fee_pools::epochs::Epoch;
+        use crate::fee_pools::{update_current_epoch_index_operation, DEFAULT_FEE_MULTIPLIER};
+
+        #[test]
+        fn test_default_value_before_the_first_epoch() {
+            let drive = setup_drive_with_initial_state_structure();
+            let transaction = drive.grove.start_transaction();
+
+            let fee_multiplier = drive
+                .get_current_epoch_fee_multiplier(Some(&transaction))
+                .expect("should get current epoch fee multiplier");
+
+            assert_eq!(fee_multiplier, DEFAULT_FEE_MULTIPLIER);
+        }
+
+        #[test]
+        fn test_governance_update_applies_from_the_next_epoch() {
+            let drive = setup_drive_with_initial_state_structure();
+            let transaction = drive.grove.start_transaction();
+
+            let mut batch = GroveDbOpBatch::new();
+            Epoch::new(0).add_init_current_operations(1.5, 1, 1, &mut batch);
+            batch.push(update_current_epoch_index_operation(0));
+            drive
+                .grove_apply_batch(batch, false, Some(&transaction))
+                .expect("should apply batch");
+
+            drive
+                .update_fee_multiplier(3.0, Some(&transaction))
+                .expect("should update fee multiplier");
+
+            let fee_multiplier = drive
+                .get_current_epoch_fee_multiplier(Some(&transaction))
+                .expect("should get current epoch fee multiplier");
+
+            assert_eq!(fee_multiplier, 1.5);
+        }
+    }
+
+    mod cache_current_epoch_fees {
+        use crate::common::helpers::setup::setup_drive_with_initial_state_structure;
+        use crate::drive::batch::GroveDbOpBatch;
+        use crate::fee_pools::epochs::Epoch;
+        use crate::fee_pools::{update_current_epoch_index_operation, DEFAULT_FEE_MULTIPLIER};
+        use rust_decimal::prelude::FromPrimitive;
+        use rust_decimal::Decimal;
+
+        #[test]
+        fn test_cached_fees_are_used_until_cached_again() {
+            let drive = setup_drive_with_initial_state_structure();
+            let transaction = drive.grove.start_transaction();
+
+            drive
+                .cache_current_epoch_fees(Some(&transaction))
+                .expect("should cache current epoch fees");
+
+            let mut batch = GroveDbOpBatch::new();
+            Epoch::new(0).add_init_current_operations(1.5, 1, 1, &mut batch);
+            batch.push(update_current_epoch_index_operation(0));
+            drive
+                .grove_apply_batch(batch, false, Some(&transaction))
+                .expect("should apply batch");
+
+            let (_, fee_multiplier) = drive
+                .get_current_epoch_fees(Some(&transaction))
+                .expect("should get current epoch fees");
+
+            assert_eq!(
+                fee_multiplier,
+                Decimal::from_f64(DEFAULT_FEE_MULTIPLIER).expect("should be a decimal")
+            );
+
+            drive
+                .cache_current_epoch_fees(Some(&transaction))
+                .expect("should cache current epoch fees");
+
+            let (_, fee_multiplier) = drive
+                .get_current_epoch_fees(Some(&transaction))
+                .expect("should get current epoch fees");
+
+            assert_eq!(fee_multiplier, Decimal::new(15, 1));
+        }
+    }
+
+    mod update_fee_multiplier {
+        use crate::common::helpers::setup::setup_drive_with_initial_state_structure;
+        use crate::error;
+        use crate::error::fee::FeeError;
+
+        #[test]
+        fn test_update_and_get_value() {
+            let drive = setup_drive_with_initial_state_structure();
+            let transaction = drive.grove.start_transaction();
+
+            drive
+                .update_fee_multiplier(1.5, Some(&transaction))
+                .expect("should update fee multiplier");
+
+            let fee_multiplier = drive
+                .get_fee_multiplier(Some(&transaction))
+                .expect("should get fee multiplier");
+
+            assert_eq!(fee_multiplier, 1.5);
+        }
+
+        #[test]
+        fn test_error_if_multiplier_is_not_positive() {
+            let drive = setup_drive_with_initial_state_structure();
+            let transaction = drive.grove.start_transaction();
+
+            match drive.update_fee_multiplier(0f64, Some(&transaction)) {
+                Ok(_) => assert!(false, "must be an error"),
+                Err(e) => match e {
+                    error::Error::Fee(FeeError::InvalidFeeMultiplier(_)) => assert!(true),
+                    _ => assert!(false, "invalid error type"),
+                },
+            }
+        }
+    }
+}
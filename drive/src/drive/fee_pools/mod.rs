@@ -2,6 +2,7 @@ use crate::drive::RootTree;
 use crate::fee_pools::epochs_root_tree_key_constants::KEY_STORAGE_FEE_POOL;
 
 pub mod epochs;
+pub mod fee_multiplier;
 pub mod storage_fee_distribution_pool;
 pub mod unpaid_epoch;
 
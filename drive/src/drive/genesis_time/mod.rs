@@ -4,11 +4,36 @@ use crate::drive::genesis_time::operations::update_genesis_time_operation;
 use crate::drive::{Drive, RootTree};
 use crate::error::drive::DriveError;
 use crate::error::Error;
-use grovedb::{Element, TransactionArg};
+use crate::fee::op::DriveOperation;
+use grovedb::{Element, GroveDb, PathQuery, Query, SizedQuery, TransactionArg};
 use std::array::TryFromSliceError;
 
 const KEY_GENESIS_TIME: &[u8; 1] = b"g";
 
+fn genesis_time_path_query() -> PathQuery {
+    let mut query = Query::new();
+    query.insert_key(KEY_GENESIS_TIME.to_vec());
+    PathQuery::new(
+        vec![vec![RootTree::Pools as u8]],
+        SizedQuery::new(query, Some(1), None),
+    )
+}
+
+fn genesis_time_from_element(element: Element) -> Result<u64, Error> {
+    if let Element::Item(item, _) = element {
+        let genesis_time =
+            u64::from_be_bytes(item.as_slice().try_into().map_err(|e: TryFromSliceError| {
+                Error::Drive(DriveError::CorruptedGenesisTimeInvalidItemLength(
+                    e.to_string(),
+                ))
+            })?);
+
+        Ok(genesis_time)
+    } else {
+        Err(Error::Drive(DriveError::CorruptedGenesisTimeNotItem()))
+    }
+}
+
 impl Drive {
     pub fn get_genesis_time(&self, transaction: TransactionArg) -> Result<Option<u64>, Error> {
         // let's first check the cache
@@ -41,21 +66,37 @@ impl Drive {
                 _ => Err(e),
             })?;
 
-        if let Some(Element::Item(item, _)) = element {
-            let genesis_time = u64::from_be_bytes(item.as_slice().try_into().map_err(
-                |e: TryFromSliceError| {
-                    Error::Drive(DriveError::CorruptedGenesisTimeInvalidItemLength(
-                        e.to_string(),
-                    ))
-                },
-            )?);
-
-            Ok(Some(genesis_time))
+        if let Some(element) = element {
+            Ok(Some(genesis_time_from_element(element)?))
         } else {
             Err(Error::Drive(DriveError::CorruptedGenesisTimeNotItem()))
         }
     }
 
+    pub fn prove_genesis_time(&self, transaction: TransactionArg) -> Result<Vec<u8>, Error> {
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.grove_get_proved_path_query(
+            &genesis_time_path_query(),
+            transaction,
+            &mut drive_operations,
+        )
+    }
+
+    pub fn verify_genesis_time(proof: &[u8]) -> Result<([u8; 32], Option<u64>), Error> {
+        let (root_hash, proved_key_values) =
+            GroveDb::verify_query(proof, &genesis_time_path_query()).map_err(Error::GroveDB)?;
+
+        let genesis_time = proved_key_values
+            .first()
+            .map(|(_, value)| {
+                genesis_time_from_element(Element::deserialize(value).map_err(Error::GroveDB)?)
+            })
+            .transpose()?;
+
+        Ok((root_hash, genesis_time))
+    }
+
     pub fn init_genesis_time(
         &self,
         genesis_time_ms: u64,
@@ -159,5 +200,25 @@ mod tests {
                 None => assert!(false, "should be present"),
             }
         }
+
+        #[test]
+        fn test_prove_and_verify_genesis_time() {
+            let drive = setup_drive_with_initial_state_structure();
+
+            let genesis_time_ms = 100;
+
+            drive
+                .init_genesis_time(genesis_time_ms, None)
+                .expect("should update genesis time");
+
+            let proof = drive
+                .prove_genesis_time(None)
+                .expect("should prove genesis time");
+
+            let (_, proved_genesis_time_ms) = crate::drive::Drive::verify_genesis_time(&proof)
+                .expect("should verify genesis time");
+
+            assert_eq!(proved_genesis_time_ms, Some(genesis_time_ms));
+        }
     }
 }
@@ -429,6 +429,61 @@ impl Drive {
         }
     }
 
+    /// Like grove_get but references are returned instead of the elements they refer to
+    pub(crate) fn grove_get_raw<'a, 'c, P>(
+        &'a self,
+        path: P,
+        key_value_info: KeyValueInfo<'c>,
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<Option<Element>, Error>
+    where
+        P: IntoIterator<Item = &'c [u8]>,
+        <P as IntoIterator>::IntoIter: ExactSizeIterator + DoubleEndedIterator + Clone,
+    {
+        let path_iter = path.into_iter();
+        match key_value_info {
+            KeyRefRequest(key) => {
+                let CostContext { value, cost } =
+                    self.grove.get_raw(path_iter.clone(), key, transaction);
+                drive_operations.push(CalculatedCostOperation(cost));
+                Ok(Some(value.map_err(Error::GroveDB)?))
+            }
+            KeyValueMaxSize((key_size, value_size)) => {
+                drive_operations.push(CostCalculationQueryOperation(
+                    SizesOfQueryOperation::for_value_retrieval_in_path(
+                        key_size, path_iter, value_size,
+                    ),
+                ));
+                Ok(None)
+            }
+        }
+    }
+
+    /// Adds the refund of the element about to be deleted at the path and key
+    pub(crate) fn add_storage_removal_operation<'c, P>(
+        &self,
+        path: P,
+        key: &'c [u8],
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<(), Error>
+    where
+        P: IntoIterator<Item = &'c [u8]>,
+        <P as IntoIterator>::IntoIter: ExactSizeIterator + DoubleEndedIterator + Clone,
+    {
+        match self.grove_get_raw(path, KeyRefRequest(key), transaction, drive_operations) {
+            Ok(Some(element)) => {
+                drive_operations.push(DriveOperation::for_removed_key_element(key, &element)?);
+                Ok(())
+            }
+            Ok(None)
+            | Err(Error::GroveDB(GroveError::PathKeyNotFound(_)))
+            | Err(Error::GroveDB(GroveError::PathNotFound(_))) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
     pub(crate) fn grove_get_path_query(
         &self,
         path_query: &PathQuery,
@@ -0,0 +1,352 @@
+use grovedb::batch::Op;
+use grovedb::{Element, ElementFlags, PathQuery, Query, SizedQuery, TransactionArg};
+
+use crate::drive::batch::GroveDbOpBatch;
+use crate::drive::flags::StorageFlags;
+use crate::drive::identity::{identity_path, identity_vec_path, IDENTITY_BALANCE_KEY};
+use crate::drive::Drive;
+use crate::error::drive::DriveError;
+use crate::error::identity::IdentityError;
+use crate::error::Error;
+use crate::fee::op::DriveOperation;
+use crate::fee::result::FeeResult;
+
+pub(crate) fn balance_from_element(element: &Element) -> Result<u64, Error> {
+    if let Element::Item(balance_bytes, _) = element {
+        Ok(u64::from_be_bytes(
+            balance_bytes.as_slice().try_into().map_err(|_| {
+                Error::Drive(DriveError::CorruptedIdentityBalance(
+                    "identity balance item have an invalid length",
+                ))
+            })?,
+        ))
+    } else {
+        Err(Error::Drive(DriveError::CorruptedIdentityBalance(
+            "identity balance must be an item",
+        )))
+    }
+}
+
+pub(crate) fn identity_balance_path_query(identity_id: &[u8]) -> PathQuery {
+    let mut query = Query::new();
+    query.insert_key(IDENTITY_BALANCE_KEY.to_vec());
+    PathQuery::new(
+        identity_vec_path(identity_id),
+        SizedQuery::new(query, Some(1), None),
+    )
+}
+
+impl Drive {
+    pub(crate) fn add_insert_identity_balance_operation(
+        &self,
+        identity_id: &[u8],
+        balance: u64,
+        element_flags: ElementFlags,
+        batch: &mut GroveDbOpBatch,
+    ) {
+        let element = Element::Item(balance.to_be_bytes().to_vec(), element_flags);
+
+        // The balance could already be changed by this batch (i.e. several rewards paid
+        // to the same identity in one block), in that case we replace the pending operation
+        let pending_operation = batch
+            .operations
+            .iter_mut()
+            .find(|op| op.path == identity_vec_path(identity_id) && op.key == IDENTITY_BALANCE_KEY);
+
+        match pending_operation {
+            Some(op) => op.op = Op::Insert { element },
+            None => batch.add_insert(
+                identity_vec_path(identity_id),
+                IDENTITY_BALANCE_KEY.to_vec(),
+                element,
+            ),
+        }
+    }
+
+    /// Fetches the balance and its element flags taking into account operations
+    /// that are already in the batch
+    fn fetch_identity_balance_with_flags_for_batch(
+        &self,
+        identity_id: &[u8],
+        transaction: TransactionArg,
+        batch: &GroveDbOpBatch,
+    ) -> Result<(u64, ElementFlags), Error> {
+        let pending_element = batch.operations.iter().find_map(|op| {
+            if op.path == identity_vec_path(identity_id) && op.key == IDENTITY_BALANCE_KEY {
+                if let Op::Insert { element } = &op.op {
+                    return Some(element.clone());
+                }
+            }
+            None
+        });
+
+        let element = match pending_element {
+            Some(element) => element,
+            None => self
+                .fetch_identity_balance_element(identity_id, transaction)?
+                .ok_or(Error::Identity(IdentityError::IdentityNotFound(
+                    "identity balance not found",
+                )))?,
+        };
+
+        let balance = balance_from_element(&element)?;
+
+        Ok((balance, element.get_flags().clone()))
+    }
+
+    fn fetch_identity_balance_element(
+        &self,
+        identity_id: &[u8],
+        transaction: TransactionArg,
+    ) -> Result<Option<Element>, Error> {
+        self.grove
+            .get(
+                identity_path(identity_id),
+                &IDENTITY_BALANCE_KEY,
+                transaction,
+            )
+            .unwrap()
+            .map(Some)
+            .or_else(|e| match e {
+                grovedb::Error::PathKeyNotFound(_) | grovedb::Error::PathNotFound(_) => Ok(None),
+                _ => Err(Error::GroveDB(e)),
+            })
+    }
+
+    pub fn fetch_identity_balance(
+        &self,
+        identity_id: [u8; 32],
+        transaction: TransactionArg,
+    ) -> Result<Option<u64>, Error> {
+        self.fetch_identity_balance_element(&identity_id, transaction)?
+            .map(|element| balance_from_element(&element))
+            .transpose()
+    }
+
+    pub fn prove_identity_balance(
+        &self,
+        identity_id: [u8; 32],
+        transaction: TransactionArg,
+    ) -> Result<Vec<u8>, Error> {
+        let path_query = identity_balance_path_query(&identity_id);
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.grove_get_proved_path_query(&path_query, transaction, &mut drive_operations)
+    }
+
+    pub fn add_to_identity_balance_operations(
+        &self,
+        identity_id: &[u8],
+        added_balance: u64,
+        transaction: TransactionArg,
+        batch: &mut GroveDbOpBatch,
+    ) -> Result<(), Error> {
+        let (previous_balance, element_flags) =
+            self.fetch_identity_balance_with_flags_for_batch(identity_id, transaction, batch)?;
+
+        let new_balance = previous_balance
+            .checked_add(added_balance)
+            .ok_or(Error::Identity(IdentityError::CriticalBalanceOverflow(
+                "identity balance add overflow error",
+            )))?;
+
+        self.add_insert_identity_balance_operation(identity_id, new_balance, element_flags, batch);
+
+        Ok(())
+    }
+
+    pub fn remove_from_identity_balance_operations(
+        &self,
+        identity_id: &[u8],
+        required_removed_balance: u64,
+        transaction: TransactionArg,
+        batch: &mut GroveDbOpBatch,
+    ) -> Result<(), Error> {
+        let (previous_balance, element_flags) =
+            self.fetch_identity_balance_with_flags_for_batch(identity_id, transaction, batch)?;
+
+        let new_balance = previous_balance
+            .checked_sub(required_removed_balance)
+            .ok_or(Error::Identity(IdentityError::IdentityInsufficientBalance(
+                "identity does not have the required balance",
+            )))?;
+
+        self.add_insert_identity_balance_operation(identity_id, new_balance, element_flags, batch);
+
+        Ok(())
+    }
+
+    pub fn add_to_identity_balance(
+        &self,
+        identity_id: [u8; 32],
+        added_balance: u64,
+        apply: bool,
+        transaction: TransactionArg,
+    ) -> Result<FeeResult, Error> {
+        let mut batch = GroveDbOpBatch::new();
+
+        if apply {
+            self.add_to_identity_balance_operations(
+                &identity_id,
+                added_balance,
+                transaction,
+                &mut batch,
+            )?;
+        } else {
+            self.add_insert_identity_balance_operation(
+                &identity_id,
+                u64::MAX,
+                StorageFlags::default().to_element_flags(),
+                &mut batch,
+            );
+        }
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.apply_batch_grovedb_operations(apply, transaction, batch, &mut drive_operations)?;
+
+        self.calculate_fee(None, Some(drive_operations), false, transaction)
+    }
+
+    pub fn remove_from_identity_balance(
+        &self,
+        identity_id: [u8; 32],
+        required_removed_balance: u64,
+        apply: bool,
+        transaction: TransactionArg,
+    ) -> Result<FeeResult, Error> {
+        let mut batch = GroveDbOpBatch::new();
+
+        if apply {
+            self.remove_from_identity_balance_operations(
+                &identity_id,
+                required_removed_balance,
+                transaction,
+                &mut batch,
+            )?;
+        } else {
+            self.add_insert_identity_balance_operation(
+                &identity_id,
+                u64::MAX,
+                StorageFlags::default().to_element_flags(),
+                &mut batch,
+            );
+        }
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.apply_batch_grovedb_operations(apply, transaction, batch, &mut drive_operations)?;
+
+        self.calculate_fee(None, Some(drive_operations), false, transaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::helpers::identities::create_test_identity;
+    use crate::common::helpers::setup::setup_drive_with_initial_state_structure;
+    use crate::drive::batch::GroveDbOpBatch;
+    use crate::drive::identity::balance::identity_balance_path_query;
+    use crate::error::identity::IdentityError;
+    use crate::error::Error;
+    use grovedb::GroveDb;
+
+    #[test]
+    fn test_add_and_remove_identity_balance() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let transaction = drive.grove.start_transaction();
+
+        let identity = create_test_identity(&drive, [7; 32], Some(&transaction));
+
+        let balance = drive
+            .fetch_identity_balance(identity.id.buffer, Some(&transaction))
+            .expect("should fetch balance");
+
+        assert_eq!(balance, Some(0));
+
+        drive
+            .add_to_identity_balance(identity.id.buffer, 300, true, Some(&transaction))
+            .expect("should add to balance");
+
+        drive
+            .remove_from_identity_balance(identity.id.buffer, 100, true, Some(&transaction))
+            .expect("should remove from balance");
+
+        let result =
+            drive.remove_from_identity_balance(identity.id.buffer, 201, true, Some(&transaction));
+
+        assert!(matches!(
+            result,
+            Err(Error::Identity(IdentityError::IdentityInsufficientBalance(
+                _
+            )))
+        ));
+
+        let balance = drive
+            .fetch_identity_balance(identity.id.buffer, Some(&transaction))
+            .expect("should fetch balance");
+
+        assert_eq!(balance, Some(200));
+
+        let (fetched_identity, _) = drive
+            .fetch_identity(&identity.id.buffer, Some(&transaction))
+            .expect("should fetch identity");
+
+        assert_eq!(fetched_identity.balance, 200);
+
+        let proof = drive
+            .prove_identity_balance(identity.id.buffer, Some(&transaction))
+            .expect("should prove balance");
+
+        let (_, proved_key_values) = GroveDb::verify_query(
+            proof.as_slice(),
+            &identity_balance_path_query(&identity.id.buffer),
+        )
+        .expect("should verify proof");
+
+        assert_eq!(proved_key_values.len(), 1);
+    }
+
+    #[test]
+    fn test_add_to_identity_balance_several_times_in_one_batch() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let transaction = drive.grove.start_transaction();
+
+        let identity = create_test_identity(&drive, [8; 32], Some(&transaction));
+
+        let mut batch = GroveDbOpBatch::new();
+
+        drive
+            .add_to_identity_balance_operations(
+                &identity.id.buffer,
+                10,
+                Some(&transaction),
+                &mut batch,
+            )
+            .expect("should add to balance");
+
+        drive
+            .add_to_identity_balance_operations(
+                &identity.id.buffer,
+                15,
+                Some(&transaction),
+                &mut batch,
+            )
+            .expect("should add to balance");
+
+        assert_eq!(batch.len(), 1);
+
+        drive
+            .grove_apply_batch(batch, false, Some(&transaction))
+            .expect("should apply batch");
+
+        let balance = drive
+            .fetch_identity_balance(identity.id.buffer, Some(&transaction))
+            .expect("should fetch balance");
+
+        assert_eq!(balance, Some(25));
+    }
+}
@@ -0,0 +1,361 @@
+use std::collections::BTreeMap;
+
+use grovedb::batch::Op;
+use grovedb::query_result_type::QueryResultType::{
+    QueryElementResultType, QueryKeyElementPairResultType,
+};
+use grovedb::{Element, PathQuery, Query, SizedQuery, TransactionArg};
+
+use crate::drive::batch::GroveDbOpBatch;
+use crate::drive::flags::StorageFlags;
+use crate::drive::identity::key_hashes::PUBLIC_KEY_HASH_SIZE;
+use crate::drive::identity::keys::{identity_keys_vec_path, IdentityKey};
+use crate::drive::identity::{identities_path, identities_vec_path, identity_vec_path};
+use crate::drive::Drive;
+use crate::error::identity::IdentityError;
+use crate::error::Error;
+use crate::fee::op::DriveOperation;
+use crate::fee::op::DriveOperation::StorageRemovalOperation;
+use crate::fee::result::FeeResult;
+
+fn add_removed_bytes(
+    removed_bytes_by_epoch: &mut BTreeMap<u16, u64>,
+    element: &Element,
+    key_len: usize,
+) -> Result<(), Error> {
+    let storage_flags = StorageFlags::from_element_flags(element.get_flags().clone())?;
+    let removed_bytes = removed_bytes_by_epoch
+        .entry(storage_flags.epoch)
+        .or_default();
+    *removed_bytes += element.node_byte_size(key_len) as u64;
+    Ok(())
+}
+
+/// Only the bytes the replacing element doesn't take again are removed
+fn add_replaced_bytes(
+    removed_bytes_by_epoch: &mut BTreeMap<u16, u64>,
+    element: &Element,
+    replacing_element: &Element,
+    key_len: usize,
+) -> Result<(), Error> {
+    let storage_flags = StorageFlags::from_element_flags(element.get_flags().clone())?;
+    let removed_bytes = removed_bytes_by_epoch
+        .entry(storage_flags.epoch)
+        .or_default();
+    *removed_bytes += element
+        .node_byte_size(key_len)
+        .saturating_sub(replacing_element.node_byte_size(key_len)) as u64;
+    Ok(())
+}
+
+impl Drive {
+    pub fn is_identity_id_tombstoned(
+        &self,
+        identity_id: &[u8],
+        transaction: TransactionArg,
+    ) -> Result<bool, Error> {
+        let element = self
+            .grove
+            .get(identities_path(), identity_id, transaction)
+            .unwrap()
+            .map(Some)
+            .or_else(|e| match e {
+                grovedb::Error::PathKeyNotFound(_) => Ok(None),
+                _ => Err(e),
+            })?;
+
+        Ok(matches!(element, Some(Element::Item(..))))
+    }
+
+    /// Checks the tombstone taking into account operations that are already in the batch
+    pub(crate) fn is_identity_id_tombstoned_for_batch(
+        &self,
+        identity_id: &[u8],
+        transaction: TransactionArg,
+        batch: &GroveDbOpBatch,
+    ) -> Result<bool, Error> {
+        let pending_operation = batch
+            .operations
+            .iter()
+            .rev()
+            .find(|op| op.path == identities_vec_path() && op.key == identity_id);
+
+        match pending_operation.map(|op| &op.op) {
+            Some(Op::Insert { element }) => Ok(matches!(element, Element::Item(..))),
+            Some(Op::Delete) => Ok(false),
+            _ => self.is_identity_id_tombstoned(identity_id, transaction),
+        }
+    }
+
+    /// Removes the identity subtree and its public key hashes. The removed bytes
+    /// are returned grouped by the epoch they were paid in, so they can be refunded.
+    /// A tombstone can be left in place of the identity so its id can't be reused.
+    pub fn delete_identity_operations(
+        &self,
+        identity_id: &[u8],
+        leave_tombstone: bool,
+        transaction: TransactionArg,
+        batch: &mut GroveDbOpBatch,
+    ) -> Result<BTreeMap<u16, u64>, Error> {
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        let mut removed_bytes_by_epoch: BTreeMap<u16, u64> = BTreeMap::new();
+
+        let identity_tree_element = self
+            .grove
+            .get(identities_path(), identity_id, transaction)
+            .unwrap()
+            .map(Some)
+            .or_else(|e| match e {
+                grovedb::Error::PathKeyNotFound(_) => Ok(None),
+                _ => Err(e),
+            })?;
+
+        let identity_tree_element = match identity_tree_element {
+            Some(element @ Element::Tree(..)) => element,
+            _ => {
+                return Err(Error::Identity(IdentityError::IdentityNotFound(
+                    "identity to delete not found",
+                )))
+            }
+        };
+
+        // keys and their hashes
+        let mut keys_query = Query::new();
+        keys_query.insert_all();
+
+        let (keys_result_items, _) = self.grove_get_raw_path_query(
+            &PathQuery::new(
+                identity_keys_vec_path(identity_id),
+                SizedQuery::new(keys_query, None, None),
+            ),
+            transaction,
+            QueryElementResultType,
+            &mut drive_operations,
+        )?;
+
+        for element in keys_result_items.to_elements() {
+            add_removed_bytes(
+                &mut removed_bytes_by_epoch,
+                &element,
+                std::mem::size_of::<dpp::identity::KeyID>(),
+            )?;
+
+            let (identity_key, element_flags) = IdentityKey::from_element(element)?;
+
+            if !identity_key.is_disabled() {
+                add_removed_bytes(
+                    &mut removed_bytes_by_epoch,
+                    &Element::Item(identity_id.to_vec(), element_flags),
+                    PUBLIC_KEY_HASH_SIZE,
+                )?;
+
+                self.add_delete_identity_key_hash_operations(
+                    identity_id,
+                    &identity_key.public_key,
+                    transaction,
+                    batch,
+                )?;
+            }
+
+            batch.add_delete(
+                identity_keys_vec_path(identity_id),
+                identity_key.public_key.id.to_be_bytes().to_vec(),
+            );
+        }
+
+        // identity item, balance, keys tree and revision
+        let mut identity_query = Query::new();
+        identity_query.insert_all();
+
+        let (identity_result_items, _) = self.grove_get_raw_path_query(
+            &PathQuery::new(
+                identity_vec_path(identity_id),
+                SizedQuery::new(identity_query, None, None),
+            ),
+            transaction,
+            QueryKeyElementPairResultType,
+            &mut drive_operations,
+        )?;
+
+        for (key, element) in identity_result_items.to_key_elements() {
+            add_removed_bytes(&mut removed_bytes_by_epoch, &element, key.len())?;
+
+            batch.add_delete(identity_vec_path(identity_id), key);
+        }
+
+        if leave_tombstone {
+            let tombstone = Element::Item(vec![], identity_tree_element.get_flags().clone());
+
+            add_replaced_bytes(
+                &mut removed_bytes_by_epoch,
+                &identity_tree_element,
+                &tombstone,
+                identity_id.len(),
+            )?;
+
+            batch.add_insert(identities_vec_path(), identity_id.to_vec(), tombstone);
+        } else {
+            add_removed_bytes(
+                &mut removed_bytes_by_epoch,
+                &identity_tree_element,
+                identity_id.len(),
+            )?;
+
+            batch.add_delete(identities_vec_path(), identity_id.to_vec());
+        }
+
+        Ok(removed_bytes_by_epoch)
+    }
+
+    pub fn delete_identity(
+        &self,
+        identity_id: [u8; 32],
+        leave_tombstone: bool,
+        apply: bool,
+        transaction: TransactionArg,
+    ) -> Result<FeeResult, Error> {
+        let mut batch = GroveDbOpBatch::new();
+
+        let removed_bytes_by_epoch = self.delete_identity_operations(
+            &identity_id,
+            leave_tombstone,
+            transaction,
+            &mut batch,
+        )?;
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.apply_batch_grovedb_operations(apply, transaction, batch, &mut drive_operations)?;
+
+        drive_operations.extend(
+            removed_bytes_by_epoch
+                .into_iter()
+                .map(|(epoch, removed_bytes)| StorageRemovalOperation(epoch, removed_bytes)),
+        );
+
+        self.calculate_fee(None, Some(drive_operations), false, transaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::helpers::identities::create_test_identity;
+    use crate::common::helpers::setup::setup_drive_with_initial_state_structure;
+    use crate::drive::batch::GroveDbOpBatch;
+    use crate::drive::flags::StorageFlags;
+    use crate::drive::identity::key_hashes::public_key_hash;
+    use crate::error::identity::IdentityError;
+    use crate::error::Error;
+
+    #[test]
+    fn test_delete_identity() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let transaction = drive.grove.start_transaction();
+
+        let identity = create_test_identity(&drive, [11; 32], Some(&transaction));
+
+        let fee_result = drive
+            .delete_identity(identity.id.buffer, false, true, Some(&transaction))
+            .expect("should delete identity");
+
+        assert_eq!(fee_result.removed_bytes_by_epoch.len(), 1);
+        assert_eq!(fee_result.removed_bytes_by_epoch[0].0, 0);
+        assert!(fee_result.removed_bytes_by_epoch[0].1 > 0);
+        assert_eq!(fee_result.refunds_by_epoch.len(), 1);
+
+        let result = drive.fetch_identity(&identity.id.buffer, Some(&transaction));
+
+        assert!(matches!(
+            result,
+            Err(Error::Identity(IdentityError::IdentityNotFound(_)))
+        ));
+
+        let identity_id = drive
+            .fetch_identity_id_by_public_key_hash(
+                public_key_hash(&identity.public_keys[0]),
+                Some(&transaction),
+            )
+            .expect("should fetch identity id by key hash");
+
+        assert_eq!(identity_id, None);
+
+        // without a tombstone the id can be reused
+        drive
+            .insert_identity(
+                identity.clone(),
+                true,
+                false,
+                StorageFlags::default(),
+                Some(&transaction),
+            )
+            .expect("should insert identity again");
+    }
+
+    #[test]
+    fn test_delete_identity_with_tombstone() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let transaction = drive.grove.start_transaction();
+
+        let identity = create_test_identity(&drive, [12; 32], Some(&transaction));
+
+        let removal_fee_result = drive
+            .delete_identity(identity.id.buffer, false, false, Some(&transaction))
+            .expect("should estimate the deletion");
+
+        let fee_result = drive
+            .delete_identity(identity.id.buffer, true, true, Some(&transaction))
+            .expect("should delete identity");
+
+        // the tombstone takes back part of the identity tree element bytes
+        assert!(
+            fee_result.removed_bytes_by_epoch[0].1 < removal_fee_result.removed_bytes_by_epoch[0].1
+        );
+
+        assert!(drive
+            .is_identity_id_tombstoned(&identity.id.buffer, Some(&transaction))
+            .expect("should check tombstone"));
+
+        let result = drive.insert_identity(
+            identity.clone(),
+            true,
+            false,
+            StorageFlags::default(),
+            Some(&transaction),
+        );
+
+        assert!(matches!(
+            result,
+            Err(Error::Identity(IdentityError::IdentityIdTombstoned(_)))
+        ));
+
+        // the tombstone is also checked against the batch
+        let other_identity = create_test_identity(&drive, [13; 32], Some(&transaction));
+
+        let mut batch = GroveDbOpBatch::new();
+
+        drive
+            .delete_identity_operations(
+                &other_identity.id.buffer,
+                true,
+                Some(&transaction),
+                &mut batch,
+            )
+            .expect("should delete identity");
+
+        let result = drive.add_insert_identity_operations(
+            other_identity,
+            true,
+            StorageFlags::default(),
+            Some(&transaction),
+            &mut batch,
+        );
+
+        assert!(matches!(
+            result,
+            Err(Error::Identity(IdentityError::IdentityIdTombstoned(_)))
+        ));
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::BTreeMap;
+
+use dpp::identity::{Identity, IdentityPublicKey};
+use grovedb::batch::Op;
+use grovedb::query_result_type::QueryResultType::QueryKeyElementPairResultType;
+use grovedb::{Element, ElementFlags, PathQuery, Query, QueryItem, SizedQuery, TransactionArg};
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+use crate::drive::batch::GroveDbOpBatch;
+use crate::drive::{Drive, RootTree};
+use crate::error::drive::DriveError;
+use crate::error::identity::IdentityError;
+use crate::error::Error;
+use crate::fee::op::DriveOperation;
+
+pub const PUBLIC_KEY_HASH_SIZE: usize = 20;
+
+pub(crate) fn public_key_hashes_vec_path() -> Vec<Vec<u8>> {
+    vec![vec![RootTree::PublicKeyHashesToIdentities as u8]]
+}
+
+/// The hash160 (ripemd160 of sha256) of the public key data
+pub fn public_key_hash(public_key: &IdentityPublicKey) -> [u8; PUBLIC_KEY_HASH_SIZE] {
+    let sha256 = Sha256::digest(public_key.data.as_slice());
+    Ripemd160::digest(sha256).into()
+}
+
+fn identity_id_from_element(element: Element) -> Result<[u8; 32], Error> {
+    if let Element::Item(identity_id, _) = element {
+        identity_id.as_slice().try_into().map_err(|_| {
+            Error::Drive(DriveError::CorruptedIdentityPublicKeyHash(
+                "identity id referenced by a public key hash must be 32 bytes",
+            ))
+        })
+    } else {
+        Err(Error::Drive(DriveError::CorruptedIdentityPublicKeyHash(
+            "public key hash must point to an item",
+        )))
+    }
+}
+
+impl Drive {
+    /// Public key hashes are unique, a hash already used by another identity
+    /// can't be inserted. Estimates don't read the state, the worst case is
+    /// that the hash is free.
+    pub(crate) fn add_insert_identity_key_hash_operations(
+        &self,
+        identity_id: &[u8],
+        public_key: &IdentityPublicKey,
+        element_flags: ElementFlags,
+        apply: bool,
+        transaction: TransactionArg,
+        batch: &mut GroveDbOpBatch,
+    ) -> Result<(), Error> {
+        let key_hash = public_key_hash(public_key);
+
+        if apply {
+            if let Some(owner_id) =
+                self.fetch_identity_id_by_public_key_hash_for_batch(key_hash, transaction, batch)?
+            {
+                if owner_id.as_slice() != identity_id {
+                    return Err(Error::Identity(IdentityError::IdentityKeyHashAlreadyUsed(
+                        "public key hash is already used by another identity",
+                    )));
+                }
+            }
+        }
+
+        batch.add_insert(
+            public_key_hashes_vec_path(),
+            key_hash.to_vec(),
+            Element::Item(identity_id.to_vec(), element_flags),
+        );
+
+        Ok(())
+    }
+
+    /// The public key hash is removed only if it still points to the identity
+    pub(crate) fn add_delete_identity_key_hash_operations(
+        &self,
+        identity_id: &[u8],
+        public_key: &IdentityPublicKey,
+        transaction: TransactionArg,
+        batch: &mut GroveDbOpBatch,
+    ) -> Result<(), Error> {
+        let key_hash = public_key_hash(public_key);
+
+        let owner_id =
+            self.fetch_identity_id_by_public_key_hash_for_batch(key_hash, transaction, batch)?;
+
+        if owner_id.as_ref().map(|id| id.as_slice()) != Some(identity_id) {
+            return Err(Error::Drive(DriveError::CorruptedIdentityPublicKeyHash(
+                "public key hash to delete doesn't point to the identity",
+            )));
+        }
+
+        batch.add_delete(public_key_hashes_vec_path(), key_hash.to_vec());
+
+        Ok(())
+    }
+
+    fn public_key_hashes_path_query(public_key_hashes: &[[u8; PUBLIC_KEY_HASH_SIZE]]) -> PathQuery {
+        let mut query = Query::new();
+        for public_key_hash in public_key_hashes {
+            query.insert_item(QueryItem::Key(public_key_hash.to_vec()));
+        }
+        PathQuery::new(
+            public_key_hashes_vec_path(),
+            SizedQuery::new(query, None, None),
+        )
+    }
+
+    pub fn fetch_identity_id_by_public_key_hash(
+        &self,
+        public_key_hash: [u8; PUBLIC_KEY_HASH_SIZE],
+        transaction: TransactionArg,
+    ) -> Result<Option<[u8; 32]>, Error> {
+        let mut identity_ids =
+            self.fetch_identity_ids_by_public_key_hashes(&[public_key_hash], transaction)?;
+        Ok(identity_ids.remove(&public_key_hash))
+    }
+
+    /// Fetches the identity id the public key hash points to taking into account
+    /// operations that are already in the batch
+    fn fetch_identity_id_by_public_key_hash_for_batch(
+        &self,
+        public_key_hash: [u8; PUBLIC_KEY_HASH_SIZE],
+        transaction: TransactionArg,
+        batch: &GroveDbOpBatch,
+    ) -> Result<Option<[u8; 32]>, Error> {
+        let pending_operation = batch
+            .operations
+            .iter()
+            .rev()
+            .find(|op| op.path == public_key_hashes_vec_path() && op.key == public_key_hash);
+
+        match pending_operation.map(|op| &op.op) {
+            Some(Op::Insert { element }) => identity_id_from_element(element.clone()).map(Some),
+            Some(Op::Delete) => Ok(None),
+            _ => self.fetch_identity_id_by_public_key_hash(public_key_hash, transaction),
+        }
+    }
+
+    pub fn fetch_identity_by_public_key_hash(
+        &self,
+        public_key_hash: [u8; PUBLIC_KEY_HASH_SIZE],
+        transaction: TransactionArg,
+    ) -> Result<Option<Identity>, Error> {
+        match self.fetch_identity_id_by_public_key_hash(public_key_hash, transaction)? {
+            None => Ok(None),
+            Some(identity_id) => {
+                let (identity, _) = self.fetch_identity(&identity_id, transaction)?;
+                Ok(Some(identity))
+            }
+        }
+    }
+
+    pub fn fetch_identity_ids_by_public_key_hashes(
+        &self,
+        public_key_hashes: &[[u8; PUBLIC_KEY_HASH_SIZE]],
+        transaction: TransactionArg,
+    ) -> Result<BTreeMap<[u8; PUBLIC_KEY_HASH_SIZE], [u8; 32]>, Error> {
+        let path_query = Self::public_key_hashes_path_query(public_key_hashes);
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        let (result_items, _) = self.grove_get_raw_path_query(
+            &path_query,
+            transaction,
+            QueryKeyElementPairResultType,
+            &mut drive_operations,
+        )?;
+
+        result_items
+            .to_key_elements()
+            .into_iter()
+            .map(|(public_key_hash, element)| {
+                let public_key_hash: [u8; PUBLIC_KEY_HASH_SIZE] =
+                    public_key_hash.as_slice().try_into().map_err(|_| {
+                        Error::Drive(DriveError::CorruptedIdentityPublicKeyHash(
+                            "public key hash must be 20 bytes",
+                        ))
+                    })?;
+                Ok((public_key_hash, identity_id_from_element(element)?))
+            })
+            .collect()
+    }
+
+    pub fn prove_identity_id_by_public_key_hash(
+        &self,
+        public_key_hash: [u8; PUBLIC_KEY_HASH_SIZE],
+        transaction: TransactionArg,
+    ) -> Result<Vec<u8>, Error> {
+        self.prove_identity_ids_by_public_key_hashes(&[public_key_hash], transaction)
+    }
+
+    pub fn prove_identity_ids_by_public_key_hashes(
+        &self,
+        public_key_hashes: &[[u8; PUBLIC_KEY_HASH_SIZE]],
+        transaction: TransactionArg,
+    ) -> Result<Vec<u8>, Error> {
+        let path_query = Self::public_key_hashes_path_query(public_key_hashes);
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.grove_get_proved_path_query(&path_query, transaction, &mut drive_operations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::helpers::identities::create_test_identity;
+    use crate::common::helpers::setup::setup_drive_with_initial_state_structure;
+    use crate::drive::batch::GroveDbOpBatch;
+    use crate::drive::flags::StorageFlags;
+    use crate::drive::identity::key_hashes::public_key_hash;
+    use crate::drive::Drive;
+    use crate::error::identity::IdentityError;
+    use crate::error::Error;
+    use dpp::identifier::Identifier;
+    use dpp::identity::Identity;
+    use grovedb::GroveDb;
+
+    #[test]
+    fn test_fetch_identity_by_public_key_hash() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let transaction = drive.grove.start_transaction();
+
+        let identity_bytes = hex::decode("01000000a462696458203012c19b98ec0033addb36cd64b7f510670f2a351a4304b5f6994144286efdac6762616c616e636500687265766973696f6e006a7075626c69634b65797381a6626964006464617461582102abb64674c5df796559eb3cf92a84525cc1a6068e7ad9d4ff48a1f0b179ae29e164747970650067707572706f73650068726561644f6e6c79f46d73656375726974794c6576656c00").expect("expected to decode identity hex");
+
+        let identity = Identity::from_buffer(identity_bytes.as_slice())
+            .expect("expected to deserialize an identity");
+
+        drive
+            .insert_identity(
+                identity.clone(),
+                true,
+                false,
+                StorageFlags::default(),
+                Some(&transaction),
+            )
+            .expect("expected to insert identity");
+
+        let key_hash = public_key_hash(&identity.public_keys[0]);
+
+        let fetched_identity = drive
+            .fetch_identity_by_public_key_hash(key_hash, Some(&transaction))
+            .expect("should fetch an identity")
+            .expect("should have an identity for the key hash");
+
+        assert_eq!(fetched_identity.id.buffer, identity.id.buffer);
+
+        let unknown_key_hash = [5u8; 20];
+
+        let identity_ids = drive
+            .fetch_identity_ids_by_public_key_hashes(
+                &[key_hash, unknown_key_hash],
+                Some(&transaction),
+            )
+            .expect("should fetch identity ids");
+
+        assert_eq!(identity_ids.len(), 1);
+        assert_eq!(identity_ids.get(&key_hash), Some(&identity.id.buffer));
+
+        let proof = drive
+            .prove_identity_ids_by_public_key_hashes(
+                &[key_hash, unknown_key_hash],
+                Some(&transaction),
+            )
+            .expect("should prove identity ids");
+
+        let (_, proved_key_values) = GroveDb::verify_query(
+            proof.as_slice(),
+            &Drive::public_key_hashes_path_query(&[key_hash, unknown_key_hash]),
+        )
+        .expect("should verify proof");
+
+        assert_eq!(proved_key_values.len(), 1);
+    }
+
+    #[test]
+    fn test_insert_key_hash_used_by_another_identity() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let transaction = drive.grove.start_transaction();
+
+        let identity = create_test_identity(&drive, [20; 32], Some(&transaction));
+
+        let mut other_identity = identity.clone();
+        other_identity.id = Identifier::new([21; 32]);
+
+        let result = drive.insert_identity(
+            other_identity,
+            true,
+            false,
+            StorageFlags::default(),
+            Some(&transaction),
+        );
+
+        assert!(matches!(
+            result,
+            Err(Error::Identity(IdentityError::IdentityKeyHashAlreadyUsed(
+                _
+            )))
+        ));
+
+        // the hash can't be used twice within the same batch either
+        let mut first_identity = identity;
+        first_identity.id = Identifier::new([22; 32]);
+        first_identity.public_keys[0].data = vec![22; 33];
+
+        let mut second_identity = first_identity.clone();
+        second_identity.id = Identifier::new([23; 32]);
+
+        let mut batch = GroveDbOpBatch::new();
+
+        drive
+            .add_insert_identity_operations(
+                first_identity,
+                true,
+                StorageFlags::default(),
+                Some(&transaction),
+                &mut batch,
+            )
+            .expect("expected to add identity operations");
+
+        let result = drive.add_insert_identity_operations(
+            second_identity,
+            true,
+            StorageFlags::default(),
+            Some(&transaction),
+            &mut batch,
+        );
+
+        assert!(matches!(
+            result,
+            Err(Error::Identity(IdentityError::IdentityKeyHashAlreadyUsed(
+                _
+            )))
+        ));
+    }
+}
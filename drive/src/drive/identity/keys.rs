@@ -0,0 +1,488 @@
+use std::collections::BTreeSet;
+
+use dpp::identity::{IdentityPublicKey, KeyID, Purpose, SecurityLevel};
+use grovedb::query_result_type::QueryResultType::QueryElementResultType;
+use grovedb::{Element, ElementFlags, PathQuery, Query, SizedQuery, TransactionArg};
+use serde::{Deserialize, Serialize};
+
+use crate::drive::batch::GroveDbOpBatch;
+use crate::drive::flags::StorageFlags;
+use crate::drive::identity::{identity_vec_path, IDENTITY_KEYS_KEY};
+use crate::drive::Drive;
+use crate::error::drive::DriveError;
+use crate::error::identity::IdentityError;
+use crate::error::Error;
+use crate::fee::op::DriveOperation;
+use crate::fee::result::FeeResult;
+
+pub(crate) fn identity_keys_vec_path(identity_id: &[u8]) -> Vec<Vec<u8>> {
+    let mut path = identity_vec_path(identity_id);
+    path.push(IDENTITY_KEYS_KEY.to_vec());
+    path
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentityKey {
+    pub public_key: IdentityPublicKey,
+    pub disabled_at: Option<u64>,
+}
+
+impl IdentityKey {
+    pub fn is_disabled(&self) -> bool {
+        self.disabled_at.is_some()
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, Error> {
+        let mut buffer: Vec<u8> = Vec::new();
+        ciborium::ser::into_writer(&self, &mut buffer).map_err(|_| {
+            Error::Identity(IdentityError::IdentitySerialization(
+                "failed to serialize identity key to CBOR",
+            ))
+        })?;
+        Ok(buffer)
+    }
+
+    pub(crate) fn from_element(element: Element) -> Result<(Self, ElementFlags), Error> {
+        if let Element::Item(identity_key_cbor, element_flags) = element {
+            let identity_key =
+                ciborium::de::from_reader(identity_key_cbor.as_slice()).map_err(|_| {
+                    Error::Identity(IdentityError::IdentitySerialization(
+                        "failed to de-serialize identity key from CBOR",
+                    ))
+                })?;
+
+            Ok((identity_key, element_flags))
+        } else {
+            Err(Error::Drive(DriveError::CorruptedIdentityKeyNotItem(
+                "identity key must be an item",
+            )))
+        }
+    }
+}
+
+pub enum IdentityKeysRequest {
+    AllKeys,
+    SpecificKeys(Vec<KeyID>),
+    KeysWithPurpose(Purpose),
+    KeysWithSecurityLevel(SecurityLevel),
+}
+
+impl IdentityKeysRequest {
+    fn path_query(&self, identity_id: &[u8]) -> PathQuery {
+        let mut query = Query::new();
+        match self {
+            IdentityKeysRequest::SpecificKeys(key_ids) => {
+                for key_id in key_ids {
+                    query.insert_key(key_id.to_be_bytes().to_vec());
+                }
+            }
+            // Identities have only a few keys so purpose and security level
+            // are filtered after fetching
+            _ => query.insert_all(),
+        }
+        PathQuery::new(
+            identity_keys_vec_path(identity_id),
+            SizedQuery::new(query, None, None),
+        )
+    }
+
+    fn matches(&self, identity_key: &IdentityKey) -> bool {
+        match self {
+            IdentityKeysRequest::AllKeys | IdentityKeysRequest::SpecificKeys(_) => true,
+            IdentityKeysRequest::KeysWithPurpose(purpose) => {
+                identity_key.public_key.purpose == *purpose
+            }
+            IdentityKeysRequest::KeysWithSecurityLevel(security_level) => {
+                identity_key.public_key.security_level == *security_level
+            }
+        }
+    }
+}
+
+impl Drive {
+    /// Inserts the key into the identity keys tree, enabled keys are also
+    /// indexed by their hash
+    pub(crate) fn add_insert_identity_key_operations(
+        &self,
+        identity_id: &[u8],
+        identity_key: &IdentityKey,
+        element_flags: ElementFlags,
+        apply: bool,
+        transaction: TransactionArg,
+        batch: &mut GroveDbOpBatch,
+    ) -> Result<(), Error> {
+        if !identity_key.is_disabled() {
+            self.add_insert_identity_key_hash_operations(
+                identity_id,
+                &identity_key.public_key,
+                element_flags.clone(),
+                apply,
+                transaction,
+                batch,
+            )?;
+        }
+
+        batch.add_insert(
+            identity_keys_vec_path(identity_id),
+            identity_key.public_key.id.to_be_bytes().to_vec(),
+            Element::Item(identity_key.serialize()?, element_flags),
+        );
+
+        Ok(())
+    }
+
+    fn fetch_identity_keys_with_flags(
+        &self,
+        identity_id: &[u8],
+        request: &IdentityKeysRequest,
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<Vec<(IdentityKey, ElementFlags)>, Error> {
+        let path_query = request.path_query(identity_id);
+
+        let (result_items, _) = self.grove_get_raw_path_query(
+            &path_query,
+            transaction,
+            QueryElementResultType,
+            drive_operations,
+        )?;
+
+        let identity_keys = result_items
+            .to_elements()
+            .into_iter()
+            .map(IdentityKey::from_element)
+            .collect::<Result<Vec<(IdentityKey, ElementFlags)>, Error>>()?;
+
+        Ok(identity_keys
+            .into_iter()
+            .filter(|(identity_key, _)| request.matches(identity_key))
+            .collect())
+    }
+
+    pub fn fetch_identity_keys(
+        &self,
+        identity_id: [u8; 32],
+        request: IdentityKeysRequest,
+        transaction: TransactionArg,
+    ) -> Result<Vec<IdentityKey>, Error> {
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        Ok(self
+            .fetch_identity_keys_with_flags(
+                &identity_id,
+                &request,
+                transaction,
+                &mut drive_operations,
+            )?
+            .into_iter()
+            .map(|(identity_key, _)| identity_key)
+            .collect())
+    }
+
+    pub fn prove_identity_keys(
+        &self,
+        identity_id: [u8; 32],
+        request: IdentityKeysRequest,
+        transaction: TransactionArg,
+    ) -> Result<Vec<u8>, Error> {
+        let path_query = request.path_query(&identity_id);
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.grove_get_proved_path_query(&path_query, transaction, &mut drive_operations)
+    }
+
+    pub fn add_new_keys_to_identity_operations(
+        &self,
+        identity_id: &[u8],
+        keys_to_add: Vec<IdentityPublicKey>,
+        storage_flags: &StorageFlags,
+        transaction: TransactionArg,
+        batch: &mut GroveDbOpBatch,
+    ) -> Result<(), Error> {
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        let mut key_ids: BTreeSet<KeyID> = self
+            .fetch_identity_keys_with_flags(
+                identity_id,
+                &IdentityKeysRequest::AllKeys,
+                transaction,
+                &mut drive_operations,
+            )?
+            .into_iter()
+            .map(|(identity_key, _)| identity_key.public_key.id)
+            .collect();
+
+        for public_key in keys_to_add {
+            if !key_ids.insert(public_key.id) {
+                return Err(Error::Identity(IdentityError::IdentityKeyAlreadyExists(
+                    "identity already has a key with this id",
+                )));
+            }
+
+            self.add_insert_identity_key_operations(
+                identity_id,
+                &IdentityKey {
+                    public_key,
+                    disabled_at: None,
+                },
+                storage_flags.to_element_flags(),
+                true,
+                transaction,
+                batch,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn add_new_keys_to_identity(
+        &self,
+        identity_id: [u8; 32],
+        keys_to_add: Vec<IdentityPublicKey>,
+        apply: bool,
+        storage_flags: StorageFlags,
+        transaction: TransactionArg,
+    ) -> Result<FeeResult, Error> {
+        let mut batch = GroveDbOpBatch::new();
+
+        self.add_new_keys_to_identity_operations(
+            &identity_id,
+            keys_to_add,
+            &storage_flags,
+            transaction,
+            &mut batch,
+        )?;
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.apply_batch_grovedb_operations(apply, transaction, batch, &mut drive_operations)?;
+
+        self.calculate_fee(None, Some(drive_operations), false, transaction)
+    }
+
+    /// Marks keys as disabled, disabled keys are removed from the public key hash index
+    pub fn disable_identity_keys_operations(
+        &self,
+        identity_id: &[u8],
+        key_ids: Vec<KeyID>,
+        disabled_at: u64,
+        transaction: TransactionArg,
+        batch: &mut GroveDbOpBatch,
+    ) -> Result<(), Error> {
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        let key_ids_count = key_ids.len();
+
+        let identity_keys = self.fetch_identity_keys_with_flags(
+            identity_id,
+            &IdentityKeysRequest::SpecificKeys(key_ids),
+            transaction,
+            &mut drive_operations,
+        )?;
+
+        if identity_keys.len() != key_ids_count {
+            return Err(Error::Identity(IdentityError::IdentityKeyNotFound(
+                "identity key to disable not found",
+            )));
+        }
+
+        for (mut identity_key, element_flags) in identity_keys {
+            if identity_key.is_disabled() {
+                return Err(Error::Identity(IdentityError::IdentityKeyAlreadyDisabled(
+                    "identity key is already disabled",
+                )));
+            }
+
+            self.add_delete_identity_key_hash_operations(
+                identity_id,
+                &identity_key.public_key,
+                transaction,
+                batch,
+            )?;
+
+            identity_key.disabled_at = Some(disabled_at);
+
+            self.add_insert_identity_key_operations(
+                identity_id,
+                &identity_key,
+                element_flags,
+                true,
+                transaction,
+                batch,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn disable_identity_keys(
+        &self,
+        identity_id: [u8; 32],
+        key_ids: Vec<KeyID>,
+        disabled_at: u64,
+        apply: bool,
+        transaction: TransactionArg,
+    ) -> Result<FeeResult, Error> {
+        let mut batch = GroveDbOpBatch::new();
+
+        self.disable_identity_keys_operations(
+            &identity_id,
+            key_ids,
+            disabled_at,
+            transaction,
+            &mut batch,
+        )?;
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.apply_batch_grovedb_operations(apply, transaction, batch, &mut drive_operations)?;
+
+        self.calculate_fee(None, Some(drive_operations), false, transaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::helpers::identities::create_test_identity;
+    use crate::common::helpers::setup::setup_drive_with_initial_state_structure;
+    use crate::drive::batch::GroveDbOpBatch;
+    use crate::drive::flags::StorageFlags;
+    use crate::drive::identity::key_hashes::public_key_hash;
+    use crate::drive::identity::keys::IdentityKeysRequest;
+    use crate::error::drive::DriveError;
+    use crate::error::identity::IdentityError;
+    use crate::error::Error;
+    use dpp::identity::{IdentityPublicKey, KeyType, Purpose, SecurityLevel};
+
+    #[test]
+    fn test_add_and_disable_identity_keys() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let transaction = drive.grove.start_transaction();
+
+        let identity = create_test_identity(&drive, [9; 32], Some(&transaction));
+
+        let new_key = IdentityPublicKey {
+            id: 2,
+            key_type: KeyType::ECDSA_SECP256K1,
+            data: vec![4, 5, 6, 7],
+            purpose: Purpose::ENCRYPTION,
+            security_level: SecurityLevel::MEDIUM,
+            read_only: false,
+        };
+
+        drive
+            .add_new_keys_to_identity(
+                identity.id.buffer,
+                vec![new_key.clone()],
+                true,
+                StorageFlags::default(),
+                Some(&transaction),
+            )
+            .expect("should add a new key");
+
+        let result = drive.add_new_keys_to_identity(
+            identity.id.buffer,
+            vec![new_key.clone()],
+            true,
+            StorageFlags::default(),
+            Some(&transaction),
+        );
+
+        assert!(matches!(
+            result,
+            Err(Error::Identity(IdentityError::IdentityKeyAlreadyExists(_)))
+        ));
+
+        let keys = drive
+            .fetch_identity_keys(
+                identity.id.buffer,
+                IdentityKeysRequest::AllKeys,
+                Some(&transaction),
+            )
+            .expect("should fetch keys");
+
+        assert_eq!(keys.len(), 2);
+
+        let keys = drive
+            .fetch_identity_keys(
+                identity.id.buffer,
+                IdentityKeysRequest::KeysWithPurpose(Purpose::ENCRYPTION),
+                Some(&transaction),
+            )
+            .expect("should fetch keys");
+
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].public_key, new_key);
+
+        let key_identity_id = drive
+            .fetch_identity_id_by_public_key_hash(public_key_hash(&new_key), Some(&transaction))
+            .expect("should fetch identity id by key hash");
+
+        assert_eq!(key_identity_id, Some(identity.id.buffer));
+
+        drive
+            .disable_identity_keys(identity.id.buffer, vec![2], 100, true, Some(&transaction))
+            .expect("should disable key");
+
+        let keys = drive
+            .fetch_identity_keys(
+                identity.id.buffer,
+                IdentityKeysRequest::SpecificKeys(vec![2]),
+                Some(&transaction),
+            )
+            .expect("should fetch keys");
+
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].disabled_at, Some(100));
+
+        let key_identity_id = drive
+            .fetch_identity_id_by_public_key_hash(public_key_hash(&new_key), Some(&transaction))
+            .expect("should fetch identity id by key hash");
+
+        assert_eq!(key_identity_id, None);
+
+        let (fetched_identity, _) = drive
+            .fetch_identity(&identity.id.buffer, Some(&transaction))
+            .expect("should fetch identity");
+
+        assert_eq!(fetched_identity.public_keys.len(), 2);
+    }
+
+    #[test]
+    fn test_disable_identity_key_twice_in_batch() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let transaction = drive.grove.start_transaction();
+
+        let identity = create_test_identity(&drive, [11; 32], Some(&transaction));
+
+        let mut batch = GroveDbOpBatch::new();
+
+        drive
+            .disable_identity_keys_operations(
+                &identity.id.buffer,
+                vec![1],
+                100,
+                Some(&transaction),
+                &mut batch,
+            )
+            .expect("should disable key");
+
+        // the key hash was already removed by the batch
+        let result = drive.disable_identity_keys_operations(
+            &identity.id.buffer,
+            vec![1],
+            100,
+            Some(&transaction),
+            &mut batch,
+        );
+
+        assert!(matches!(
+            result,
+            Err(Error::Drive(DriveError::CorruptedIdentityPublicKeyHash(_)))
+        ));
+    }
+}
@@ -1,46 +1,294 @@
-use dpp::identity::Identity;
-use grovedb::query_result_type::QueryResultType::QueryElementResultType;
-use grovedb::{Element, PathQuery, Query, QueryItem, SizedQuery, TransactionArg};
+use std::collections::BTreeMap;
+
+use dpp::identity::{Identity, IdentityPublicKey};
+use grovedb::query_result_type::QueryResultType::QueryPathKeyElementTrioResultType;
+use grovedb::{Element, GroveDb, PathQuery, Query, QueryItem, SizedQuery, TransactionArg};
 
 use crate::drive::batch::GroveDbOpBatch;
 use crate::drive::flags::StorageFlags;
+use crate::drive::identity::keys::{identity_keys_vec_path, IdentityKey};
 use crate::drive::{Drive, RootTree};
 use crate::error::drive::DriveError;
 use crate::error::identity::IdentityError;
+use crate::error::query::QueryError;
 use crate::error::Error;
-use crate::fee::calculate_fee;
 use crate::fee::op::DriveOperation;
+use crate::fee::result::FeeResult;
+
+pub mod balance;
+pub mod delete;
+pub mod key_hashes;
+pub mod keys;
+pub mod revision;
 
 const IDENTITY_KEY: [u8; 1] = [0];
+pub(crate) const IDENTITY_BALANCE_KEY: [u8; 1] = [1];
+pub(crate) const IDENTITY_KEYS_KEY: [u8; 1] = [2];
+pub(crate) const IDENTITY_REVISION_KEY: [u8; 1] = [3];
+
+pub(crate) fn identities_path() -> [&'static [u8]; 1] {
+    [Into::<&[u8; 1]>::into(RootTree::Identities)]
+}
+
+pub(crate) fn identities_vec_path() -> Vec<Vec<u8>> {
+    vec![vec![RootTree::Identities as u8]]
+}
+
+pub(crate) fn identity_path(identity_id: &[u8]) -> [&[u8]; 2] {
+    [Into::<&[u8; 1]>::into(RootTree::Identities), identity_id]
+}
+
+pub(crate) fn identity_vec_path(identity_id: &[u8]) -> Vec<Vec<u8>> {
+    vec![vec![RootTree::Identities as u8], identity_id.to_vec()]
+}
+
+fn serialize_identity(identity: &Identity) -> Result<Vec<u8>, Error> {
+    identity.to_buffer().map_err(|_| {
+        Error::Identity(IdentityError::IdentitySerialization(
+            "failed to serialize identity to CBOR",
+        ))
+    })
+}
+
+fn identity_from_element(element: Element) -> Result<(Identity, StorageFlags), Error> {
+    if let Element::Item(identity_cbor, element_flags) = element {
+        let identity = Identity::from_buffer(identity_cbor.as_slice()).map_err(|_| {
+            Error::Identity(IdentityError::IdentitySerialization(
+                "failed to de-serialize identity from CBOR",
+            ))
+        })?;
+
+        Ok((identity, StorageFlags::from_element_flags(element_flags)?))
+    } else {
+        Err(Error::Drive(DriveError::CorruptedIdentityNotItem(
+            "identity must be an item",
+        )))
+    }
+}
+
+fn identities_path_query(identity_ids: Vec<Vec<u8>>) -> PathQuery {
+    let mut identity_ids_query = Query::new();
+    for identity_id in identity_ids {
+        identity_ids_query.insert_key(identity_id);
+    }
+
+    let mut identity_query = Query::new();
+    identity_query.insert_key(IDENTITY_KEY.to_vec());
+    identity_query.insert_key(IDENTITY_BALANCE_KEY.to_vec());
+    identity_query.insert_key(IDENTITY_KEYS_KEY.to_vec());
+    identity_query.insert_key(IDENTITY_REVISION_KEY.to_vec());
+
+    let mut keys_query = Query::new();
+    keys_query.insert_all();
+
+    identity_query.add_conditional_subquery(
+        QueryItem::Key(IDENTITY_KEYS_KEY.to_vec()),
+        None,
+        Some(keys_query),
+    );
+
+    identity_ids_query.set_subquery(identity_query);
+
+    PathQuery::new(
+        identities_vec_path(),
+        SizedQuery::new(identity_ids_query, None, None),
+    )
+}
+
+type PathKeyElement = (Vec<Vec<u8>>, Vec<u8>, Element);
+
+#[derive(Default)]
+struct IdentityElements {
+    identity: Option<(Identity, StorageFlags)>,
+    balance: Option<u64>,
+    revision: Option<u64>,
+    public_keys: Vec<IdentityPublicKey>,
+}
+
+/// Identity items are combined with their balance, keys and revision, which are
+/// stored separately and are the source of truth. Elements are grouped by the
+/// identity subtree their path belongs to.
+fn identities_from_path_key_elements(
+    path_key_elements: Vec<PathKeyElement>,
+) -> Result<Vec<(Identity, StorageFlags)>, Error> {
+    let mut identities_elements: BTreeMap<Vec<u8>, IdentityElements> = BTreeMap::new();
+
+    for (path, key, element) in path_key_elements {
+        // identity subtrees and tombstones of deleted identities
+        if path == identities_vec_path() {
+            continue;
+        }
+
+        let identity_id = path
+            .get(1)
+            .ok_or(Error::Drive(DriveError::CorruptedIdentityElement(
+                "identity element has an unexpected path",
+            )))?
+            .clone();
+
+        let identity_elements = identities_elements.entry(identity_id.clone()).or_default();
+
+        if path == identity_keys_vec_path(&identity_id) {
+            let (identity_key, _) = IdentityKey::from_element(element)?;
+            identity_elements.public_keys.push(identity_key.public_key);
+        } else if path != identity_vec_path(&identity_id) {
+            return Err(Error::Drive(DriveError::CorruptedIdentityElement(
+                "identity element has an unexpected path",
+            )));
+        } else if key == IDENTITY_KEY {
+            identity_elements.identity = Some(identity_from_element(element)?);
+        } else if key == IDENTITY_BALANCE_KEY {
+            identity_elements.balance = Some(balance::balance_from_element(&element)?);
+        } else if key == IDENTITY_REVISION_KEY {
+            identity_elements.revision = Some(revision::revision_from_element(&element)?);
+        } else if key != IDENTITY_KEYS_KEY {
+            return Err(Error::Drive(DriveError::CorruptedIdentityElement(
+                "identity element has an unexpected key",
+            )));
+        }
+    }
+
+    identities_elements
+        .into_iter()
+        .map(|(identity_id, identity_elements)| {
+            let (mut identity, storage_flags) = identity_elements.identity.ok_or(Error::Drive(
+                DriveError::CorruptedIdentityNotItem("identity element without identity item"),
+            ))?;
+
+            if identity.id.buffer.as_slice() != identity_id.as_slice() {
+                return Err(Error::Drive(DriveError::CorruptedIdentityElement(
+                    "identity item doesn't belong to its subtree",
+                )));
+            }
+
+            identity.balance =
+                identity_balance_to_i64(identity_elements.balance.ok_or(Error::Drive(
+                    DriveError::CorruptedIdentityBalance("identity balance not found"),
+                ))?)?;
+            identity.revision = revision::identity_revision_to_i64(
+                identity_elements.revision.ok_or(Error::Drive(
+                    DriveError::CorruptedIdentityRevision("identity revision not found"),
+                ))?,
+            )?;
+            identity.public_keys = identity_elements.public_keys;
+
+            Ok((identity, storage_flags))
+        })
+        .collect()
+}
+
+/// Proofs hold only keys and values. Each identity is proved by its own path query,
+/// so its elements can only be its subtree, the one byte keys of the subtree or the
+/// public keys in its keys tree.
+fn proved_identity_path_key_elements(
+    identity_id: &[u8],
+    proved_key_values: Vec<(Vec<u8>, Vec<u8>)>,
+) -> Result<Vec<PathKeyElement>, Error> {
+    proved_key_values
+        .into_iter()
+        .map(|(key, value)| {
+            let element = Element::deserialize(&value).map_err(Error::GroveDB)?;
+
+            let path = if key == identity_id {
+                identities_vec_path()
+            } else if key.len() == IDENTITY_KEY.len() {
+                identity_vec_path(identity_id)
+            } else {
+                identity_keys_vec_path(identity_id)
+            };
+
+            Ok((path, key, element))
+        })
+        .collect()
+}
+
+fn identity_balance_to_i64(balance: u64) -> Result<i64, Error> {
+    // TODO: Identity balance should be u64 in DPP as well
+    balance.try_into().map_err(|_| {
+        Error::Identity(IdentityError::CriticalBalanceOverflow(
+            "identity balance doesn't fit into i64",
+        ))
+    })
+}
 
 impl Drive {
     pub fn add_insert_identity_operations(
         &self,
         identity: Identity,
+        apply: bool,
         storage_flags: StorageFlags,
+        transaction: TransactionArg,
         batch: &mut GroveDbOpBatch,
     ) -> Result<(), Error> {
-        let identity_bytes = identity.to_buffer().map_err(|_| {
-            Error::Identity(IdentityError::IdentitySerialization(
-                "failed to serialize identity to CBOR",
+        // estimates don't read the state, the worst case is that the id is free
+        if apply
+            && self.is_identity_id_tombstoned_for_batch(
+                identity.id.buffer.as_slice(),
+                transaction,
+                batch,
+            )?
+        {
+            return Err(Error::Identity(IdentityError::IdentityIdTombstoned(
+                "identity id belongs to a deleted identity and can't be reused",
+            )));
+        }
+
+        let identity_bytes = serialize_identity(&identity)?;
+
+        let balance: u64 = identity.balance.try_into().map_err(|_| {
+            Error::Identity(IdentityError::InvalidIdentityStructure(
+                "identity balance can't be negative",
             ))
         })?;
 
+        let revision = revision::identity_revision_to_u64(identity.revision)?;
+
         batch.add_insert_empty_tree_with_flags(
-            vec![vec![RootTree::Identities as u8]],
+            identities_vec_path(),
             identity.id.buffer.to_vec(),
             &storage_flags,
         );
 
         batch.add_insert(
-            vec![
-                vec![RootTree::Identities as u8],
-                identity.id.buffer.to_vec(),
-            ],
+            identity_vec_path(identity.id.buffer.as_slice()),
             IDENTITY_KEY.to_vec(),
             Element::Item(identity_bytes, storage_flags.to_element_flags()),
         );
 
+        self.add_insert_identity_balance_operation(
+            identity.id.buffer.as_slice(),
+            balance,
+            storage_flags.to_element_flags(),
+            batch,
+        );
+
+        self.add_insert_identity_revision_operation(
+            identity.id.buffer.as_slice(),
+            revision,
+            storage_flags.to_element_flags(),
+            batch,
+        );
+
+        batch.add_insert_empty_tree_with_flags(
+            identity_vec_path(identity.id.buffer.as_slice()),
+            IDENTITY_KEYS_KEY.to_vec(),
+            &storage_flags,
+        );
+
+        for public_key in identity.public_keys.into_iter() {
+            self.add_insert_identity_key_operations(
+                identity.id.buffer.as_slice(),
+                &IdentityKey {
+                    public_key,
+                    disabled_at: None,
+                },
+                storage_flags.to_element_flags(),
+                apply,
+                transaction,
+                batch,
+            )?;
+        }
+
         Ok(())
     }
 
@@ -48,18 +296,30 @@ impl Drive {
         &self,
         identity: Identity,
         apply: bool,
+        with_fee_breakdown: bool,
         storage_flags: StorageFlags,
         transaction: TransactionArg,
-    ) -> Result<(i64, u64), Error> {
+    ) -> Result<FeeResult, Error> {
         let mut batch = GroveDbOpBatch::new();
 
-        self.add_insert_identity_operations(identity, storage_flags, &mut batch)?;
+        self.add_insert_identity_operations(
+            identity,
+            apply,
+            storage_flags,
+            transaction,
+            &mut batch,
+        )?;
 
         let mut drive_operations: Vec<DriveOperation> = vec![];
 
         self.apply_batch_grovedb_operations(apply, transaction, batch, &mut drive_operations)?;
 
-        calculate_fee(None, Some(drive_operations))
+        self.calculate_fee(
+            None,
+            Some(drive_operations),
+            with_fee_breakdown,
+            transaction,
+        )
     }
 
     pub fn fetch_identity(
@@ -67,29 +327,14 @@ impl Drive {
         id: &[u8],
         transaction: TransactionArg,
     ) -> Result<(Identity, StorageFlags), Error> {
-        let element = self
-            .grove
-            .get(
-                [Into::<&[u8; 1]>::into(RootTree::Identities).as_slice(), id],
-                &IDENTITY_KEY,
-                transaction,
-            )
-            .unwrap()
-            .map_err(Error::GroveDB)?;
-
-        if let Element::Item(identity_cbor, element_flags) = element {
-            let identity = Identity::from_buffer(identity_cbor.as_slice()).map_err(|_| {
-                Error::Identity(IdentityError::IdentitySerialization(
-                    "failed to de-serialize identity from CBOR",
-                ))
-            })?;
-
-            Ok((identity, StorageFlags::from_element_flags(element_flags)?))
-        } else {
-            Err(Error::Drive(DriveError::CorruptedIdentityNotItem(
-                "identity must be an item",
-            )))
-        }
+        self.fetch_identities_with_flags_for_path_query(
+            &identities_path_query(vec![id.to_vec()]),
+            transaction,
+        )?
+        .pop()
+        .ok_or(Error::Identity(IdentityError::IdentityNotFound(
+            "identity not found",
+        )))
     }
 
     pub fn fetch_identities(
@@ -109,53 +354,115 @@ impl Drive {
         ids: &Vec<[u8; 32]>,
         transaction: TransactionArg,
     ) -> Result<Vec<(Identity, StorageFlags)>, Error> {
-        let mut query = Query::new();
-        query.set_subquery_key(IDENTITY_KEY.to_vec());
-        for id in ids {
-            query.insert_item(QueryItem::Key(id.to_vec()));
-        }
-        let path_query = PathQuery {
-            path: vec![vec![RootTree::Identities as u8]],
-            query: SizedQuery {
-                query,
-                limit: None,
-                offset: None,
-            },
-        };
+        self.fetch_identities_with_flags_for_path_query(
+            &identities_path_query(ids.iter().map(|id| id.to_vec()).collect()),
+            transaction,
+        )
+    }
+
+    fn fetch_identities_with_flags_for_path_query(
+        &self,
+        path_query: &PathQuery,
+        transaction: TransactionArg,
+    ) -> Result<Vec<(Identity, StorageFlags)>, Error> {
         let (result_items, _) = self
             .grove
-            .query_raw(&path_query, QueryElementResultType, transaction)
+            .query_raw(path_query, QueryPathKeyElementTrioResultType, transaction)
             .unwrap()
             .map_err(Error::GroveDB)?;
 
-        result_items
-            .to_elements()
-            .into_iter()
-            .map(|element| {
-                if let Element::Item(identity_cbor, element_flags) = element {
-                    let identity =
-                        Identity::from_buffer(identity_cbor.as_slice()).map_err(|_| {
-                            Error::Identity(IdentityError::IdentitySerialization(
-                                "failed to de-serialize identity from CBOR",
-                            ))
-                        })?;
-
-                    Ok((identity, StorageFlags::from_element_flags(element_flags)?))
-                } else {
-                    Err(Error::Drive(DriveError::CorruptedIdentityNotItem(
-                        "identity must be an item",
-                    )))
-                }
-            })
+        identities_from_path_key_elements(result_items.to_path_key_elements())
+    }
+
+    pub fn prove_identity(
+        &self,
+        identity_id: [u8; 32],
+        transaction: TransactionArg,
+    ) -> Result<Vec<u8>, Error> {
+        let path_query = identities_path_query(vec![identity_id.to_vec()]);
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.grove_get_proved_path_query(&path_query, transaction, &mut drive_operations)
+    }
+
+    /// Returns one proof per identity, in the order of the ids
+    pub fn prove_identities(
+        &self,
+        identity_ids: &[[u8; 32]],
+        transaction: TransactionArg,
+    ) -> Result<Vec<Vec<u8>>, Error> {
+        identity_ids
+            .iter()
+            .map(|identity_id| self.prove_identity(*identity_id, transaction))
             .collect()
     }
+
+    pub fn verify_identity(
+        proof: &[u8],
+        identity_id: [u8; 32],
+    ) -> Result<([u8; 32], Option<Identity>), Error> {
+        let path_query = identities_path_query(vec![identity_id.to_vec()]);
+
+        let (root_hash, proved_key_values) =
+            GroveDb::verify_query(proof, &path_query).map_err(Error::GroveDB)?;
+
+        let path_key_elements =
+            proved_identity_path_key_elements(identity_id.as_slice(), proved_key_values)?;
+
+        let identity = identities_from_path_key_elements(path_key_elements)?
+            .pop()
+            .map(|(identity, _)| identity);
+
+        Ok((root_hash, identity))
+    }
+
+    /// Verifies the proofs of prove_identities without the state, they must all be of
+    /// the same state. Identities that don't exist are not part of the result.
+    pub fn verify_identities(
+        proofs: &[Vec<u8>],
+        identity_ids: &[[u8; 32]],
+    ) -> Result<([u8; 32], Vec<Identity>), Error> {
+        if proofs.is_empty() || proofs.len() != identity_ids.len() {
+            return Err(Error::Query(QueryError::InvalidProof(
+                "there must be one proof per identity",
+            )));
+        }
+
+        let mut root_hash: Option<[u8; 32]> = None;
+        let mut identities: Vec<Identity> = vec![];
+
+        for (proof, identity_id) in proofs.iter().zip(identity_ids) {
+            let (proof_root_hash, identity) = Self::verify_identity(proof, *identity_id)?;
+
+            if *root_hash.get_or_insert(proof_root_hash) != proof_root_hash {
+                return Err(Error::Query(QueryError::InvalidProof(
+                    "identity proofs are not of the same state",
+                )));
+            }
+
+            identities.extend(identity);
+        }
+
+        Ok((root_hash.unwrap_or_default(), identities))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::common::helpers::setup::setup_drive;
+    use crate::common::helpers::identities::create_test_identity;
+    use crate::common::helpers::setup::{setup_drive, setup_drive_with_initial_state_structure};
     use crate::drive::flags::StorageFlags;
+    use crate::drive::identity::{
+        identities_from_path_key_elements, identity_vec_path, serialize_identity,
+        IDENTITY_BALANCE_KEY, IDENTITY_KEY, IDENTITY_REVISION_KEY,
+    };
+    use crate::drive::Drive;
+    use crate::error::drive::DriveError;
+    use crate::error::Error;
+    use dpp::identifier::Identifier;
     use dpp::identity::Identity;
+    use grovedb::Element;
 
     #[test]
     fn test_insert_and_fetch_identity() {
@@ -176,6 +483,7 @@ mod tests {
             .insert_identity(
                 identity.clone(),
                 true,
+                false,
                 StorageFlags::default(),
                 Some(&transaction),
             )
@@ -190,4 +498,119 @@ mod tests {
             identity.to_buffer().expect("should serialize")
         );
     }
+
+    #[test]
+    fn test_prove_and_verify_identities() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let transaction = drive.grove.start_transaction();
+
+        let identity = create_test_identity(&drive, [13; 32], Some(&transaction));
+
+        // public key hashes are unique
+        let mut other_identity = identity.clone();
+        other_identity.id = Identifier::new([14; 32]);
+        other_identity.public_keys[0].data = vec![14; 33];
+
+        drive
+            .insert_identity(
+                other_identity.clone(),
+                true,
+                false,
+                StorageFlags::default(),
+                Some(&transaction),
+            )
+            .expect("expected to insert identity");
+
+        drive
+            .add_to_identity_balance(other_identity.id.buffer, 50, true, Some(&transaction))
+            .expect("should add to balance");
+
+        let root_hash = drive
+            .grove
+            .root_hash(Some(&transaction))
+            .unwrap()
+            .expect("should get root hash");
+
+        let proof = drive
+            .prove_identity(identity.id.buffer, Some(&transaction))
+            .expect("should prove identity");
+
+        let (proof_root_hash, proved_identity) =
+            Drive::verify_identity(proof.as_slice(), identity.id.buffer)
+                .expect("should verify identity");
+
+        assert_eq!(proof_root_hash, root_hash);
+        assert_eq!(
+            proved_identity
+                .expect("identity should be proved")
+                .to_buffer()
+                .expect("should serialize"),
+            identity.to_buffer().expect("should serialize")
+        );
+
+        let identity_ids = [identity.id.buffer, other_identity.id.buffer, [15; 32]];
+
+        let proofs = drive
+            .prove_identities(&identity_ids, Some(&transaction))
+            .expect("should prove identities");
+
+        let (proof_root_hash, proved_identities) =
+            Drive::verify_identities(&proofs, &identity_ids).expect("should verify identities");
+
+        assert_eq!(proof_root_hash, root_hash);
+        assert_eq!(proved_identities.len(), 2);
+        assert_eq!(proved_identities[1].balance, 50);
+
+        // each proof is verified against the path query of its own identity, a swapped
+        // proof can't attribute an identity to another id
+        if let Ok((_, swapped_identities)) =
+            Drive::verify_identities(&[proofs[1].clone(), proofs[0].clone()], &identity_ids[..2])
+        {
+            assert!(swapped_identities.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_identity_item_in_another_identity_subtree() {
+        let identity = Identity {
+            id: Identifier::new([16; 32]),
+            revision: 1,
+            balance: 0,
+            protocol_version: 0,
+            public_keys: vec![],
+            asset_lock_proof: None,
+            metadata: None,
+        };
+
+        let element_flags = StorageFlags::default().to_element_flags();
+
+        let path_key_elements = vec![
+            (
+                identity_vec_path(&[17; 32]),
+                IDENTITY_KEY.to_vec(),
+                Element::Item(
+                    serialize_identity(&identity).expect("should serialize"),
+                    element_flags.clone(),
+                ),
+            ),
+            (
+                identity_vec_path(&[17; 32]),
+                IDENTITY_BALANCE_KEY.to_vec(),
+                Element::Item(0u64.to_be_bytes().to_vec(), element_flags.clone()),
+            ),
+            (
+                identity_vec_path(&[17; 32]),
+                IDENTITY_REVISION_KEY.to_vec(),
+                Element::Item(1u64.to_be_bytes().to_vec(), element_flags),
+            ),
+        ];
+
+        let result = identities_from_path_key_elements(path_key_elements);
+
+        assert!(matches!(
+            result,
+            Err(Error::Drive(DriveError::CorruptedIdentityElement(_)))
+        ));
+    }
 }
@@ -0,0 +1,328 @@
+use dpp::identity::Identity;
+use grovedb::batch::Op;
+use grovedb::{Element, ElementFlags, PathQuery, Query, SizedQuery, TransactionArg};
+
+use crate::drive::batch::GroveDbOpBatch;
+use crate::drive::identity::{
+    identity_path, identity_vec_path, serialize_identity, IDENTITY_KEY, IDENTITY_REVISION_KEY,
+};
+use crate::drive::Drive;
+use crate::error::drive::DriveError;
+use crate::error::identity::IdentityError;
+use crate::error::Error;
+use crate::fee::op::DriveOperation;
+use crate::fee::result::FeeResult;
+
+pub(crate) fn identity_revision_to_u64(revision: i64) -> Result<u64, Error> {
+    revision.try_into().map_err(|_| {
+        Error::Identity(IdentityError::InvalidIdentityStructure(
+            "identity revision can't be negative",
+        ))
+    })
+}
+
+pub(crate) fn identity_revision_to_i64(revision: u64) -> Result<i64, Error> {
+    revision.try_into().map_err(|_| {
+        Error::Drive(DriveError::CorruptedIdentityRevision(
+            "identity revision doesn't fit into i64",
+        ))
+    })
+}
+
+pub(crate) fn revision_from_element(element: &Element) -> Result<u64, Error> {
+    if let Element::Item(revision_bytes, _) = element {
+        Ok(u64::from_be_bytes(
+            revision_bytes.as_slice().try_into().map_err(|_| {
+                Error::Drive(DriveError::CorruptedIdentityRevision(
+                    "identity revision item have an invalid length",
+                ))
+            })?,
+        ))
+    } else {
+        Err(Error::Drive(DriveError::CorruptedIdentityRevision(
+            "identity revision must be an item",
+        )))
+    }
+}
+
+pub(crate) fn identity_revision_path_query(identity_id: &[u8]) -> PathQuery {
+    let mut query = Query::new();
+    query.insert_key(IDENTITY_REVISION_KEY.to_vec());
+    PathQuery::new(
+        identity_vec_path(identity_id),
+        SizedQuery::new(query, Some(1), None),
+    )
+}
+
+/// An update staged earlier in the batch is replaced, so the batch holds a single
+/// operation per element
+fn add_replacing_insert(
+    batch: &mut GroveDbOpBatch,
+    path: Vec<Vec<u8>>,
+    key: Vec<u8>,
+    element: Element,
+) {
+    let pending_operation = batch
+        .operations
+        .iter_mut()
+        .find(|op| op.path == path && op.key == key);
+
+    match pending_operation {
+        Some(op) => op.op = Op::Insert { element },
+        None => batch.add_insert(path, key, element),
+    }
+}
+
+impl Drive {
+    pub(crate) fn add_insert_identity_revision_operation(
+        &self,
+        identity_id: &[u8],
+        revision: u64,
+        element_flags: ElementFlags,
+        batch: &mut GroveDbOpBatch,
+    ) {
+        add_replacing_insert(
+            batch,
+            identity_vec_path(identity_id),
+            IDENTITY_REVISION_KEY.to_vec(),
+            Element::Item(revision.to_be_bytes().to_vec(), element_flags),
+        );
+    }
+
+    /// Fetches the revision element taking into account operations that are
+    /// already in the batch
+    fn fetch_identity_revision_element_for_batch(
+        &self,
+        identity_id: &[u8],
+        transaction: TransactionArg,
+        batch: &GroveDbOpBatch,
+    ) -> Result<Option<Element>, Error> {
+        let pending_element = batch.operations.iter().find_map(|op| {
+            if op.path == identity_vec_path(identity_id) && op.key == IDENTITY_REVISION_KEY {
+                if let Op::Insert { element } = &op.op {
+                    return Some(element.clone());
+                }
+            }
+            None
+        });
+
+        match pending_element {
+            Some(element) => Ok(Some(element)),
+            None => self.fetch_identity_revision_element(identity_id, transaction),
+        }
+    }
+
+    fn fetch_identity_revision_element(
+        &self,
+        identity_id: &[u8],
+        transaction: TransactionArg,
+    ) -> Result<Option<Element>, Error> {
+        self.grove
+            .get(
+                identity_path(identity_id),
+                &IDENTITY_REVISION_KEY,
+                transaction,
+            )
+            .unwrap()
+            .map(Some)
+            .or_else(|e| match e {
+                grovedb::Error::PathKeyNotFound(_) | grovedb::Error::PathNotFound(_) => Ok(None),
+                _ => Err(Error::GroveDB(e)),
+            })
+    }
+
+    pub fn fetch_identity_revision(
+        &self,
+        identity_id: [u8; 32],
+        transaction: TransactionArg,
+    ) -> Result<Option<u64>, Error> {
+        self.fetch_identity_revision_element(&identity_id, transaction)?
+            .map(|element| revision_from_element(&element))
+            .transpose()
+    }
+
+    pub fn prove_identity_revision(
+        &self,
+        identity_id: [u8; 32],
+        transaction: TransactionArg,
+    ) -> Result<Vec<u8>, Error> {
+        let path_query = identity_revision_path_query(&identity_id);
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.grove_get_proved_path_query(&path_query, transaction, &mut drive_operations)
+    }
+
+    /// Replaces the identity item and bumps its revision. The stored revision must
+    /// be equal to the expected revision, the balance and keys are updated with
+    /// their own operations.
+    pub fn update_identity_operations(
+        &self,
+        identity: Identity,
+        expected_revision: u64,
+        transaction: TransactionArg,
+        batch: &mut GroveDbOpBatch,
+    ) -> Result<(), Error> {
+        let identity_id = identity.id.buffer;
+
+        let revision_element = self
+            .fetch_identity_revision_element_for_batch(&identity_id, transaction, batch)?
+            .ok_or(Error::Identity(IdentityError::IdentityNotFound(
+                "identity revision not found",
+            )))?;
+
+        let current_revision = revision_from_element(&revision_element)?;
+
+        if current_revision != expected_revision {
+            return Err(Error::Identity(IdentityError::IdentityRevisionMismatch(
+                "identity revision is not equal to the expected revision",
+            )));
+        }
+
+        let new_revision = identity_revision_to_u64(identity.revision)?;
+
+        if new_revision <= current_revision {
+            return Err(Error::Identity(IdentityError::InvalidIdentityRevision(
+                "new identity revision must be greater than the current revision",
+            )));
+        }
+
+        let element_flags = revision_element.get_flags().clone();
+
+        add_replacing_insert(
+            batch,
+            identity_vec_path(&identity_id),
+            IDENTITY_KEY.to_vec(),
+            Element::Item(serialize_identity(&identity)?, element_flags.clone()),
+        );
+
+        self.add_insert_identity_revision_operation(
+            &identity_id,
+            new_revision,
+            element_flags,
+            batch,
+        );
+
+        Ok(())
+    }
+
+    pub fn update_identity(
+        &self,
+        identity: Identity,
+        expected_revision: u64,
+        apply: bool,
+        transaction: TransactionArg,
+    ) -> Result<FeeResult, Error> {
+        let mut batch = GroveDbOpBatch::new();
+
+        self.update_identity_operations(identity, expected_revision, transaction, &mut batch)?;
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+
+        self.apply_batch_grovedb_operations(apply, transaction, batch, &mut drive_operations)?;
+
+        self.calculate_fee(None, Some(drive_operations), false, transaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::helpers::identities::create_test_identity;
+    use crate::common::helpers::setup::setup_drive_with_initial_state_structure;
+    use crate::drive::batch::GroveDbOpBatch;
+    use crate::drive::identity::revision::identity_revision_path_query;
+    use crate::error::identity::IdentityError;
+    use crate::error::Error;
+    use grovedb::GroveDb;
+
+    #[test]
+    fn test_update_identity_with_expected_revision() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let transaction = drive.grove.start_transaction();
+
+        let mut identity = create_test_identity(&drive, [10; 32], Some(&transaction));
+
+        let revision = drive
+            .fetch_identity_revision(identity.id.buffer, Some(&transaction))
+            .expect("should fetch revision");
+
+        assert_eq!(revision, Some(1));
+
+        identity.revision = 2;
+        identity.protocol_version = 1;
+
+        drive
+            .update_identity(identity.clone(), 1, true, Some(&transaction))
+            .expect("should update identity");
+
+        identity.revision = 3;
+
+        let result = drive.update_identity(identity.clone(), 1, true, Some(&transaction));
+
+        assert!(matches!(
+            result,
+            Err(Error::Identity(IdentityError::IdentityRevisionMismatch(_)))
+        ));
+
+        let (fetched_identity, _) = drive
+            .fetch_identity(&identity.id.buffer, Some(&transaction))
+            .expect("should fetch identity");
+
+        assert_eq!(fetched_identity.revision, 2);
+        assert_eq!(fetched_identity.protocol_version, 1);
+
+        let proof = drive
+            .prove_identity_revision(identity.id.buffer, Some(&transaction))
+            .expect("should prove revision");
+
+        let (_, proved_key_values) = GroveDb::verify_query(
+            proof.as_slice(),
+            &identity_revision_path_query(&identity.id.buffer),
+        )
+        .expect("should verify proof");
+
+        assert_eq!(proved_key_values.len(), 1);
+    }
+
+    #[test]
+    fn test_update_identity_twice_in_batch() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let transaction = drive.grove.start_transaction();
+
+        let mut identity = create_test_identity(&drive, [12; 32], Some(&transaction));
+
+        let mut batch = GroveDbOpBatch::new();
+
+        identity.revision = 2;
+
+        drive
+            .update_identity_operations(identity.clone(), 1, Some(&transaction), &mut batch)
+            .expect("should update identity");
+
+        identity.revision = 3;
+
+        let result =
+            drive.update_identity_operations(identity.clone(), 1, Some(&transaction), &mut batch);
+
+        assert!(matches!(
+            result,
+            Err(Error::Identity(IdentityError::IdentityRevisionMismatch(_)))
+        ));
+
+        drive
+            .update_identity_operations(identity.clone(), 2, Some(&transaction), &mut batch)
+            .expect("should update identity with the revision staged in the batch");
+
+        drive
+            .grove_apply_batch(batch, false, Some(&transaction))
+            .expect("should apply batch");
+
+        let revision = drive
+            .fetch_identity_revision(identity.id.buffer, Some(&transaction))
+            .expect("should fetch revision");
+
+        assert_eq!(revision, Some(3));
+    }
+}
@@ -1,5 +1,8 @@
 use crate::drive::batch::GroveDbOpBatch;
-use crate::drive::contract::add_init_contracts_structure_operations;
+use crate::drive::contract::{
+    add_init_contract_deletions_structure_operations, add_init_contracts_structure_operations,
+};
+use crate::drive::document::index_backfill::add_init_index_backfills_structure_operations;
 use crate::drive::{Drive, RootTree};
 use crate::error::Error;
 use crate::fee_pools::add_create_fee_pool_trees_operations;
@@ -22,6 +25,12 @@ impl Drive {
         // initialize the pools with epochs
         add_create_fee_pool_trees_operations(&mut batch);
 
+        batch.add_insert_empty_tree(vec![], vec![RootTree::Misc as u8]);
+
+        add_init_index_backfills_structure_operations(&mut batch);
+
+        add_init_contract_deletions_structure_operations(&mut batch);
+
         self.grove_apply_batch(batch, false, transaction)?;
 
         Ok(())
@@ -62,6 +71,6 @@ mod tests {
                 &mut drive_operations,
             )
             .expect("expected to get root elements");
-        assert_eq!(elements.len(), 5);
+        assert_eq!(elements.len(), 6);
     }
 }
@@ -2,19 +2,23 @@ use std::cell::RefCell;
 use std::path::Path;
 use std::sync::Arc;
 
+use enum_map::EnumMap;
 use grovedb::{GroveDb, Transaction, TransactionArg};
 use moka::sync::Cache;
-
-use object_size_info::DocumentAndContractInfo;
-use object_size_info::DocumentInfo::DocumentSize;
+use rust_decimal::Decimal;
 
 use crate::contract::Contract;
 use crate::drive::batch::GroveDbOpBatch;
 use crate::drive::config::DriveConfig;
 use crate::error::Error;
-use crate::fee::op::DriveOperation;
+use crate::fee::calculate_fee;
+use crate::fee::fee_schedule::FeeSchedule;
 use crate::fee::op::DriveOperation::GroveOperation;
+use crate::fee::op::{BaseOp, DriveOperation};
+use crate::fee::result::FeeResult;
+use crate::fee_pools::epochs::Epoch;
 
+pub mod asset_lock;
 pub mod batch;
 pub mod config;
 pub mod contract;
@@ -27,13 +31,17 @@ mod grove_operations;
 pub mod identity;
 pub mod initialization;
 pub mod object_size_info;
+pub mod protocol_version;
 pub mod query;
-
-use dpp::data_contract::extra::DriveContractExt;
+pub mod worst_case_fee;
 
 pub struct DriveCache {
     pub cached_contracts: Cache<[u8; 32], Arc<Contract>>,
     pub genesis_time_ms: Option<u64>,
+    /// The fee schedule of the current epoch, cached at block begin
+    pub current_epoch_fee_schedule: Option<FeeSchedule>,
+    /// The fee multiplier of the current epoch, cached at block begin
+    pub current_epoch_fee_multiplier: Option<Decimal>,
 }
 
 pub struct Drive {
@@ -50,6 +58,7 @@ pub enum RootTree {
     PublicKeyHashesToIdentities = 2,
     SpentAssetLockTransactions = 3,
     Pools = 4,
+    Misc = 5,
 }
 
 pub const STORAGE_COST: i32 = 50;
@@ -74,6 +83,7 @@ impl From<RootTree> for &'static [u8; 1] {
             RootTree::PublicKeyHashesToIdentities => &[2],
             RootTree::SpentAssetLockTransactions => &[3],
             RootTree::Pools => &[4],
+            RootTree::Misc => &[5],
         }
     }
 }
@@ -98,6 +108,8 @@ impl Drive {
                     cache: RefCell::new(DriveCache {
                         cached_contracts: Cache::new(200),
                         genesis_time_ms,
+                        current_epoch_fee_schedule: None,
+                        current_epoch_fee_multiplier: None,
                     }),
                 })
             }
@@ -136,6 +148,30 @@ impl Drive {
         }
     }
 
+    /// Calculates the fees of the operations with the fee schedule and the multiplier
+    /// of the current epoch, they are only read from the state if they are not cached.
+    /// Operations only known by their sizes are charged as their worst case.
+    pub(crate) fn calculate_fee(
+        &self,
+        base_operations: Option<EnumMap<BaseOp, u64>>,
+        drive_operations: Option<Vec<DriveOperation>>,
+        with_breakdown: bool,
+        transaction: TransactionArg,
+    ) -> Result<FeeResult, Error> {
+        let drive_operations = drive_operations
+            .map(|drive_operations| self.worst_case_drive_operations(drive_operations))
+            .transpose()?;
+        let (fee_schedule, fee_multiplier) = self.get_current_epoch_fees(transaction)?;
+        calculate_fee(
+            base_operations,
+            drive_operations,
+            &fee_schedule,
+            |epoch_index| self.get_epoch_fee_schedule(&Epoch::new(epoch_index), transaction),
+            fee_multiplier,
+            with_breakdown,
+        )
+    }
+
     fn apply_batch_drive_operations(
         &self,
         apply: bool,
@@ -176,26 +212,6 @@ impl Drive {
         }
         Ok(())
     }
-
-    pub fn worst_case_fee_for_document_type_with_name(
-        &self,
-        contract: &Contract,
-        document_type_name: &str,
-    ) -> Result<(i64, u64), Error> {
-        let document_type = contract.document_type_for_name(document_type_name)?;
-        self.add_document_for_contract(
-            DocumentAndContractInfo {
-                document_info: DocumentSize(document_type.max_size()),
-                contract,
-                document_type,
-                owner_id: None,
-            },
-            false,
-            0.0,
-            false,
-            None,
-        )
-    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,211 @@
+use crate::drive::defaults::PROTOCOL_VERSION;
+use crate::drive::fee_pools::{pools_path, pools_vec_path};
+use crate::drive::Drive;
+use crate::error::fee::FeeError;
+use crate::error::Error;
+use crate::fee::fee_schedule::FeeSchedule;
+use crate::fee_pools::epochs::{epoch_key_constants, Epoch};
+use grovedb::batch::{GroveDbOp, Op};
+use grovedb::{Element, TransactionArg};
+
+const KEY_PROTOCOL_VERSION: &[u8; 1] = b"v";
+
+fn update_protocol_version_operation(protocol_version: u32) -> GroveDbOp {
+    GroveDbOp {
+        path: pools_vec_path(),
+        key: KEY_PROTOCOL_VERSION.to_vec(),
+        op: Op::Insert {
+            element: Element::Item(protocol_version.to_be_bytes().to_vec(), None),
+        },
+    }
+}
+
+/// Parses a stored protocol version
+fn protocol_version_from_element(element: Element) -> Result<u32, Error> {
+    match element {
+        Element::Item(item, _) => Ok(u32::from_be_bytes(item.as_slice().try_into().map_err(
+            |_| {
+                Error::Fee(FeeError::CorruptedProtocolVersionItemLength(
+                    "protocol version item have an invalid length",
+                ))
+            },
+        )?)),
+        _ => Err(Error::Fee(FeeError::CorruptedProtocolVersionNotItem(
+            "protocol version must be an item",
+        ))),
+    }
+}
+
+impl Drive {
+    /// Returns the protocol version set by the network, its fee schedule is charged from
+    /// the next epoch on
+    pub fn get_protocol_version(&self, transaction: TransactionArg) -> Result<u32, Error> {
+        let element = self
+            .grove
+            .get(pools_path(), KEY_PROTOCOL_VERSION.as_slice(), transaction)
+            .unwrap()
+            .map(Some)
+            .or_else(|e| match e {
+                // the protocol version is only stored once it was upgraded
+                grovedb::Error::PathKeyNotFound(_) => Ok(None),
+                _ => Err(e),
+            })?;
+
+        element.map_or(Ok(PROTOCOL_VERSION), protocol_version_from_element)
+    }
+
+    /// Returns the protocol version persisted for the epoch when it started
+    pub fn get_epoch_protocol_version(
+        &self,
+        epoch_tree: &Epoch,
+        transaction: TransactionArg,
+    ) -> Result<u32, Error> {
+        let element = self
+            .grove
+            .get(
+                epoch_tree.get_path(),
+                epoch_key_constants::KEY_PROTOCOL_VERSION.as_slice(),
+                transaction,
+            )
+            .unwrap()
+            .map(Some)
+            .or_else(|e| match e {
+                // epochs that did not start yet and epochs started before the protocol
+                // version was persisted
+                grovedb::Error::PathKeyNotFound(_) => Ok(None),
+                _ => Err(e),
+            })?;
+
+        element.map_or(Ok(PROTOCOL_VERSION), protocol_version_from_element)
+    }
+
+    /// Sets the protocol version, fees are charged with its fee schedule from the next
+    /// epoch on
+    pub fn update_protocol_version(
+        &self,
+        protocol_version: u32,
+        transaction: TransactionArg,
+    ) -> Result<(), Error> {
+        FeeSchedule::for_protocol_version(&self.config.fee_schedules, protocol_version)?;
+
+        self.grove_apply_operation(
+            update_protocol_version_operation(protocol_version),
+            false,
+            transaction,
+        )
+    }
+
+    /// Returns the fee schedule of the protocol version of the current epoch
+    pub fn get_fee_schedule(&self, transaction: TransactionArg) -> Result<&FeeSchedule, Error> {
+        match self.get_current_epoch_index(transaction)? {
+            None => FeeSchedule::for_protocol_version(&self.config.fee_schedules, PROTOCOL_VERSION),
+            Some(epoch_index) => self.get_epoch_fee_schedule(&Epoch::new(epoch_index), transaction),
+        }
+    }
+
+    /// Returns the fee schedule storage was paid with in the epoch, refunds are priced
+    /// with it
+    pub fn get_epoch_fee_schedule(
+        &self,
+        epoch_tree: &Epoch,
+        transaction: TransactionArg,
+    ) -> Result<&FeeSchedule, Error> {
+        let protocol_version = self.get_epoch_protocol_version(epoch_tree, transaction)?;
+
+        FeeSchedule::for_protocol_version(&self.config.fee_schedules, protocol_version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::helpers::setup::setup_drive_with_initial_state_structure;
+    use crate::drive::batch::GroveDbOpBatch;
+    use crate::drive::config::DriveConfig;
+    use crate::drive::defaults::PROTOCOL_VERSION;
+    use crate::drive::Drive;
+    use crate::error::fee::FeeError;
+    use crate::error::Error;
+    use crate::fee::fee_schedule::FeeSchedule;
+    use crate::fee_pools::epochs::Epoch;
+    use crate::fee_pools::update_current_epoch_index_operation;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_default_protocol_version() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let protocol_version = drive
+            .get_protocol_version(None)
+            .expect("should get protocol version");
+
+        assert_eq!(protocol_version, PROTOCOL_VERSION);
+
+        let fee_schedule = drive
+            .get_fee_schedule(None)
+            .expect("should get fee schedule");
+
+        assert_eq!(fee_schedule, &FeeSchedule::default());
+    }
+
+    #[test]
+    fn test_update_protocol_version_activates_fee_schedule_with_the_next_epoch() {
+        let mut config = DriveConfig::default();
+
+        let new_fee_schedule = FeeSchedule {
+            storage_seek_cost: 200,
+            ..Default::default()
+        };
+
+        config
+            .fee_schedules
+            .insert(PROTOCOL_VERSION + 1, new_fee_schedule.clone());
+
+        let tmp_dir = TempDir::new().unwrap();
+        let drive = Drive::open(tmp_dir, Some(config)).expect("should open Drive successfully");
+
+        drive
+            .create_initial_state_structure(None)
+            .expect("expected to create root tree successfully");
+
+        drive
+            .update_protocol_version(PROTOCOL_VERSION + 1, None)
+            .expect("should update protocol version");
+
+        // the fee schedule is only activated with the next epoch
+        let fee_schedule = drive
+            .get_fee_schedule(None)
+            .expect("should get fee schedule");
+
+        assert_eq!(fee_schedule, &FeeSchedule::default());
+
+        let epoch = Epoch::new(1);
+        let mut batch = GroveDbOpBatch::new();
+        epoch.add_init_current_operations(1.0, 1, 1, &mut batch);
+        batch.push(epoch.update_protocol_version_operation(PROTOCOL_VERSION + 1));
+        batch.push(update_current_epoch_index_operation(epoch.index));
+        drive
+            .grove_apply_batch(batch, false, None)
+            .expect("should apply batch");
+
+        let fee_schedule = drive
+            .get_fee_schedule(None)
+            .expect("should get fee schedule");
+
+        assert_eq!(fee_schedule, &new_fee_schedule);
+
+        // storage paid in the previous epoch is refunded with its fee schedule
+        let fee_schedule = drive
+            .get_epoch_fee_schedule(&Epoch::new(0), None)
+            .expect("should get epoch fee schedule");
+
+        assert_eq!(fee_schedule, &FeeSchedule::default());
+
+        match drive.update_protocol_version(0, None) {
+            Ok(_) => assert!(false, "must be an error"),
+            Err(e) => match e {
+                Error::Fee(FeeError::UnknownFeeSchedule(_)) => assert!(true),
+                _ => assert!(false, "invalid error type"),
+            },
+        }
+    }
+}
@@ -4,7 +4,6 @@ use crate::contract::{Contract, DocumentType};
 use crate::drive::Drive;
 use crate::error::query::QueryError;
 use crate::error::Error;
-use crate::fee::calculate_fee;
 use crate::fee::op::DriveOperation;
 use crate::query::DriveQuery;
 
@@ -32,10 +31,35 @@ impl Drive {
             transaction,
             &mut drive_operations,
         )?;
-        let (_, cost) = calculate_fee(None, Some(drive_operations))?;
+        let cost = self
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
         Ok((items, skipped, cost))
     }
 
+    /// Counts the documents matching the query, the limit of the query is ignored
+    pub fn query_documents_count(
+        &self,
+        query_cbor: &[u8],
+        contract_id: [u8; 32],
+        document_type_name: &str,
+        transaction: TransactionArg,
+    ) -> Result<(u64, u64), Error> {
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+        let contract = self
+            .get_contract(contract_id, transaction, &mut drive_operations)?
+            .ok_or(Error::Query(QueryError::ContractNotFound(
+                "contract not found",
+            )))?;
+        let document_type = contract.document_type_for_name(document_type_name)?;
+        let query = DriveQuery::from_cbor(query_cbor, &contract, document_type)?;
+        let count = query.execute_count_internal(self, transaction, &mut drive_operations)?;
+        let cost = self
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
+        Ok((count, cost))
+    }
+
     pub fn query_documents_from_contract_cbor(
         &self,
         contract_cbor: &[u8],
@@ -55,7 +79,9 @@ impl Drive {
             transaction,
             &mut drive_operations,
         )?;
-        let (_, cost) = calculate_fee(None, Some(drive_operations))?;
+        let cost = self
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
         Ok((items, skipped, cost))
     }
 
@@ -74,7 +100,9 @@ impl Drive {
             transaction,
             &mut drive_operations,
         )?;
-        let (_, cost) = calculate_fee(None, Some(drive_operations))?;
+        let cost = self
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
         Ok((items, skipped, cost))
     }
 
@@ -112,7 +140,9 @@ impl Drive {
             transaction,
             &mut drive_operations,
         )?;
-        let (_, cost) = calculate_fee(None, Some(drive_operations))?;
+        let cost = self
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
         Ok((items, cost))
     }
 
@@ -135,7 +165,9 @@ impl Drive {
             transaction,
             &mut drive_operations,
         )?;
-        let (_, cost) = calculate_fee(None, Some(drive_operations))?;
+        let cost = self
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
         Ok((items, cost))
     }
 
@@ -155,7 +187,9 @@ impl Drive {
             transaction,
             &mut drive_operations,
         )?;
-        let (_, cost) = calculate_fee(None, Some(drive_operations))?;
+        let cost = self
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
         Ok((items, cost))
     }
 
@@ -189,7 +223,9 @@ impl Drive {
                 transaction,
                 &mut drive_operations,
             )?;
-        let (_, cost) = calculate_fee(None, Some(drive_operations))?;
+        let cost = self
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
         Ok((root_hash, items, cost))
     }
 
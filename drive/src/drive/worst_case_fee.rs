@@ -0,0 +1,557 @@
+use costs::OperationCost;
+use dpp::data_contract::extra::DriveContractExt;
+use dpp::identifier::Identifier;
+use dpp::identity::{Identity, IdentityPublicKey, KeyID, KeyType, Purpose, SecurityLevel};
+use grovedb::Element;
+use rust_decimal::Decimal;
+
+use crate::contract::Contract;
+use crate::drive::batch::GroveDbOpBatch;
+use crate::drive::flags::StorageFlags;
+use crate::drive::object_size_info::DocumentAndContractInfo;
+use crate::drive::object_size_info::DocumentInfo::DocumentSize;
+use crate::drive::Drive;
+use crate::error::Error;
+use crate::fee::calculate_fee;
+use crate::fee::fee_schedule::FeeSchedule;
+use crate::fee::op::DriveOperation::{
+    CalculatedCostOperation, CostCalculationDeleteOperation, CostCalculationInsertOperation,
+    CostCalculationQueryOperation,
+};
+use crate::fee::op::{
+    DriveOperation, SizesOfDeleteOperation, SizesOfInsertOperation, SizesOfQueryOperation,
+};
+use crate::fee::result::FeeResult;
+
+/// BLS12-381 public keys are the largest identity keys
+const MAX_PUBLIC_KEY_DATA_SIZE: usize = 48;
+
+/// Every path segment is at least a byte, so a path of one byte segments is the
+/// deepest one of its size
+fn worst_case_path(path_size: u32) -> Vec<Vec<u8>> {
+    vec![vec![0]; path_size as usize]
+}
+
+impl Drive {
+    /// Operations only known by their sizes can't be priced, in the worst case grovedb
+    /// costs them as operations at the deepest path of their path size
+    pub(super) fn worst_case_drive_operations(
+        &self,
+        drive_operations: Vec<DriveOperation>,
+    ) -> Result<Vec<DriveOperation>, Error> {
+        let mut worst_case_operations: Vec<DriveOperation> = vec![];
+        for drive_operation in drive_operations {
+            match drive_operation {
+                CostCalculationInsertOperation(SizesOfInsertOperation {
+                    path_size,
+                    key_size,
+                    value_size,
+                }) => {
+                    let element_flags = StorageFlags::default().to_element_flags();
+                    // trees are inserted without a value
+                    let element = if value_size == 0 {
+                        Element::empty_tree_with_flags(element_flags)
+                    } else {
+                        Element::Item(vec![0; value_size as usize], element_flags)
+                    };
+                    let mut batch = GroveDbOpBatch::new();
+                    batch.add_insert(
+                        worst_case_path(path_size),
+                        vec![0; key_size as usize],
+                        element,
+                    );
+                    self.grove_batch_operations_costs(batch, false, &mut worst_case_operations)?;
+                }
+                CostCalculationDeleteOperation(SizesOfDeleteOperation {
+                    path_size,
+                    key_size,
+                    multiplier,
+                    ..
+                }) => {
+                    // the multiplier is the number of trees the deletion can go up
+                    for _ in 0..multiplier.max(1) {
+                        let mut batch = GroveDbOpBatch::new();
+                        batch.add_delete(worst_case_path(path_size), vec![0; key_size as usize]);
+                        self.grove_batch_operations_costs(
+                            batch,
+                            false,
+                            &mut worst_case_operations,
+                        )?;
+                    }
+                }
+                CostCalculationQueryOperation(SizesOfQueryOperation {
+                    path_size,
+                    key_size,
+                    value_size,
+                }) => {
+                    let path = worst_case_path(path_size);
+                    let key = vec![0; key_size as usize];
+                    self.grove_has_raw(
+                        path.iter().map(|segment| segment.as_slice()),
+                        key.as_slice(),
+                        false,
+                        None,
+                        &mut worst_case_operations,
+                    )?;
+                    worst_case_operations.push(CalculatedCostOperation(OperationCost {
+                        seek_count: 0,
+                        storage_written_bytes: 0,
+                        storage_loaded_bytes: value_size,
+                        storage_freed_bytes: 0,
+                        hash_byte_calls: 0,
+                        hash_node_calls: 0,
+                    }));
+                }
+                drive_operation => worst_case_operations.push(drive_operation),
+            }
+        }
+        Ok(worst_case_operations)
+    }
+
+    /// Estimates are priced with the given fee schedule and multiplier rather than those
+    /// of the current epoch, so they don't depend on the state
+    fn worst_case_fee(
+        &self,
+        drive_operations: Vec<DriveOperation>,
+        fee_schedule: &FeeSchedule,
+        fee_multiplier: Decimal,
+    ) -> Result<FeeResult, Error> {
+        let drive_operations = self.worst_case_drive_operations(drive_operations)?;
+        // estimates remove no bytes paid in an epoch, there is nothing to refund
+        calculate_fee(
+            None,
+            Some(drive_operations),
+            fee_schedule,
+            |_| Ok(fee_schedule),
+            fee_multiplier,
+            false,
+        )
+    }
+
+    /// The fees of inserting the largest document the type allows
+    pub fn worst_case_fee_for_document_type_with_name(
+        &self,
+        contract: &Contract,
+        document_type_name: &str,
+        fee_schedule: &FeeSchedule,
+        fee_multiplier: Decimal,
+    ) -> Result<FeeResult, Error> {
+        let document_type = contract.document_type_for_name(document_type_name)?;
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+        self.add_document_for_contract_operations(
+            DocumentAndContractInfo {
+                document_info: DocumentSize(document_type.max_size()),
+                contract,
+                document_type,
+                owner_id: None,
+            },
+            false,
+            0.0,
+            false,
+            None,
+            &mut drive_operations,
+        )?;
+        self.worst_case_fee(drive_operations, fee_schedule, fee_multiplier)
+    }
+
+    /// The fees of replacing a document of the type with the largest one it allows
+    pub fn worst_case_fee_for_document_update_with_name(
+        &self,
+        contract: &Contract,
+        document_type_name: &str,
+        fee_schedule: &FeeSchedule,
+        fee_multiplier: Decimal,
+    ) -> Result<FeeResult, Error> {
+        let document_type = contract.document_type_for_name(document_type_name)?;
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+        self.update_document_for_contract_operations(
+            DocumentAndContractInfo {
+                document_info: DocumentSize(document_type.max_size()),
+                contract,
+                document_type,
+                owner_id: None,
+            },
+            0.0,
+            false,
+            None,
+            &mut drive_operations,
+        )?;
+        self.worst_case_fee(drive_operations, fee_schedule, fee_multiplier)
+    }
+
+    /// The fees of deleting the largest document the type allows
+    pub fn worst_case_fee_for_document_delete_with_name(
+        &self,
+        contract: &Contract,
+        document_type_name: &str,
+        fee_schedule: &FeeSchedule,
+        fee_multiplier: Decimal,
+    ) -> Result<FeeResult, Error> {
+        let document_type = contract.document_type_for_name(document_type_name)?;
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+        self.add_worst_case_delete_document_operations(document_type, &mut drive_operations)?;
+        self.worst_case_fee(drive_operations, fee_schedule, fee_multiplier)
+    }
+
+    /// The fees of creating the contract, or of updating a stored version of it. An update
+    /// writes at most the trees and the contract a creation writes, along with the backfill
+    /// of the indices it adds to document types that already have documents.
+    pub fn worst_case_fee_for_contract(
+        &self,
+        contract: &Contract,
+        contract_serialization: Vec<u8>,
+        fee_schedule: &FeeSchedule,
+        fee_multiplier: Decimal,
+    ) -> Result<FeeResult, Error> {
+        let storage_flags = StorageFlags::default();
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+        self.insert_contract(
+            Element::Item(contract_serialization, storage_flags.to_element_flags()),
+            contract,
+            0.0,
+            false,
+            None,
+            &mut drive_operations,
+        )?;
+
+        let mut batch_operations: Vec<DriveOperation> = vec![];
+        for document_type in contract.document_types().values() {
+            self.add_worst_case_index_backfills_operations(
+                contract,
+                document_type,
+                &storage_flags,
+                &mut batch_operations,
+            )?;
+        }
+        self.apply_batch_drive_operations(false, None, batch_operations, &mut drive_operations)?;
+
+        self.worst_case_fee(drive_operations, fee_schedule, fee_multiplier)
+    }
+
+    /// The fees of creating an identity with as many keys, each of the largest size
+    pub fn worst_case_fee_for_identity(
+        &self,
+        key_count: u16,
+        fee_schedule: &FeeSchedule,
+        fee_multiplier: Decimal,
+    ) -> Result<FeeResult, Error> {
+        let identity = Identity {
+            id: Identifier::new([u8::MAX; 32]),
+            revision: i64::MAX,
+            balance: i64::MAX,
+            protocol_version: u32::MAX,
+            public_keys: (0..key_count)
+                .map(|key_index| IdentityPublicKey {
+                    id: KeyID::MAX - key_index as KeyID,
+                    key_type: KeyType::BLS12_381,
+                    data: vec![u8::MAX; MAX_PUBLIC_KEY_DATA_SIZE],
+                    purpose: Purpose::AUTHENTICATION,
+                    security_level: SecurityLevel::MASTER,
+                    read_only: false,
+                })
+                .collect(),
+            asset_lock_proof: None,
+            metadata: None,
+        };
+        let mut batch = GroveDbOpBatch::new();
+        self.add_insert_identity_operations(
+            identity,
+            false,
+            StorageFlags::default(),
+            None,
+            &mut batch,
+        )?;
+
+        let mut drive_operations: Vec<DriveOperation> = vec![];
+        self.apply_batch_grovedb_operations(false, None, batch, &mut drive_operations)?;
+
+        self.worst_case_fee(drive_operations, fee_schedule, fee_multiplier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dpp::data_contract::extra::DriveContractExt;
+    use dpp::identifier::Identifier;
+    use dpp::identity::{Identity, IdentityPublicKey, KeyType, Purpose, SecurityLevel};
+
+    use crate::common::helpers::setup::setup_drive_with_initial_state_structure;
+    use crate::common::{setup_contract, value_to_cbor};
+    use crate::contract::{Contract, CreateRandomDocument};
+    use crate::drive::batch::GroveDbOpBatch;
+    use crate::drive::flags::StorageFlags;
+    use crate::drive::object_size_info::DocumentAndContractInfo;
+    use crate::drive::object_size_info::DocumentInfo::DocumentAndSerialization;
+    use crate::drive::Drive;
+    use crate::fee::fee_schedule::FeeSchedule;
+    use crate::fee::result::FeeResult;
+    use crate::fee_pools::epochs::Epoch;
+    use crate::fee_pools::update_current_epoch_index_operation;
+    use rust_decimal::Decimal;
+
+    fn current_epoch_fees(drive: &Drive) -> (FeeSchedule, Decimal) {
+        drive
+            .get_current_epoch_fees(None)
+            .expect("expected to get the current epoch fees")
+    }
+
+    fn assert_upper_bound(estimated_fee: FeeResult, actual_fee: FeeResult) {
+        assert!(
+            estimated_fee.storage_fee >= actual_fee.storage_fee,
+            "estimated storage fee {} is below the actual {}",
+            estimated_fee.storage_fee,
+            actual_fee.storage_fee
+        );
+        assert!(
+            estimated_fee.processing_fee >= actual_fee.processing_fee,
+            "estimated processing fee {} is below the actual {}",
+            estimated_fee.processing_fee,
+            actual_fee.processing_fee
+        );
+    }
+
+    #[test]
+    fn test_worst_case_document_fees_are_upper_bounds() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let contract = setup_contract(
+            &drive,
+            "tests/supporting_files/contract/family/family-contract.json",
+            None,
+            None,
+        );
+        let document_type = contract
+            .document_type_for_name("person")
+            .expect("expected to get document type");
+
+        let mut documents = document_type.random_filled_documents(2, Some(5));
+        let document = documents.remove(0);
+        // every indexed value changes on update
+        let mut updated_document = documents.remove(0);
+        updated_document.id = document.id;
+        updated_document.owner_id = document.owner_id;
+
+        let storage_flags = StorageFlags { epoch: 0 };
+        let (fee_schedule, fee_multiplier) = current_epoch_fees(&drive);
+
+        let estimated_fee = drive
+            .worst_case_fee_for_document_type_with_name(
+                &contract,
+                "person",
+                &fee_schedule,
+                fee_multiplier,
+            )
+            .expect("expected to estimate the insertion");
+        let actual_fee = drive
+            .add_document_for_contract(
+                DocumentAndContractInfo {
+                    document_info: DocumentAndSerialization((
+                        &document,
+                        document.to_cbor().as_slice(),
+                        &storage_flags,
+                    )),
+                    contract: &contract,
+                    document_type,
+                    owner_id: None,
+                },
+                false,
+                0f64,
+                true,
+                false,
+                None,
+            )
+            .expect("expected to insert a document successfully");
+        assert_upper_bound(estimated_fee, actual_fee);
+
+        let estimated_fee = drive
+            .worst_case_fee_for_document_update_with_name(
+                &contract,
+                "person",
+                &fee_schedule,
+                fee_multiplier,
+            )
+            .expect("expected to estimate the update");
+        let actual_fee = drive
+            .update_document_for_contract(
+                &updated_document,
+                updated_document.to_cbor().as_slice(),
+                &contract,
+                "person",
+                None,
+                0f64,
+                true,
+                false,
+                storage_flags,
+                None,
+            )
+            .expect("expected to update a document successfully");
+        assert_upper_bound(estimated_fee, actual_fee);
+
+        let estimated_fee = drive
+            .worst_case_fee_for_document_delete_with_name(
+                &contract,
+                "person",
+                &fee_schedule,
+                fee_multiplier,
+            )
+            .expect("expected to estimate the deletion");
+        let actual_fee = drive
+            .delete_document_for_contract(
+                document.id.as_slice(),
+                &contract,
+                "person",
+                None,
+                true,
+                false,
+                None,
+            )
+            .expect("expected to delete a document successfully");
+        assert_upper_bound(estimated_fee, actual_fee);
+    }
+
+    #[test]
+    fn test_worst_case_contract_fees_are_upper_bounds() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let mut contract_value: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string("tests/supporting_files/contract/family/family-contract.json")
+                .expect("expected to read the contract"),
+        )
+        .expect("expected a valid json");
+
+        let (fee_schedule, fee_multiplier) = current_epoch_fees(&drive);
+
+        let apply_contract = |contract_value: serde_json::Value| {
+            let contract_cbor = value_to_cbor(contract_value, Some(1));
+            let contract = <Contract as DriveContractExt>::from_cbor(&contract_cbor, None)
+                .expect("expected to deserialize the contract");
+
+            let estimated_fee = drive
+                .worst_case_fee_for_contract(
+                    &contract,
+                    contract_cbor.clone(),
+                    &fee_schedule,
+                    fee_multiplier,
+                )
+                .expect("expected to estimate the contract");
+            let actual_fee = drive
+                .apply_contract(
+                    &contract,
+                    contract_cbor,
+                    0f64,
+                    true,
+                    false,
+                    StorageFlags::default(),
+                    None,
+                )
+                .expect("expected to apply contract successfully");
+            assert_upper_bound(estimated_fee, actual_fee);
+            contract
+        };
+
+        apply_contract(contract_value.clone());
+
+        contract_value["version"] = serde_json::json!(2);
+        contract_value["documents"]["pet"] = serde_json::json!({
+            "indices": [{"properties": [{"name": "asc"}]}],
+            "properties": {
+                "name": {"type": "string", "maxLength": 50}
+            },
+            "additionalProperties": false
+        });
+        let contract = apply_contract(contract_value.clone());
+
+        let document_type = contract
+            .document_type_for_name("person")
+            .expect("expected to get document type");
+        let storage_flags = StorageFlags { epoch: 0 };
+        for document in document_type.random_filled_documents(5, Some(7)).iter() {
+            drive
+                .add_document_for_contract(
+                    DocumentAndContractInfo {
+                        document_info: DocumentAndSerialization((
+                            document,
+                            document.to_cbor().as_slice(),
+                            &storage_flags,
+                        )),
+                        contract: &contract,
+                        document_type,
+                        owner_id: None,
+                    },
+                    false,
+                    0f64,
+                    true,
+                    false,
+                    None,
+                )
+                .expect("expected to insert a document successfully");
+        }
+
+        // the added index starts a backfill of the existing documents
+        contract_value["version"] = serde_json::json!(3);
+        contract_value["documents"]["person"]["indices"]
+            .as_array_mut()
+            .expect("expected person indices")
+            .push(serde_json::json!({"properties": [{"lastName": "asc"}]}));
+        apply_contract(contract_value);
+
+        assert!(!drive
+            .fetch_index_backfills(contract.id.as_bytes(), "person", None, &mut vec![])
+            .expect("expected to fetch the index backfills")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_worst_case_identity_fee_is_an_upper_bound() {
+        let drive = setup_drive_with_initial_state_structure();
+
+        let identity = Identity {
+            id: Identifier::new([1; 32]),
+            revision: 1,
+            balance: 10,
+            protocol_version: 0,
+            public_keys: (0..3)
+                .map(|id| IdentityPublicKey {
+                    id,
+                    key_type: KeyType::ECDSA_SECP256K1,
+                    data: vec![id as u8; 33],
+                    purpose: Purpose::AUTHENTICATION,
+                    security_level: SecurityLevel::MASTER,
+                    read_only: false,
+                })
+                .collect(),
+            asset_lock_proof: None,
+            metadata: None,
+        };
+
+        let (fee_schedule, fee_multiplier) = current_epoch_fees(&drive);
+
+        let estimated_fee = drive
+            .worst_case_fee_for_identity(3, &fee_schedule, fee_multiplier)
+            .expect("expected to estimate the identity");
+        let actual_fee = drive
+            .insert_identity(identity, true, false, StorageFlags::default(), None)
+            .expect("expected to insert the identity");
+        assert_upper_bound(estimated_fee.clone(), actual_fee);
+
+        // a new epoch with another multiplier doesn't change the estimate, neither
+        // does the inserted identity
+        let mut batch = GroveDbOpBatch::new();
+        Epoch::new(0).add_init_current_operations(3.0, 1, 1, &mut batch);
+        batch.push(update_current_epoch_index_operation(0));
+        drive
+            .grove_apply_batch(batch, false, None)
+            .expect("expected to start a new epoch");
+        drive
+            .cache_current_epoch_fees(None)
+            .expect("expected to cache the current epoch fees");
+        assert_ne!(current_epoch_fees(&drive).1, fee_multiplier);
+
+        assert_eq!(
+            drive
+                .worst_case_fee_for_identity(3, &fee_schedule, fee_multiplier)
+                .expect("expected to estimate the identity"),
+            estimated_fee
+        );
+    }
+}
@@ -20,6 +20,16 @@ pub enum DriveError {
 
     #[error("deleting document that does not exist error: {0}")]
     DeletingDocumentThatDoesNotExist(&'static str),
+    #[error("deleting contract that does not exist error: {0}")]
+    DeletingContractThatDoesNotExist(&'static str),
+    #[error("archiving contract that does not exist error: {0}")]
+    ArchivingContractThatDoesNotExist(&'static str),
+    #[error("contract archived error: {0}")]
+    ContractArchived(&'static str),
+    #[error("deleting contract that is not archived error: {0}")]
+    DeletingContractThatIsNotArchived(&'static str),
+    #[error("contract deletion in progress error: {0}")]
+    ContractDeletionInProgress(&'static str),
 
     #[error("changing contract to readonly error: {0}")]
     ChangingContractToReadOnly(&'static str),
@@ -33,9 +43,27 @@ pub enum DriveError {
     ChangingDocumentTypeMutability(&'static str),
     #[error("changing document type keeps history error: {0}")]
     ChangingDocumentTypeKeepsHistory(&'static str),
+    #[error("adding unique index to document type with documents error: {0}")]
+    AddingUniqueIndexToDocumentTypeWithDocuments(&'static str),
+    #[error("removing document type property error: {0}")]
+    RemovingDocumentTypeProperty(&'static str),
+    #[error("changing document type property type error: {0}")]
+    ChangingDocumentTypePropertyType(&'static str),
+    #[error("adding required property to document type with documents error: {0}")]
+    AddingRequiredPropertyToDocumentTypeWithDocuments(&'static str),
+    #[error("changing document type index error: {0}")]
+    ChangingDocumentTypeIndex(&'static str),
+    #[error("removing document type unique index error: {0}")]
+    RemovingDocumentTypeUniqueIndex(&'static str),
+    #[error("removing document type index error: {0}")]
+    RemovingDocumentTypeIndex(&'static str),
+    #[error("removing document type with documents error: {0}")]
+    RemovingDocumentTypeWithDocuments(&'static str),
 
     #[error("corrupted contract path error: {0}")]
     CorruptedContractPath(&'static str),
+    #[error("corrupted contract deletion error: {0}")]
+    CorruptedContractDeletion(&'static str),
     #[error("corrupted contract indexes error: {0}")]
     CorruptedContractIndexes(&'static str),
     #[error("corrupted document path error: {0}")]
@@ -46,6 +74,20 @@ pub enum DriveError {
     CorruptedDocumentNotItem(&'static str),
     #[error("corrupted identity not an item error: {0}")]
     CorruptedIdentityNotItem(&'static str),
+    #[error("corrupted identity public key hash error: {0}")]
+    CorruptedIdentityPublicKeyHash(&'static str),
+    #[error("corrupted identity balance error: {0}")]
+    CorruptedIdentityBalance(&'static str),
+    #[error("corrupted identity key not an item error: {0}")]
+    CorruptedIdentityKeyNotItem(&'static str),
+    #[error("corrupted identity revision error: {0}")]
+    CorruptedIdentityRevision(&'static str),
+    #[error("corrupted identity element error: {0}")]
+    CorruptedIdentityElement(&'static str),
+    #[error("corrupted spent asset lock outpoint error: {0}")]
+    CorruptedSpentAssetLockOutpoint(&'static str),
+    #[error("corrupted index backfill error: {0}")]
+    CorruptedIndexBackfill(&'static str),
 
     #[error("corrupted element flags error: {0}")]
     CorruptedElementFlags(&'static str),
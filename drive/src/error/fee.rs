@@ -32,6 +32,19 @@ pub enum FeeError {
     CorruptedMultiplierNotItem(&'static str),
     #[error("corrupted multiplier invalid item length error: {0}")]
     CorruptedMultiplierInvalidItemLength(&'static str),
+    #[error("invalid fee multiplier error: {0}")]
+    InvalidFeeMultiplier(&'static str),
+    #[error("unknown fee schedule error: {0}")]
+    UnknownFeeSchedule(&'static str),
+    #[error("corrupted protocol version not an item error: {0}")]
+    CorruptedProtocolVersionNotItem(&'static str),
+    #[error("corrupted protocol version invalid item length error: {0}")]
+    CorruptedProtocolVersionItemLength(&'static str),
+
+    #[error("corrupted current epoch index invalid item length error: {0}")]
+    CorruptedCurrentEpochIndexItemLength(&'static str),
+    #[error("corrupted current epoch index not an item error: {0}")]
+    CorruptedCurrentEpochIndexNotItem(&'static str),
 
     #[error("corrupted unpaid epoch index invalid item length error: {0}")]
     CorruptedUnpaidEpochIndexItemLength(&'static str),
@@ -14,4 +14,37 @@ pub enum IdentityError {
 
     #[error("identity serialization error: {0}")]
     IdentitySerialization(&'static str),
+
+    #[error("asset lock outpoint already spent: {0}")]
+    AssetLockOutpointAlreadySpent(&'static str),
+
+    #[error("identity not found: {0}")]
+    IdentityNotFound(&'static str),
+
+    #[error("identity insufficient balance: {0}")]
+    IdentityInsufficientBalance(&'static str),
+
+    #[error("critical balance overflow: {0}")]
+    CriticalBalanceOverflow(&'static str),
+
+    #[error("identity key already exists: {0}")]
+    IdentityKeyAlreadyExists(&'static str),
+
+    #[error("identity key hash already used: {0}")]
+    IdentityKeyHashAlreadyUsed(&'static str),
+
+    #[error("identity key not found: {0}")]
+    IdentityKeyNotFound(&'static str),
+
+    #[error("identity key already disabled: {0}")]
+    IdentityKeyAlreadyDisabled(&'static str),
+
+    #[error("identity revision mismatch: {0}")]
+    IdentityRevisionMismatch(&'static str),
+
+    #[error("invalid identity revision: {0}")]
+    InvalidIdentityRevision(&'static str),
+
+    #[error("identity id tombstoned: {0}")]
+    IdentityIdTombstoned(&'static str),
 }
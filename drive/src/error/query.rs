@@ -7,6 +7,8 @@ pub enum QueryError {
 
     #[error("contract not found error: {0}")]
     ContractNotFound(&'static str),
+    #[error("contract archived error: {0}")]
+    ContractArchived(&'static str),
     #[error("document type not found error: {0}")]
     DocumentTypeNotFound(&'static str),
 
@@ -25,8 +27,13 @@ pub enum QueryError {
     InvalidInClause(&'static str),
     #[error("invalid STARTSWITH clause error: {0}")]
     InvalidStartsWithClause(&'static str),
+    #[error("invalid OR clause error: {0}")]
+    InvalidOrClause(&'static str),
 
     // Where Condition Errors
+    #[error("invalid select error: {0}")]
+    InvalidSelect(&'static str),
+
     #[error("invalid where clause order error: {0}")]
     InvalidWhereClauseOrder(&'static str),
     #[error("invalid where clause components error: {0}")]
@@ -43,6 +50,8 @@ pub enum QueryError {
     InvalidContractId(&'static str),
     #[error("query invalid limit error: {0}")]
     InvalidLimit(&'static str),
+    #[error("query invalid offset error: {0}")]
+    InvalidOffset(&'static str),
     #[error("query invalid format for where clause error: {0}")]
     InvalidFormatWhereClause(&'static str),
 
@@ -53,6 +62,12 @@ pub enum QueryError {
     DuplicateStartConditions(&'static str),
     #[error("start document not found error: {0}")]
     StartDocumentNotFound(&'static str),
+    #[error("invalid start document error: {0}")]
+    InvalidStartDocument(&'static str),
+    #[error("invalid cursor error: {0}")]
+    InvalidCursor(&'static str),
+    #[error("invalid proof error: {0}")]
+    InvalidProof(&'static str),
 
     #[error("invalid document type error: {0}")]
     InvalidDocumentType(&'static str),
@@ -63,10 +78,17 @@ pub enum QueryError {
     QueryTooFarFromIndex(&'static str),
     #[error("query on document type with no indexes: {0}")]
     QueryOnDocumentTypeWithNoIndexes(&'static str),
+    #[error("index backfill in progress error: {0}")]
+    IndexBackfillInProgress(&'static str),
+    #[error("index backfill failed error: {0}")]
+    IndexBackfillFailed(&'static str),
 
     #[error("missing order by for range error: {0}")]
     MissingOrderByForRange(&'static str),
 
+    #[error("no index supports operator error: {0}")]
+    NoIndexSupportsOperator(&'static str),
+
     #[error("range operator not in final index error: {0}")]
     RangeOperatorNotInFinalIndex(&'static str),
     #[error("in operator not in final indexes error: {0}")]
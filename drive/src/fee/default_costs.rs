@@ -4,5 +4,4 @@ pub(crate) const STORAGE_LOAD_CREDIT_PER_BYTE: u64 = 10;
 pub(crate) const NON_STORAGE_LOAD_CREDIT_PER_BYTE: u64 = 10;
 pub(crate) const HASH_BYTE_COST: u64 = 10;
 pub(crate) const HASH_NODE_COST: u64 = 10;
-pub(crate) const QUERY_CREDIT_PER_BYTE: u64 = 10;
 pub(crate) const STORAGE_SEEK_COST: u64 = 100;
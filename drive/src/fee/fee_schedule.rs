@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+
+use enum_map::{enum_map, EnumMap};
+
+use crate::drive::defaults::PROTOCOL_VERSION;
+use crate::error::fee::FeeError;
+use crate::error::Error;
+use crate::fee::default_costs::{
+    HASH_BYTE_COST, HASH_NODE_COST, NON_STORAGE_LOAD_CREDIT_PER_BYTE,
+    STORAGE_DISK_USAGE_CREDIT_PER_BYTE, STORAGE_LOAD_CREDIT_PER_BYTE,
+    STORAGE_PROCESSING_CREDIT_PER_BYTE, STORAGE_SEEK_COST,
+};
+use crate::fee::op::BaseOp;
+
+/// Fee schedules keyed by the protocol version they were introduced in
+pub type FeeSchedules = BTreeMap<u32, FeeSchedule>;
+
+/// The credits charged for operations
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeSchedule {
+    pub storage_disk_usage_credit_per_byte: i64,
+    pub storage_processing_credit_per_byte: u64,
+    pub storage_load_credit_per_byte: u64,
+    pub non_storage_load_credit_per_byte: u64,
+    pub hash_byte_cost: u64,
+    pub hash_node_cost: u64,
+    pub storage_seek_cost: u64,
+    pub base_op_costs: EnumMap<BaseOp, u64>,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        FeeSchedule {
+            storage_disk_usage_credit_per_byte: STORAGE_DISK_USAGE_CREDIT_PER_BYTE,
+            storage_processing_credit_per_byte: STORAGE_PROCESSING_CREDIT_PER_BYTE,
+            storage_load_credit_per_byte: STORAGE_LOAD_CREDIT_PER_BYTE,
+            non_storage_load_credit_per_byte: NON_STORAGE_LOAD_CREDIT_PER_BYTE,
+            hash_byte_cost: HASH_BYTE_COST,
+            hash_node_cost: HASH_NODE_COST,
+            storage_seek_cost: STORAGE_SEEK_COST,
+            base_op_costs: enum_map! {
+                BaseOp::Stop => 0,
+                BaseOp::Add => 12,
+                BaseOp::Mul => 20,
+                BaseOp::Sub => 12,
+                BaseOp::Div => 20,
+                BaseOp::Sdiv => 20,
+                BaseOp::Mod => 20,
+                BaseOp::Smod => 20,
+                BaseOp::Addmod => 32,
+                BaseOp::Mulmod => 32,
+                BaseOp::Signextend => 20,
+                BaseOp::Lt => 12,
+                BaseOp::Gt => 12,
+                BaseOp::Slt => 12,
+                BaseOp::Sgt => 12,
+                BaseOp::Eq => 12,
+                BaseOp::Iszero => 12,
+                BaseOp::And => 12,
+                BaseOp::Or => 12,
+                BaseOp::Xor => 12,
+                BaseOp::Not => 12,
+                BaseOp::Byte => 12,
+            },
+        }
+    }
+}
+
+impl FeeSchedule {
+    /// Returns the schedule in use at the protocol version, that is the one of the
+    /// latest protocol version changing fees
+    pub fn for_protocol_version(
+        fee_schedules: &FeeSchedules,
+        protocol_version: u32,
+    ) -> Result<&FeeSchedule, Error> {
+        fee_schedules
+            .range(..=protocol_version)
+            .next_back()
+            .map(|(_, fee_schedule)| fee_schedule)
+            .ok_or(Error::Fee(FeeError::UnknownFeeSchedule(
+                "no fee schedule for the protocol version",
+            )))
+    }
+}
+
+pub fn default_fee_schedules() -> FeeSchedules {
+    BTreeMap::from([(PROTOCOL_VERSION, FeeSchedule::default())])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::fee::FeeError;
+    use crate::error::Error;
+    use crate::fee::fee_schedule::{default_fee_schedules, FeeSchedule};
+
+    #[test]
+    fn test_schedule_is_used_until_next_version() {
+        let mut fee_schedules = default_fee_schedules();
+
+        let new_fee_schedule = FeeSchedule {
+            storage_seek_cost: 200,
+            ..Default::default()
+        };
+
+        fee_schedules.insert(3, new_fee_schedule.clone());
+
+        let fee_schedule = FeeSchedule::for_protocol_version(&fee_schedules, 2)
+            .expect("expected to get a fee schedule");
+        assert_eq!(fee_schedule, &FeeSchedule::default());
+
+        let fee_schedule = FeeSchedule::for_protocol_version(&fee_schedules, 4)
+            .expect("expected to get a fee schedule");
+        assert_eq!(fee_schedule, &new_fee_schedule);
+
+        match FeeSchedule::for_protocol_version(&fee_schedules, 0) {
+            Ok(_) => assert!(false, "must be an error"),
+            Err(e) => match e {
+                Error::Fee(FeeError::UnknownFeeSchedule(_)) => assert!(true),
+                _ => assert!(false, "invalid error type"),
+            },
+        }
+    }
+}
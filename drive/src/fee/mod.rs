@@ -1,21 +1,49 @@
+use std::collections::BTreeMap;
+
 use enum_map::EnumMap;
+use rust_decimal::Decimal;
 
 use crate::error::fee::FeeError;
 use crate::error::Error;
+use crate::fee::fee_schedule::FeeSchedule;
+use crate::fee::op::DriveOperation::StorageRemovalOperation;
 use crate::fee::op::{BaseOp, DriveCost, DriveOperation};
+use crate::fee::result::{FeeBreakdown, FeeResult};
 
 pub mod default_costs;
+pub mod fee_schedule;
 pub mod op;
+pub mod result;
+
+/// Credits refunded to the storage fees paid in an epoch
+pub type EpochRefund = (u16, u64);
 
-pub fn calculate_fee(
+/// Returns the fees of the operations, the fee multiplier is only applied to the
+/// processing fee as storage fees are refunded per byte. Removed bytes are refunded with
+/// the fee schedule of the epoch they were paid in.
+pub fn calculate_fee<'a, F>(
     base_operations: Option<EnumMap<BaseOp, u64>>,
     drive_operations: Option<Vec<DriveOperation>>,
-) -> Result<(i64, u64), Error> {
+    fee_schedule: &FeeSchedule,
+    epoch_fee_schedule: F,
+    fee_multiplier: Decimal,
+    with_breakdown: bool,
+) -> Result<FeeResult, Error>
+where
+    F: Fn(u16) -> Result<&'a FeeSchedule, Error>,
+{
     let mut storage_cost = 0i64;
     let mut processing_cost = 0u64;
+    let mut removed_bytes_by_epoch: BTreeMap<u16, u64> = BTreeMap::new();
+    let mut breakdown = if with_breakdown {
+        Some(FeeBreakdown::default())
+    } else {
+        None
+    };
+
     if let Some(base_operations) = base_operations {
         for (base_op, count) in base_operations.iter() {
-            match base_op.cost().checked_mul(*count) {
+            match base_op.cost(fee_schedule).checked_mul(*count) {
                 None => return Err(Error::Fee(FeeError::Overflow("overflow error"))),
                 Some(cost) => match processing_cost.checked_add(cost) {
                     None => return Err(Error::Fee(FeeError::Overflow("overflow error"))),
@@ -26,19 +54,56 @@ pub fn calculate_fee(
     }
 
     if let Some(drive_operations) = drive_operations {
-        // println!("{:#?}", drive_operations);
-        for drive_operation in DriveOperation::consume_to_costs(drive_operations)? {
-            match processing_cost.checked_add(drive_operation.ephemeral_cost()?) {
+        for drive_operation in drive_operations {
+            if let StorageRemovalOperation(epoch, removed_bytes) = drive_operation {
+                let epoch_removed_bytes = removed_bytes_by_epoch.entry(epoch).or_default();
+                *epoch_removed_bytes = epoch_removed_bytes
+                    .checked_add(removed_bytes)
+                    .ok_or(Error::Fee(FeeError::Overflow("overflow error")))?;
+            }
+
+            let kind = drive_operation.kind();
+            let operation_cost = drive_operation.operation_cost()?;
+
+            match processing_cost.checked_add(operation_cost.ephemeral_cost(fee_schedule)?) {
                 None => return Err(Error::Fee(FeeError::Overflow("overflow error"))),
                 Some(value) => processing_cost = value,
             }
 
-            match storage_cost.checked_add(drive_operation.storage_cost()?) {
+            match storage_cost.checked_add(operation_cost.storage_cost(fee_schedule)?) {
                 None => return Err(Error::Fee(FeeError::Overflow("overflow error"))),
                 Some(value) => storage_cost = value,
             }
+
+            if let Some(breakdown) = breakdown.as_mut() {
+                breakdown[kind].add_operation_cost(&operation_cost);
+            }
         }
     }
 
-    Ok((storage_cost, processing_cost))
+    let processing_cost: u64 = Decimal::from(processing_cost)
+        .checked_mul(fee_multiplier)
+        .ok_or(Error::Fee(FeeError::Overflow("overflow error")))?
+        .floor()
+        .try_into()
+        .map_err(|_| Error::Fee(FeeError::Overflow("overflow error")))?;
+
+    let refunds_by_epoch = removed_bytes_by_epoch
+        .iter()
+        .map(|(epoch, removed_bytes)| {
+            let paid_fee_schedule = epoch_fee_schedule(*epoch)?;
+            removed_bytes
+                .checked_mul(paid_fee_schedule.storage_disk_usage_credit_per_byte as u64)
+                .map(|refund| (*epoch, refund))
+                .ok_or(Error::Fee(FeeError::Overflow("overflow error")))
+        })
+        .collect::<Result<Vec<EpochRefund>, Error>>()?;
+
+    Ok(FeeResult {
+        storage_fee: storage_cost,
+        processing_fee: processing_cost,
+        removed_bytes_by_epoch: removed_bytes_by_epoch.into_iter().collect(),
+        refunds_by_epoch,
+        breakdown,
+    })
 }
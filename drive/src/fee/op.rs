@@ -7,17 +7,14 @@ use crate::drive::flags::StorageFlags;
 use crate::error::drive::DriveError;
 use crate::error::fee::FeeError;
 use crate::error::Error;
-use crate::fee::default_costs::{
-    HASH_BYTE_COST, HASH_NODE_COST, NON_STORAGE_LOAD_CREDIT_PER_BYTE,
-    STORAGE_DISK_USAGE_CREDIT_PER_BYTE, STORAGE_LOAD_CREDIT_PER_BYTE,
-    STORAGE_PROCESSING_CREDIT_PER_BYTE, STORAGE_SEEK_COST,
-};
+use crate::fee::fee_schedule::FeeSchedule;
 use crate::fee::op::DriveOperation::{
     CalculatedCostOperation, ContractFetch, CostCalculationDeleteOperation,
     CostCalculationInsertOperation, CostCalculationQueryOperation, GroveOperation,
+    StorageRemovalOperation,
 };
 
-#[derive(Debug, Enum)]
+#[derive(Clone, Copy, Debug, Enum)]
 pub enum BaseOp {
     Stop,
     Add,
@@ -44,31 +41,8 @@ pub enum BaseOp {
 }
 
 impl BaseOp {
-    pub fn cost(&self) -> u64 {
-        match self {
-            BaseOp::Stop => 0,
-            BaseOp::Add => 12,
-            BaseOp::Mul => 20,
-            BaseOp::Sub => 12,
-            BaseOp::Div => 20,
-            BaseOp::Sdiv => 20,
-            BaseOp::Mod => 20,
-            BaseOp::Smod => 20,
-            BaseOp::Addmod => 32,
-            BaseOp::Mulmod => 32,
-            BaseOp::Signextend => 20,
-            BaseOp::Lt => 12,
-            BaseOp::Gt => 12,
-            BaseOp::Slt => 12,
-            BaseOp::Sgt => 12,
-            BaseOp::Eq => 12,
-            BaseOp::Iszero => 12,
-            BaseOp::And => 12,
-            BaseOp::Or => 12,
-            BaseOp::Xor => 12,
-            BaseOp::Not => 12,
-            BaseOp::Byte => 12,
-        }
+    pub fn cost(&self, fee_schedule: &FeeSchedule) -> u64 {
+        fee_schedule.base_op_costs[*self]
     }
 }
 
@@ -91,10 +65,6 @@ pub struct SizesOfQueryOperation {
     pub value_size: u32,
 }
 
-trait OperationCostConvert {
-    fn cost(&self) -> OperationCost;
-}
-
 impl SizesOfQueryOperation {
     pub fn for_key_check_in_path<'a: 'b, 'b, 'c, P>(key_len: usize, path: P) -> Self
     where
@@ -229,43 +199,12 @@ impl SizesOfDeleteOperation {
     }
 }
 
-impl OperationCostConvert for SizesOfInsertOperation {
-    fn cost(&self) -> OperationCost {
-        OperationCost {
-            seek_count: 0,
-            storage_written_bytes: 0,
-            storage_loaded_bytes: 0,
-            storage_freed_bytes: 0,
-            hash_byte_calls: 0,
-            hash_node_calls: 0,
-        }
-    }
-}
-
-impl OperationCostConvert for SizesOfQueryOperation {
-    fn cost(&self) -> OperationCost {
-        OperationCost {
-            seek_count: 0,
-            storage_written_bytes: 0,
-            storage_loaded_bytes: 0,
-            storage_freed_bytes: 0,
-            hash_byte_calls: 0,
-            hash_node_calls: 0,
-        }
-    }
-}
-
-impl OperationCostConvert for SizesOfDeleteOperation {
-    fn cost(&self) -> OperationCost {
-        OperationCost {
-            seek_count: 0,
-            storage_written_bytes: 0,
-            storage_loaded_bytes: 0,
-            storage_freed_bytes: 0,
-            hash_byte_calls: 0,
-            hash_node_calls: 0,
-        }
-    }
+/// The kinds of drive operations fees are broken down by
+#[derive(Clone, Copy, Debug, Enum, PartialEq)]
+pub enum DriveOperationKind {
+    CalculatedCost,
+    ContractFetch,
+    StorageRemoval,
 }
 
 #[derive(Debug)]
@@ -276,6 +215,8 @@ pub enum DriveOperation {
     CostCalculationInsertOperation(SizesOfInsertOperation),
     CostCalculationDeleteOperation(SizesOfDeleteOperation),
     CostCalculationQueryOperation(SizesOfQueryOperation),
+    /// Bytes removed from the state along with the epoch they were paid in
+    StorageRemovalOperation(u16, u64),
 }
 
 impl DriveOperation {
@@ -288,22 +229,33 @@ impl DriveOperation {
             .collect()
     }
 
+    pub fn kind(&self) -> DriveOperationKind {
+        match self {
+            GroveOperation(_) | CalculatedCostOperation(_) => DriveOperationKind::CalculatedCost,
+            // operations only known by their sizes are costed as grovedb operations
+            CostCalculationInsertOperation(_)
+            | CostCalculationDeleteOperation(_)
+            | CostCalculationQueryOperation(_) => DriveOperationKind::CalculatedCost,
+            ContractFetch => DriveOperationKind::ContractFetch,
+            StorageRemovalOperation(..) => DriveOperationKind::StorageRemoval,
+        }
+    }
+
     pub fn operation_cost(self) -> Result<OperationCost, Error> {
         match self {
             GroveOperation(_) => Err(Error::Drive(DriveError::CorruptedCodeExecution(
                 "grove operations must be executed, not directly transformed to costs",
             ))),
-            CostCalculationInsertOperation(worst_case_insert_operation) => {
-                Ok(worst_case_insert_operation.cost())
-            }
-            CostCalculationQueryOperation(worst_case_query_operation) => {
-                Ok(worst_case_query_operation.cost())
-            }
-            CostCalculationDeleteOperation(worst_case_delete_operation) => {
-                Ok(worst_case_delete_operation.cost())
+            CostCalculationInsertOperation(_)
+            | CostCalculationQueryOperation(_)
+            | CostCalculationDeleteOperation(_) => {
+                Err(Error::Drive(DriveError::CorruptedCodeExecution(
+                    "operations only known by their sizes must be costed as their worst case grovedb operations",
+                )))
             }
             CalculatedCostOperation(c) => Ok(c),
-            ContractFetch => Ok(OperationCost {
+            // the refund is calculated separately
+            ContractFetch | StorageRemovalOperation(..) => Ok(OperationCost {
                 seek_count: 0,
                 storage_written_bytes: 0,
                 storage_loaded_bytes: 0,
@@ -338,6 +290,15 @@ impl DriveOperation {
         DriveOperation::for_path_key_element(path, key, tree)
     }
 
+    /// The removal of an element, refunded to the epoch in its storage flags
+    pub fn for_removed_key_element(key: &[u8], element: &Element) -> Result<Self, Error> {
+        let storage_flags = StorageFlags::from_element_flags(element.get_flags().clone())?;
+        Ok(StorageRemovalOperation(
+            storage_flags.epoch,
+            (key.len() + element.serialized_byte_size()) as u64,
+        ))
+    }
+
     pub fn for_path_key_element(path: Vec<Vec<u8>>, key: Vec<u8>, element: Element) -> Self {
         GroveOperation(GroveDbOp::insert(path, key, element))
     }
@@ -382,8 +343,8 @@ impl DriveOperation {
 }
 
 pub trait DriveCost {
-    fn ephemeral_cost(&self) -> Result<u64, Error>;
-    fn storage_cost(&self) -> Result<i64, Error>;
+    fn ephemeral_cost(&self, fee_schedule: &FeeSchedule) -> Result<u64, Error>;
+    fn storage_cost(&self, fee_schedule: &FeeSchedule) -> Result<i64, Error>;
 }
 
 fn get_overflow_error(str: &'static str) -> Error {
@@ -391,7 +352,7 @@ fn get_overflow_error(str: &'static str) -> Error {
 }
 
 impl DriveCost for OperationCost {
-    fn ephemeral_cost(&self) -> Result<u64, Error> {
+    fn ephemeral_cost(&self, fee_schedule: &FeeSchedule) -> Result<u64, Error> {
         let OperationCost {
             seek_count,
             storage_written_bytes,
@@ -401,22 +362,22 @@ impl DriveCost for OperationCost {
             hash_node_calls,
         } = *self;
         let seek_cost = (seek_count as u64)
-            .checked_mul(STORAGE_SEEK_COST)
+            .checked_mul(fee_schedule.storage_seek_cost)
             .ok_or_else(|| get_overflow_error("seek cost overflow"))?;
         let storage_written_bytes_ephemeral_cost = (storage_written_bytes as u64)
-            .checked_mul(STORAGE_PROCESSING_CREDIT_PER_BYTE)
+            .checked_mul(fee_schedule.storage_processing_credit_per_byte)
             .ok_or_else(|| get_overflow_error("storage written bytes cost overflow"))?;
         let _storage_loaded_bytes_cost = (storage_loaded_bytes as u64)
-            .checked_mul(STORAGE_LOAD_CREDIT_PER_BYTE)
+            .checked_mul(fee_schedule.storage_load_credit_per_byte)
             .ok_or_else(|| get_overflow_error("storage loaded cost overflow"))?;
         let storage_loaded_bytes_cost = (storage_loaded_bytes as u64)
-            .checked_mul(NON_STORAGE_LOAD_CREDIT_PER_BYTE)
+            .checked_mul(fee_schedule.non_storage_load_credit_per_byte)
             .ok_or_else(|| get_overflow_error("loaded bytes cost overflow"))?;
         let hash_byte_cost = (hash_byte_calls as u64)
-            .checked_mul(HASH_BYTE_COST)
+            .checked_mul(fee_schedule.hash_byte_cost)
             .ok_or_else(|| get_overflow_error("hash byte cost overflow"))?;
         let hash_node_cost = (hash_node_calls as u64)
-            .checked_mul(HASH_NODE_COST)
+            .checked_mul(fee_schedule.hash_node_cost)
             .ok_or_else(|| get_overflow_error("hash node cost overflow"))?;
         let cost = seek_cost
             .checked_add(storage_written_bytes_ephemeral_cost)
@@ -432,13 +393,13 @@ impl DriveCost for OperationCost {
         cost
     }
 
-    fn storage_cost(&self) -> Result<i64, Error> {
+    fn storage_cost(&self, fee_schedule: &FeeSchedule) -> Result<i64, Error> {
         let OperationCost {
             storage_written_bytes,
             ..
         } = *self;
         let storage_written_bytes_disk_cost = (storage_written_bytes as i64)
-            .checked_mul(STORAGE_DISK_USAGE_CREDIT_PER_BYTE)
+            .checked_mul(fee_schedule.storage_disk_usage_credit_per_byte)
             .ok_or_else(|| get_overflow_error("storage written bytes cost overflow"));
         storage_written_bytes_disk_cost
     }
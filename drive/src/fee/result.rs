@@ -0,0 +1,55 @@
+use costs::OperationCost;
+use enum_map::EnumMap;
+
+use crate::fee::op::DriveOperationKind;
+use crate::fee::EpochRefund;
+
+/// The work done by operations of a kind
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OperationCostBreakdown {
+    pub seek_count: u64,
+    pub storage_loaded_bytes: u64,
+    pub storage_written_bytes: u64,
+    pub storage_freed_bytes: u64,
+    pub hash_byte_calls: u64,
+    pub hash_node_calls: u64,
+}
+
+impl OperationCostBreakdown {
+    pub(crate) fn add_operation_cost(&mut self, operation_cost: &OperationCost) {
+        self.seek_count = self
+            .seek_count
+            .saturating_add(operation_cost.seek_count as u64);
+        self.storage_loaded_bytes = self
+            .storage_loaded_bytes
+            .saturating_add(operation_cost.storage_loaded_bytes as u64);
+        self.storage_written_bytes = self
+            .storage_written_bytes
+            .saturating_add(operation_cost.storage_written_bytes as u64);
+        self.storage_freed_bytes = self
+            .storage_freed_bytes
+            .saturating_add(operation_cost.storage_freed_bytes as u64);
+        self.hash_byte_calls = self
+            .hash_byte_calls
+            .saturating_add(operation_cost.hash_byte_calls as u64);
+        self.hash_node_calls = self
+            .hash_node_calls
+            .saturating_add(operation_cost.hash_node_calls as u64);
+    }
+}
+
+pub type FeeBreakdown = EnumMap<DriveOperationKind, OperationCostBreakdown>;
+
+/// The fees of a state change
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeeResult {
+    pub storage_fee: i64,
+    pub processing_fee: u64,
+    /// Bytes removed from the state by the epoch they were paid in
+    pub removed_bytes_by_epoch: Vec<(u16, u64)>,
+    /// Credits refunded for the removed bytes by the epoch they were paid in
+    pub refunds_by_epoch: Vec<EpochRefund>,
+    /// Work done by operation kind, only set when the call asked for it with
+    /// `with_fee_breakdown`
+    pub breakdown: Option<FeeBreakdown>,
+}
@@ -4,4 +4,5 @@ pub const KEY_START_TIME: &[u8; 1] = b"t";
 pub const KEY_START_BLOCK_HEIGHT: &[u8; 1] = b"c";
 pub const KEY_PROPOSERS: &[u8; 1] = b"m";
 pub const KEY_FEE_MULTIPLIER: &[u8; 1] = b"x";
+pub const KEY_PROTOCOL_VERSION: &[u8; 1] = b"v";
 pub(crate) const EPOCH_STORAGE_OFFSET: u16 = 256;
@@ -3,8 +3,8 @@ use crate::drive::fee_pools::pools_vec_path;
 use crate::drive::Drive;
 use crate::error::Error;
 use crate::fee_pools::epochs::epoch_key_constants::{
-    KEY_FEE_MULTIPLIER, KEY_POOL_PROCESSING_FEES, KEY_POOL_STORAGE_FEES, KEY_START_BLOCK_HEIGHT,
-    KEY_START_TIME,
+    KEY_FEE_MULTIPLIER, KEY_POOL_PROCESSING_FEES, KEY_POOL_STORAGE_FEES, KEY_PROTOCOL_VERSION,
+    KEY_START_BLOCK_HEIGHT, KEY_START_TIME,
 };
 use crate::fee_pools::epochs::{epoch_key_constants, Epoch};
 use grovedb::batch::Op::Insert;
@@ -95,6 +95,17 @@ impl Epoch {
         }
     }
 
+    /// Storage paid in the epoch is refunded with the fee schedule of this protocol version
+    pub fn update_protocol_version_operation(&self, protocol_version: u32) -> GroveDbOp {
+        GroveDbOp {
+            path: self.get_vec_path(),
+            key: KEY_PROTOCOL_VERSION.to_vec(),
+            op: Insert {
+                element: Element::Item(protocol_version.to_be_bytes().to_vec(), None),
+            },
+        }
+    }
+
     pub fn update_processing_credits_for_distribution_operation(
         &self,
         processing_fee: u64,
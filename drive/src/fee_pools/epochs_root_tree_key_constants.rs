@@ -1,2 +1,4 @@
 pub const KEY_STORAGE_FEE_POOL: &[u8; 1] = b"s";
 pub const KEY_UNPAID_EPOCH_INDEX: &[u8; 1] = b"u";
+pub const KEY_FEE_MULTIPLIER: &[u8; 1] = b"m";
+pub const KEY_CURRENT_EPOCH_INDEX: &[u8; 1] = b"c";
@@ -3,7 +3,7 @@ use crate::drive::fee_pools::epochs::constants::{GENESIS_EPOCH_INDEX, PERPETUAL_
 use crate::drive::fee_pools::pools_vec_path;
 use crate::fee_pools::epochs::Epoch;
 use crate::fee_pools::epochs_root_tree_key_constants::{
-    KEY_STORAGE_FEE_POOL, KEY_UNPAID_EPOCH_INDEX,
+    KEY_CURRENT_EPOCH_INDEX, KEY_FEE_MULTIPLIER, KEY_STORAGE_FEE_POOL, KEY_UNPAID_EPOCH_INDEX,
 };
 use grovedb::batch::GroveDbOp;
 use grovedb::batch::Op::Insert;
@@ -12,6 +12,9 @@ use grovedb::Element;
 pub mod epochs;
 pub mod epochs_root_tree_key_constants;
 
+/// The fee multiplier used until governance sets another one
+pub const DEFAULT_FEE_MULTIPLIER: f64 = 2.0;
+
 pub fn add_create_fee_pool_trees_operations(batch: &mut GroveDbOpBatch) {
     // Init storage credit pool
     batch.push(update_storage_fee_distribution_pool_operation(0));
@@ -19,6 +22,9 @@ pub fn add_create_fee_pool_trees_operations(batch: &mut GroveDbOpBatch) {
     // Init next epoch to pay
     batch.push(update_unpaid_epoch_index_operation(GENESIS_EPOCH_INDEX));
 
+    // Init fee multiplier
+    batch.push(update_fee_multiplier_operation(DEFAULT_FEE_MULTIPLIER));
+
     // We need to insert 50 years worth of epochs,
     // with 20 epochs per year that's 1000 epochs
     for i in GENESIS_EPOCH_INDEX..PERPETUAL_STORAGE_EPOCHS {
@@ -37,6 +43,27 @@ pub fn update_storage_fee_distribution_pool_operation(storage_fee: u64) -> Grove
     }
 }
 
+pub fn update_fee_multiplier_operation(multiplier: f64) -> GroveDbOp {
+    GroveDbOp {
+        path: pools_vec_path(),
+        key: KEY_FEE_MULTIPLIER.to_vec(),
+        op: Insert {
+            element: Element::new_item(multiplier.to_be_bytes().to_vec()),
+        },
+    }
+}
+
+/// Fees are charged with the multiplier persisted in the current epoch
+pub fn update_current_epoch_index_operation(epoch_index: u16) -> GroveDbOp {
+    GroveDbOp {
+        path: pools_vec_path(),
+        key: KEY_CURRENT_EPOCH_INDEX.to_vec(),
+        op: Insert {
+            element: Element::new_item(epoch_index.to_be_bytes().to_vec()),
+        },
+    }
+}
+
 pub fn update_unpaid_epoch_index_operation(epoch_index: u16) -> GroveDbOp {
     GroveDbOp {
         path: pools_vec_path(),
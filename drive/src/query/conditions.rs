@@ -6,14 +6,16 @@ use sqlparser::ast;
 
 use WhereOperator::{
     Between, BetweenExcludeBounds, BetweenExcludeLeft, BetweenExcludeRight, Equal, GreaterThan,
-    GreaterThanOrEquals, In, LessThan, LessThanOrEquals, StartsWith,
+    GreaterThanOrEquals, In, LessThan, LessThanOrEquals, NotEqual, NotIn, StartsWith,
 };
 
-use crate::contract::{document::Document, DocumentType};
+use crate::contract::DocumentType;
 use crate::error::query::QueryError;
 use crate::error::Error;
+use crate::query::cursor::IndexKeyPath;
+use crate::query::defaults;
 
-fn sql_value_to_cbor(sql_value: ast::Value) -> Option<Value> {
+pub(crate) fn sql_value_to_cbor(sql_value: ast::Value) -> Option<Value> {
     match sql_value {
         ast::Value::Boolean(bool) => Some(Value::Bool(bool)),
         ast::Value::Number(num, _) => {
@@ -49,6 +51,10 @@ pub enum WhereOperator {
     BetweenExcludeRight,
     In,
     StartsWith,
+    NotEqual,
+    /// Planned as the ranges around the values, so at most `MAX_OR_CLAUSES - 1`
+    /// values are allowed
+    NotIn,
 }
 
 impl WhereOperator {
@@ -65,6 +71,8 @@ impl WhereOperator {
             BetweenExcludeRight => false,
             In => false,
             StartsWith => false,
+            NotEqual => true,
+            NotIn => false,
         }
     }
 
@@ -93,6 +101,10 @@ impl WhereOperator {
             StartsWith => Err(Error::Query(QueryError::InvalidWhereClauseOrder(
                 "Startswith clause order invalid",
             ))),
+            NotEqual => Ok(NotEqual),
+            NotIn => Err(Error::Query(QueryError::InvalidWhereClauseOrder(
+                "Not in clause order invalid",
+            ))),
         }
     }
 }
@@ -102,9 +114,8 @@ impl WhereOperator {
         match self {
             Equal => false,
             GreaterThan | GreaterThanOrEquals | LessThan | LessThanOrEquals | Between
-            | BetweenExcludeBounds | BetweenExcludeLeft | BetweenExcludeRight | In | StartsWith => {
-                true
-            }
+            | BetweenExcludeBounds | BetweenExcludeLeft | BetweenExcludeRight | In | StartsWith
+            | NotEqual | NotIn => true,
         }
     }
 
@@ -130,6 +141,8 @@ impl WhereOperator {
             | "between_exclude_right" => Some(BetweenExcludeRight),
             "In" | "in" => Some(In),
             "StartsWith" | "startsWith" | "startswith" | "starts_with" => Some(StartsWith),
+            "!=" | "<>" => Some(NotEqual),
+            "NotIn" | "notIn" | "notin" | "not_in" => Some(NotIn),
             &_ => None,
         }
     }
@@ -142,6 +155,7 @@ impl WhereOperator {
             ast::BinaryOperator::Lt => Some(WhereOperator::LessThan),
             ast::BinaryOperator::LtEq => Some(WhereOperator::LessThanOrEquals),
             ast::BinaryOperator::Like => Some(WhereOperator::StartsWith),
+            ast::BinaryOperator::NotEq => Some(WhereOperator::NotEqual),
             _ => None,
         }
     }
@@ -381,6 +395,7 @@ impl<'a> WhereClause {
                 BetweenExcludeBounds => false,
                 BetweenExcludeRight => false,
                 BetweenExcludeLeft => false,
+                NotEqual | NotIn => false,
             })
             .collect();
 
@@ -398,6 +413,7 @@ impl<'a> WhereClause {
                 BetweenExcludeBounds => true,
                 BetweenExcludeRight => true,
                 BetweenExcludeLeft => true,
+                NotEqual | NotIn => false,
             })
             .collect();
 
@@ -531,24 +547,24 @@ impl<'a> WhereClause {
         Ok((left_key, right_key))
     }
 
-    // The start at document fields are:
-    // document: The Document that we should start at
+    // The start at key path fields are:
+    // key_path: The index key path of the document that we should start at
     // included: whether we should start at or after this document
     // left_to_right: should we be going left to right or right to left?
     pub(crate) fn to_path_query(
         &self,
         document_type: &DocumentType,
-        start_at_document: &Option<(Document, bool)>,
+        start_at_key_path: &Option<(IndexKeyPath, bool)>,
         left_to_right: bool,
     ) -> Result<Query, Error> {
-        // If there is a start_at_document, we need to get the value that it has for the
+        // If there is a start at key path, we need to get the key that it has for the
         // current field.
-        let starts_at_key_option = match start_at_document {
+        let starts_at_key_option = match start_at_key_path {
             None => None,
-            Some((document, included)) => {
+            Some((key_path, included)) => {
                 // if the key doesn't exist then we should ignore the starts at key
-                document
-                    .get_raw_for_document_type(self.field.as_str(), document_type, None)?
+                key_path
+                    .key_for_property(self.field.as_str())
                     .map(|raw_value_option| (raw_value_option, *included))
             }
         };
@@ -867,10 +883,176 @@ impl<'a> WhereClause {
                     }
                 }
             }
+            NotEqual | NotIn => {
+                return Err(Error::Query(QueryError::NoIndexSupportsOperator(
+                    "negated clauses must be planned before building the path query",
+                )))
+            }
         }
         Ok(query)
     }
 
+    /// Not equal and not in clauses are replaced by the ranges around their values,
+    /// each range becoming its own conjunction
+    pub(crate) fn expand_negated_clauses(
+        disjunctive_where_clauses: Vec<Vec<WhereClause>>,
+    ) -> Result<Vec<Vec<WhereClause>>, Error> {
+        let mut expanded_where_clauses: Vec<Vec<WhereClause>> = vec![];
+
+        for where_clauses in disjunctive_where_clauses {
+            let mut conjunctions: Vec<Vec<WhereClause>> = vec![vec![]];
+
+            for where_clause in where_clauses {
+                let alternatives = match where_clause.operator {
+                    NotEqual => where_clause.ranges_around_values(vec![&where_clause.value])?,
+                    NotIn => {
+                        let in_values = where_clause.in_values()?;
+                        if in_values.len() >= defaults::MAX_OR_CLAUSES {
+                            return Err(Error::Query(QueryError::InvalidInClause(
+                                "not in clause can have at most 9 values",
+                            )));
+                        }
+                        let mut value_clauses = in_values
+                            .iter()
+                            .map(|value| WhereClause {
+                                field: where_clause.field.clone(),
+                                operator: Equal,
+                                value: value.clone(),
+                            })
+                            .collect::<Vec<WhereClause>>();
+                        let mut sort_error = None;
+                        value_clauses.sort_by(|left, right| {
+                            match (left.less_than(right, false), right.less_than(left, false)) {
+                                (Ok(true), _) => std::cmp::Ordering::Less,
+                                (Ok(false), Ok(true)) => std::cmp::Ordering::Greater,
+                                (Ok(false), Ok(false)) => std::cmp::Ordering::Equal,
+                                (Err(e), _) | (_, Err(e)) => {
+                                    sort_error.get_or_insert(e);
+                                    std::cmp::Ordering::Equal
+                                }
+                            }
+                        });
+                        if let Some(e) = sort_error {
+                            return Err(e);
+                        }
+                        where_clause.ranges_around_values(
+                            value_clauses.iter().map(|clause| &clause.value).collect(),
+                        )?
+                    }
+                    _ => vec![where_clause],
+                };
+
+                conjunctions = conjunctions
+                    .iter()
+                    .flat_map(|conjunction| {
+                        alternatives.iter().map(move |alternative| {
+                            let mut conjunction = conjunction.clone();
+                            conjunction.push(alternative.clone());
+                            conjunction
+                        })
+                    })
+                    .collect();
+
+                if conjunctions.len() > defaults::MAX_OR_CLAUSES {
+                    return Err(Error::Query(QueryError::InvalidOrClause(
+                        "query has too many or clauses once negations are expanded",
+                    )));
+                }
+            }
+
+            expanded_where_clauses.extend(conjunctions);
+        }
+
+        Ok(expanded_where_clauses)
+    }
+
+    /// The ranges below, between and above the sorted values
+    fn ranges_around_values(&self, sorted_values: Vec<&Value>) -> Result<Vec<WhereClause>, Error> {
+        let (first_value, last_value) = match (sorted_values.first(), sorted_values.last()) {
+            (Some(first_value), Some(last_value)) => (*first_value, *last_value),
+            _ => {
+                return Err(Error::Query(QueryError::InvalidInClause(
+                    "not in clause must have at least 1 value",
+                )))
+            }
+        };
+
+        let mut ranges = vec![WhereClause {
+            field: self.field.clone(),
+            operator: LessThan,
+            value: first_value.clone(),
+        }];
+
+        for window in sorted_values.windows(2) {
+            ranges.push(WhereClause {
+                field: self.field.clone(),
+                operator: BetweenExcludeBounds,
+                value: Value::Array(vec![window[0].clone(), window[1].clone()]),
+            });
+        }
+
+        ranges.push(WhereClause {
+            field: self.field.clone(),
+            operator: GreaterThan,
+            value: last_value.clone(),
+        });
+
+        Ok(ranges)
+    }
+
+    /// Puts the where expression into its disjunctive normal form, each returned
+    /// vector of clauses is a conjunction
+    pub(crate) fn build_disjunctive_where_clauses_from_operations(
+        binary_operation: &ast::Expr,
+    ) -> Result<Vec<Vec<WhereClause>>, Error> {
+        let disjunctive_where_clauses = match binary_operation {
+            ast::Expr::Nested(expr) => Self::build_disjunctive_where_clauses_from_operations(expr)?,
+            ast::Expr::BinaryOp {
+                left,
+                op: ast::BinaryOperator::Or,
+                right,
+            } => {
+                let mut disjunctive_where_clauses =
+                    Self::build_disjunctive_where_clauses_from_operations(left)?;
+                disjunctive_where_clauses.extend(
+                    Self::build_disjunctive_where_clauses_from_operations(right)?,
+                );
+                disjunctive_where_clauses
+            }
+            ast::Expr::BinaryOp {
+                left,
+                op: ast::BinaryOperator::And,
+                right,
+            } => {
+                let left_where_clauses =
+                    Self::build_disjunctive_where_clauses_from_operations(left)?;
+                let right_where_clauses =
+                    Self::build_disjunctive_where_clauses_from_operations(right)?;
+                left_where_clauses
+                    .iter()
+                    .flat_map(|left_clauses| {
+                        right_where_clauses.iter().map(move |right_clauses| {
+                            [left_clauses.clone(), right_clauses.clone()].concat()
+                        })
+                    })
+                    .collect()
+            }
+            _ => {
+                let mut where_clauses = vec![];
+                Self::build_where_clauses_from_operations(binary_operation, &mut where_clauses)?;
+                vec![where_clauses]
+            }
+        };
+
+        if disjunctive_where_clauses.len() > defaults::MAX_OR_CLAUSES {
+            return Err(Error::Query(QueryError::InvalidOrClause(
+                "query has too many or clauses",
+            )));
+        }
+
+        Ok(disjunctive_where_clauses)
+    }
+
     pub(crate) fn build_where_clauses_from_operations(
         binary_operation: &ast::Expr,
         where_clauses: &mut Vec<WhereClause>,
@@ -881,11 +1063,7 @@ impl<'a> WhereClause {
                 list,
                 negated,
             } => {
-                if *negated {
-                    return Err(Error::Query(QueryError::Unsupported(
-                        "Invalid query: negated in clause not supported",
-                    )));
-                }
+                let operator = if *negated { NotIn } else { In };
 
                 let field_name = if let ast::Expr::Identifier(ident) = &**expr {
                     ident.value.clone()
@@ -913,7 +1091,7 @@ impl<'a> WhereClause {
 
                 where_clauses.push(WhereClause {
                     field: field_name,
-                    operator: WhereOperator::In,
+                    operator,
                     value: Value::Array(in_values),
                 });
 
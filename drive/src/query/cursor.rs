@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+
+use ciborium::value::Value;
+
+use crate::contract::document::Document;
+use crate::contract::DocumentType;
+use crate::error::query::QueryError;
+use crate::error::Error;
+
+/// The keys of a document in the index a query is made on, they are all that is
+/// needed to continue the query from that document
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexKeyPath {
+    /// The keys of the document for the properties of the index, a property the
+    /// document doesn't have has no key
+    pub property_keys: BTreeMap<String, Vec<u8>>,
+    /// The id of the document, the last key of the path
+    pub id: [u8; 32],
+}
+
+impl IndexKeyPath {
+    pub fn for_document<'p>(
+        document: &Document,
+        document_type: &DocumentType,
+        properties: impl IntoIterator<Item = &'p str>,
+    ) -> Result<Self, Error> {
+        let mut property_keys = BTreeMap::new();
+        for property in properties {
+            if let Some(key) = document.get_raw_for_document_type(property, document_type, None)? {
+                property_keys.insert(property.to_string(), key);
+            }
+        }
+        Ok(IndexKeyPath {
+            property_keys,
+            id: document.id,
+        })
+    }
+
+    /// The key of the document for the property, `$id` is the id of the document
+    pub fn key_for_property(&self, property: &str) -> Option<Vec<u8>> {
+        if property == "$id" {
+            Some(self.id.to_vec())
+        } else {
+            self.property_keys.get(property).cloned()
+        }
+    }
+}
+
+/// An opaque continuation token pointing after the last document of a page.
+/// It keeps the index key path of that document, so the query can go on without
+/// fetching the document, even if it was deleted since.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryCursor {
+    /// The contract of the queried document type
+    pub contract_id: [u8; 32],
+    /// The queried document type
+    pub document_type_name: String,
+    /// The properties of the index the query is made on, empty for the primary key
+    pub index_properties: Vec<String>,
+    /// The index key path of the last document of the page
+    pub index_key_path: IndexKeyPath,
+    /// The direction of the query the cursor was made for
+    pub ascending: bool,
+}
+
+impl QueryCursor {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let cursor = BTreeMap::from([
+            ("ascending".to_string(), Value::Bool(self.ascending)),
+            (
+                "contractId".to_string(),
+                Value::Bytes(self.contract_id.to_vec()),
+            ),
+            (
+                "documentType".to_string(),
+                Value::Text(self.document_type_name.clone()),
+            ),
+            (
+                "id".to_string(),
+                Value::Bytes(self.index_key_path.id.to_vec()),
+            ),
+            (
+                "indexProperties".to_string(),
+                Value::Array(
+                    self.index_properties
+                        .iter()
+                        .map(|name| Value::Text(name.clone()))
+                        .collect(),
+                ),
+            ),
+            (
+                "propertyKeys".to_string(),
+                Value::Map(
+                    self.index_key_path
+                        .property_keys
+                        .iter()
+                        .map(|(name, key)| (Value::Text(name.clone()), Value::Bytes(key.clone())))
+                        .collect(),
+                ),
+            ),
+        ]);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        ciborium::ser::into_writer(&cursor, &mut buffer).map_err(|_| {
+            Error::Query(QueryError::InvalidCursor("unable to serialize the cursor"))
+        })?;
+        Ok(buffer)
+    }
+
+    pub fn from_bytes(cursor_bytes: &[u8]) -> Result<Self, Error> {
+        let mut cursor: BTreeMap<String, Value> = ciborium::de::from_reader(cursor_bytes)
+            .map_err(|_| Error::Query(QueryError::InvalidCursor("unable to decode the cursor")))?;
+
+        let ascending = match cursor.remove("ascending") {
+            Some(Value::Bool(ascending)) => ascending,
+            _ => {
+                return Err(Error::Query(QueryError::InvalidCursor(
+                    "cursor direction must be a boolean",
+                )))
+            }
+        };
+
+        let contract_id: [u8; 32] = match cursor.remove("contractId") {
+            Some(Value::Bytes(contract_id)) => contract_id.try_into().map_err(|_| {
+                Error::Query(QueryError::InvalidCursor(
+                    "cursor contract id must be 32 bytes",
+                ))
+            })?,
+            _ => {
+                return Err(Error::Query(QueryError::InvalidCursor(
+                    "cursor contract id must be bytes",
+                )))
+            }
+        };
+
+        let document_type_name = match cursor.remove("documentType") {
+            Some(Value::Text(document_type_name)) => document_type_name,
+            _ => {
+                return Err(Error::Query(QueryError::InvalidCursor(
+                    "cursor document type must be text",
+                )))
+            }
+        };
+
+        let id: [u8; 32] = match cursor.remove("id") {
+            Some(Value::Bytes(id)) => id.try_into().map_err(|_| {
+                Error::Query(QueryError::InvalidCursor("cursor id must be 32 bytes"))
+            })?,
+            _ => {
+                return Err(Error::Query(QueryError::InvalidCursor(
+                    "cursor id must be bytes",
+                )))
+            }
+        };
+
+        let index_properties = match cursor.remove("indexProperties") {
+            Some(Value::Array(names)) => names
+                .into_iter()
+                .map(|name| match name {
+                    Value::Text(name) => Ok(name),
+                    _ => Err(Error::Query(QueryError::InvalidCursor(
+                        "cursor index properties must be text",
+                    ))),
+                })
+                .collect::<Result<Vec<String>, Error>>()?,
+            _ => {
+                return Err(Error::Query(QueryError::InvalidCursor(
+                    "cursor index properties must be an array",
+                )))
+            }
+        };
+
+        let property_keys = match cursor.remove("propertyKeys") {
+            Some(Value::Map(property_keys)) => property_keys
+                .into_iter()
+                .map(|property_key| match property_key {
+                    (Value::Text(name), Value::Bytes(key)) => Ok((name, key)),
+                    _ => Err(Error::Query(QueryError::InvalidCursor(
+                        "cursor property keys must be bytes by property name",
+                    ))),
+                })
+                .collect::<Result<BTreeMap<String, Vec<u8>>, Error>>()?,
+            _ => {
+                return Err(Error::Query(QueryError::InvalidCursor(
+                    "cursor property keys must be a map",
+                )))
+            }
+        };
+
+        if property_keys
+            .keys()
+            .any(|name| !index_properties.contains(name))
+        {
+            return Err(Error::Query(QueryError::InvalidCursor(
+                "cursor property keys must be for the index properties",
+            )));
+        }
+
+        if !cursor.is_empty() {
+            return Err(Error::Query(QueryError::InvalidCursor(
+                "cursor has unknown fields",
+            )));
+        }
+
+        Ok(QueryCursor {
+            contract_id,
+            document_type_name,
+            index_properties,
+            index_key_path: IndexKeyPath { property_keys, id },
+            ascending,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::query::cursor::{IndexKeyPath, QueryCursor};
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = QueryCursor {
+            contract_id: [3; 32],
+            document_type_name: "person".to_string(),
+            index_properties: vec!["firstName".to_string(), "lastName".to_string()],
+            index_key_path: IndexKeyPath {
+                property_keys: BTreeMap::from([("firstName".to_string(), b"Kevina".to_vec())]),
+                id: [1; 32],
+            },
+            ascending: false,
+        };
+
+        let cursor_bytes = cursor.to_bytes().expect("expected to serialize the cursor");
+
+        let decoded_cursor =
+            QueryCursor::from_bytes(cursor_bytes.as_slice()).expect("expected to decode cursor");
+
+        assert_eq!(cursor, decoded_cursor);
+
+        QueryCursor::from_bytes(&cursor_bytes[1..]).expect_err("truncated cursor is invalid");
+
+        // keys of properties outside of the index can't be in the path
+        let forged_cursor = QueryCursor {
+            index_properties: vec!["lastName".to_string()],
+            ..cursor
+        };
+        let forged_cursor_bytes = forged_cursor
+            .to_bytes()
+            .expect("expected to serialize the cursor");
+        QueryCursor::from_bytes(forged_cursor_bytes.as_slice())
+            .expect_err("the key path must be in the index");
+    }
+}
@@ -1,3 +1,6 @@
 pub(crate) const DEFAULT_QUERY_LIMIT: u16 = 100;
 pub(crate) const MAX_QUERY_LIMIT: u16 = 100;
+pub(crate) const MAX_QUERY_OFFSET: u16 = 1000;
 pub(crate) const MAX_INDEX_DIFFERENCE: u16 = 2;
+pub(crate) const MAX_OR_CLAUSES: usize = 10;
+pub(crate) const MAX_COUNT_LIMIT: u16 = 10000;
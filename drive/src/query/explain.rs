@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+
+use costs::OperationCost;
+use grovedb::PathQuery;
+use rust_decimal::Decimal;
+
+use crate::contract::{DocumentType, Index};
+use crate::error::query::QueryError;
+use crate::error::Error;
+use crate::fee::calculate_fee;
+use crate::fee::fee_schedule::FeeSchedule;
+use crate::fee::op::DriveOperation::CalculatedCostOperation;
+use crate::query::{defaults, DriveQuery, IndexKeyPath, QuerySelect};
+
+/// Why an index of the document type was not used for a query
+#[derive(Clone, Debug, PartialEq)]
+pub enum IndexRejectionReason {
+    /// The index can't serve the where clauses and order by of the query
+    NotMatching,
+    /// The index has too many properties the query doesn't use
+    TooFarFromQuery(u16),
+    /// Another index is closer to the query
+    NotBestMatch(u16),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RejectedIndex<'a> {
+    pub index: &'a Index,
+    pub reason: IndexRejectionReason,
+}
+
+/// How a query would be executed. There is no index for primary key queries,
+/// and neither an index nor a path query if no index can serve the query.
+#[derive(Clone, Debug)]
+pub struct QueryExplanation<'a> {
+    pub index: Option<&'a Index>,
+    pub path_query: Option<PathQuery>,
+    /// The processing fee of the query with every returned document at its max size
+    pub worst_case_processing_fee: u64,
+    pub rejected_indexes: Vec<RejectedIndex<'a>>,
+}
+
+impl<'a> DriveQuery<'a> {
+    /// Explains the plan of the query without touching the state. The start document
+    /// is only known with a cursor, so non primary key queries with startAt or
+    /// startAfter can't be explained.
+    /// The fee schedule and multiplier are the ones of `Drive::get_current_epoch_fees`.
+    pub fn explain(
+        &self,
+        fee_schedule: &FeeSchedule,
+        fee_multiplier: Decimal,
+    ) -> Result<QueryExplanation<'a>, Error> {
+        if self.is_disjunctive() {
+            return Err(Error::Query(QueryError::Unsupported(
+                "queries with or clauses must be explained for each conjunction",
+            )));
+        }
+
+        let (index, rejected_indexes) = if self.is_for_primary_key() {
+            (None, vec![])
+        } else {
+            let document_type: &'a DocumentType = self.document_type;
+            let (fields, in_field, order_by_keys) = self.index_selection_fields();
+            // the best index is taken from the document type to keep its lifetime
+            let best_index = self.find_best_index().ok().and_then(|index| {
+                document_type
+                    .indices
+                    .iter()
+                    .find(|document_type_index| *document_type_index == index)
+            });
+
+            let rejected_indexes = document_type
+                .indices
+                .iter()
+                .filter(|index| Some(*index) != best_index)
+                .map(|index| {
+                    let reason = match index.matches(
+                        fields.as_slice(),
+                        in_field,
+                        order_by_keys.as_slice(),
+                    ) {
+                        None => IndexRejectionReason::NotMatching,
+                        Some(difference) if difference > defaults::MAX_INDEX_DIFFERENCE => {
+                            IndexRejectionReason::TooFarFromQuery(difference)
+                        }
+                        Some(difference) => IndexRejectionReason::NotBestMatch(difference),
+                    };
+                    RejectedIndex { index, reason }
+                })
+                .collect();
+
+            (best_index, rejected_indexes)
+        };
+
+        if index.is_none() && !self.is_for_primary_key() {
+            return Ok(QueryExplanation {
+                index,
+                path_query: None,
+                worst_case_processing_fee: 0,
+                rejected_indexes,
+            });
+        }
+
+        let starts_at_key_path = match (&self.start_cursor, &self.start_at) {
+            (Some(cursor), _) => Some((cursor.index_key_path.clone(), self.start_at_included)),
+            (None, None) => None,
+            // the id is all a primary key query needs from the start document
+            (None, Some(starts_at)) if self.is_for_primary_key() => Some((
+                IndexKeyPath {
+                    property_keys: BTreeMap::new(),
+                    id: starts_at.as_slice().try_into().map_err(|_| {
+                        Error::Query(QueryError::InvalidStartDocument(
+                            "startAt id must be 32 bytes",
+                        ))
+                    })?,
+                },
+                self.start_at_included,
+            )),
+            (None, Some(_)) => {
+                return Err(Error::Query(QueryError::Unsupported(
+                    "the plan depends on the startAt or startAfter document, use a cursor to explain it",
+                )))
+            }
+        };
+        let path_query = self.construct_path_query(starts_at_key_path)?;
+
+        let path_size: u32 = path_query.path.iter().map(|key| key.len() as u32).sum();
+        let key_size: u32 = path_query
+            .query
+            .query
+            .items
+            .iter()
+            .map(|query_item| query_item.processing_footprint())
+            .sum();
+        let value_size = match self.select {
+            QuerySelect::Documents => (self.limit as u32) * (self.document_type.max_size() as u32),
+            QuerySelect::Count => 0,
+        };
+
+        // size estimates are not charged, so the worst case is charged as already calculated
+        let worst_case_processing_fee = calculate_fee(
+            None,
+            Some(vec![CalculatedCostOperation(OperationCost {
+                seek_count: (path_query.path.len() as u16).saturating_add(self.limit),
+                storage_written_bytes: 0,
+                storage_loaded_bytes: path_size
+                    .saturating_add(key_size)
+                    .saturating_add(value_size),
+                storage_freed_bytes: 0,
+                hash_byte_calls: 0,
+                hash_node_calls: 0,
+            })]),
+            fee_schedule,
+            // nothing is removed by a query
+            |_| Ok(fee_schedule),
+            fee_multiplier,
+            false,
+        )?
+        .processing_fee;
+
+        Ok(QueryExplanation {
+            index,
+            path_query: Some(path_query),
+            worst_case_processing_fee,
+            rejected_indexes,
+        })
+    }
+}
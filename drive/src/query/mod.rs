@@ -1,10 +1,11 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::BitXor;
 
 use ciborium::value::Value;
 use dpp::data_contract::extra::encode_float;
 use dpp::data_contract::extra::ContractError;
 use dpp::data_contract::extra::DriveContractExt;
+use grovedb::query_result_type::QueryResultType::QueryKeyElementPairResultType;
 pub use grovedb::{
     Element, Error as GroveError, GroveDb, PathQuery, Query, QueryItem, SizedQuery, TransactionArg,
 };
@@ -17,8 +18,11 @@ use sqlparser::ast::{OrderByExpr, Select, Statement};
 use sqlparser::dialect::GenericDialect;
 use sqlparser::parser::Parser;
 
+use conditions::sql_value_to_cbor;
 use conditions::WhereOperator::{Equal, In};
 pub use conditions::{WhereClause, WhereOperator};
+pub use cursor::{IndexKeyPath, QueryCursor};
+pub use explain::{IndexRejectionReason, QueryExplanation, RejectedIndex};
 pub use ordering::OrderClause;
 
 use crate::common::bytes_for_system_value;
@@ -30,11 +34,12 @@ use crate::error::query::QueryError;
 use crate::error::structure::StructureError;
 use crate::error::Error;
 use crate::error::Error::GroveDB;
-use crate::fee::calculate_fee;
 use crate::fee::op::DriveOperation;
 
 pub mod conditions;
+pub mod cursor;
 mod defaults;
+pub mod explain;
 pub mod ordering;
 mod test_index;
 
@@ -147,19 +152,154 @@ impl InternalClauses {
             ))),
         }
     }
+
+    /// Returns the clauses of the first conjunction and the clauses of the other ones
+    fn extract_from_disjunctive_clauses(
+        disjunctive_where_clauses: Vec<Vec<WhereClause>>,
+    ) -> Result<(Self, Vec<Self>), Error> {
+        // not equal and not in are planned as or clauses over the ranges around their values
+        let disjunctive_where_clauses =
+            WhereClause::expand_negated_clauses(disjunctive_where_clauses)?;
+
+        if disjunctive_where_clauses.len() > defaults::MAX_OR_CLAUSES {
+            return Err(Error::Query(QueryError::InvalidOrClause(
+                "query has too many or clauses",
+            )));
+        }
+
+        let mut all_internal_clauses = disjunctive_where_clauses
+            .into_iter()
+            .map(Self::extract_from_clauses)
+            .collect::<Result<Vec<Self>, Error>>()?;
+
+        if all_internal_clauses.is_empty() {
+            return Ok((Self::default(), vec![]));
+        }
+
+        let internal_clauses = all_internal_clauses.remove(0);
+
+        Ok((internal_clauses, all_internal_clauses))
+    }
+}
+
+fn where_clauses_from_value(where_value: &Value) -> Result<Vec<WhereClause>, Error> {
+    if let Value::Array(clauses) = where_value {
+        clauses
+            .iter()
+            .map(|where_clause| {
+                if let Value::Array(clauses_components) = where_clause {
+                    WhereClause::from_components(clauses_components)
+                } else {
+                    Err(Error::Query(QueryError::InvalidFormatWhereClause(
+                        "where clause must be an array",
+                    )))
+                }
+            })
+            .collect::<Result<Vec<WhereClause>, Error>>()
+    } else {
+        Err(Error::Query(QueryError::InvalidFormatWhereClause(
+            "where clause must be an array",
+        )))
+    }
 }
 
-#[derive(Debug, PartialEq)]
+/// Takes the `"$id" > 'base58'` cursor out of the top level conjunction of a sql
+/// selection. `>` and `<` map to startAfter, `>=` and `<=` to startAt, the operator
+/// has to go in the direction of the query, otherwise it stays a where clause.
+fn extract_sql_start_clause(
+    selection: &ast::Expr,
+    ascending: bool,
+) -> Result<(Option<ast::Expr>, Option<(Value, bool)>), Error> {
+    fn conjuncts(expr: &ast::Expr) -> Vec<&ast::Expr> {
+        match expr {
+            ast::Expr::BinaryOp {
+                left,
+                op: ast::BinaryOperator::And,
+                right,
+            } => {
+                let mut flattened = conjuncts(left);
+                flattened.extend(conjuncts(right));
+                flattened
+            }
+            _ => vec![expr],
+        }
+    }
+
+    let mut start_clause = None;
+    let mut remaining: Vec<ast::Expr> = vec![];
+
+    for conjunct in conjuncts(selection) {
+        let start = match conjunct {
+            ast::Expr::BinaryOp { left, op, right } => match (&**left, &**right) {
+                (ast::Expr::Identifier(ident), ast::Expr::Value(sql_value))
+                    if ident.value == "$id" =>
+                {
+                    let included = match (op, ascending) {
+                        (ast::BinaryOperator::Gt, true) | (ast::BinaryOperator::Lt, false) => {
+                            Some(false)
+                        }
+                        (ast::BinaryOperator::GtEq, true) | (ast::BinaryOperator::LtEq, false) => {
+                            Some(true)
+                        }
+                        _ => None,
+                    };
+                    included.map(|included| (sql_value, included))
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match start {
+            Some((sql_value, included)) => {
+                if start_clause.is_some() {
+                    return Err(Error::Query(QueryError::DuplicateStartConditions(
+                        "only one of startAt or startAfter should be provided",
+                    )));
+                }
+                let value = sql_value_to_cbor(sql_value.clone()).ok_or(Error::Query(
+                    QueryError::InvalidSQL("Invalid query: unexpected value type"),
+                ))?;
+                start_clause = Some((value, included));
+            }
+            None => remaining.push(conjunct.clone()),
+        }
+    }
+
+    let remaining_selection = remaining
+        .into_iter()
+        .reduce(|left, right| ast::Expr::BinaryOp {
+            left: Box::new(left),
+            op: ast::BinaryOperator::And,
+            right: Box::new(right),
+        });
+
+    Ok((remaining_selection, start_clause))
+}
+
+/// What a query returns
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum QuerySelect {
+    Documents,
+    Count,
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct DriveQuery<'a> {
     pub contract: &'a Contract,
     pub document_type: &'a DocumentType,
     pub internal_clauses: InternalClauses,
+    /// Other conjunctions of an OR query, documents matching any of them are returned
+    pub or_internal_clauses: Vec<InternalClauses>,
     pub offset: u16,
     pub limit: u16,
     pub order_by: IndexMap<String, OrderClause>,
     pub start_at: Option<Vec<u8>>,
     pub start_at_included: bool,
+    /// The key path of the start document comes from the cursor instead of being fetched
+    pub start_cursor: Option<QueryCursor>,
     pub block_time: Option<f64>,
+    pub select: QuerySelect,
 }
 
 impl<'a> DriveQuery<'a> {
@@ -204,6 +344,24 @@ impl<'a> DriveQuery<'a> {
                 "limit should be a integer from 1 to 100",
             )))?;
 
+        let offset: u16 = query_document
+            .remove("offset")
+            .map_or(Some(0), |id_cbor| {
+                if let Value::Integer(b) = id_cbor {
+                    let reduced = i128::from(b);
+                    if reduced < 0 || reduced > (defaults::MAX_QUERY_OFFSET as i128) {
+                        None
+                    } else {
+                        Some(reduced as u16)
+                    }
+                } else {
+                    None
+                }
+            })
+            .ok_or(Error::Query(QueryError::InvalidOffset(
+                "offset should be a integer from 0 to 1000",
+            )))?;
+
         let block_time: Option<f64> = query_document.remove("blockTime").and_then(|id_cbor| {
             if let Value::Float(b) = id_cbor {
                 Some(b)
@@ -214,31 +372,51 @@ impl<'a> DriveQuery<'a> {
             }
         });
 
-        let all_where_clauses: Vec<WhereClause> =
+        let select =
+            query_document
+                .remove("select")
+                .map_or(
+                    Ok(QuerySelect::Documents),
+                    |select_cbor| match select_cbor.as_text() {
+                        Some("count") => Ok(QuerySelect::Count),
+                        _ => Err(Error::Query(QueryError::InvalidSelect(
+                            "select should be \"count\" if provided",
+                        ))),
+                    },
+                )?;
+
+        let all_where_clauses: Vec<WhereClause> = query_document
+            .remove("where")
+            .map_or(Ok(vec![]), |id_cbor| where_clauses_from_value(&id_cbor))?;
+
+        // Each or clause is an array of where clauses that is combined with the where clauses
+        let disjunctive_where_clauses: Vec<Vec<WhereClause>> =
             query_document
-                .remove("where")
-                .map_or(Ok(vec![]), |id_cbor| {
-                    if let Value::Array(clauses) = id_cbor {
-                        clauses
+                .remove("or")
+                .map_or(Ok(vec![all_where_clauses.clone()]), |id_cbor| {
+                    if let Value::Array(or_clauses) = id_cbor {
+                        if or_clauses.is_empty() {
+                            return Err(Error::Query(QueryError::InvalidOrClause(
+                                "or clause must have at least 1 value",
+                            )));
+                        }
+                        or_clauses
                             .iter()
-                            .map(|where_clause| {
-                                if let Value::Array(clauses_components) = where_clause {
-                                    WhereClause::from_components(clauses_components)
-                                } else {
-                                    Err(Error::Query(QueryError::InvalidFormatWhereClause(
-                                        "where clause must be an array",
-                                    )))
-                                }
+                            .map(|or_clause| {
+                                let mut where_clauses = all_where_clauses.clone();
+                                where_clauses.extend(where_clauses_from_value(or_clause)?);
+                                Ok(where_clauses)
                             })
-                            .collect::<Result<Vec<WhereClause>, Error>>()
+                            .collect::<Result<Vec<Vec<WhereClause>>, Error>>()
                     } else {
-                        Err(Error::Query(QueryError::InvalidFormatWhereClause(
-                            "where clause must be an array",
+                        Err(Error::Query(QueryError::InvalidOrClause(
+                            "or clause must be an array of where clauses",
                         )))
                     }
                 })?;
 
-        let internal_clauses = InternalClauses::extract_from_clauses(all_where_clauses)?;
+        let (internal_clauses, or_internal_clauses) =
+            InternalClauses::extract_from_disjunctive_clauses(disjunctive_where_clauses)?;
 
         let start_at_option = query_document.remove("startAt");
         let start_after_option = query_document.remove("startAfter");
@@ -260,12 +438,14 @@ impl<'a> DriveQuery<'a> {
             start_at_included = true;
         }
 
-        let start_at: Option<Vec<u8>> = if start_option.is_some() {
+        let mut start_at: Option<Vec<u8>> = if start_option.is_some() {
             bytes_for_system_value(&start_option.unwrap())?
         } else {
             None
         };
 
+        let start_cursor_option = query_document.remove("cursor");
+
         let order_by: IndexMap<String, OrderClause> = query_document
             .remove("orderBy")
             .map_or(vec![], |id_cbor| {
@@ -288,23 +468,86 @@ impl<'a> DriveQuery<'a> {
             .map(|order_clause| Ok((order_clause.field.clone(), order_clause.to_owned())))
             .collect::<Result<IndexMap<String, OrderClause>, Error>>()?;
 
+        // A cursor continues after the last document of the previous page
+        let start_cursor = match start_cursor_option {
+            None => None,
+            Some(Value::Bytes(cursor_bytes)) => {
+                if start_at.is_some() {
+                    return Err(Error::Query(QueryError::DuplicateStartConditions(
+                        "a cursor can not be used with startAt or startAfter",
+                    )));
+                }
+                // the cursor already points after the skipped documents of the previous pages
+                if offset > 0 {
+                    return Err(Error::Query(QueryError::InvalidOffset(
+                        "a cursor can not be used with an offset",
+                    )));
+                }
+                let cursor = QueryCursor::from_bytes(cursor_bytes.as_slice())?;
+                if cursor.ascending != Self::order_is_ascending(&order_by) {
+                    return Err(Error::Query(QueryError::InvalidCursor(
+                        "cursor was made for a query in the other direction",
+                    )));
+                }
+                start_at = Some(cursor.index_key_path.id.to_vec());
+                start_at_included = false;
+                Some(cursor)
+            }
+            Some(_) => {
+                return Err(Error::Query(QueryError::InvalidCursor(
+                    "cursor must be bytes",
+                )))
+            }
+        };
+
         if !query_document.is_empty() {
             return Err(Error::Query(QueryError::Unsupported(
                 "unsupported syntax in where clause",
             )));
         }
 
-        Ok(DriveQuery {
+        let query = DriveQuery {
             contract,
             document_type,
             internal_clauses,
-            offset: 0,
+            or_internal_clauses,
+            offset,
             limit,
             order_by,
             start_at,
             start_at_included,
+            start_cursor,
             block_time,
-        })
+            select,
+        };
+
+        if let Some(cursor) = &query.start_cursor {
+            query.check_cursor(cursor)?;
+        }
+
+        Ok(query)
+    }
+
+    /// A cursor can only continue the query it was made for, on the same index
+    fn check_cursor(&self, cursor: &QueryCursor) -> Result<(), Error> {
+        if self.is_disjunctive() {
+            return Err(Error::Query(QueryError::InvalidCursor(
+                "cursors can not be used with or clauses",
+            )));
+        }
+        if cursor.contract_id != self.contract.id.buffer
+            || cursor.document_type_name != self.document_type.name
+        {
+            return Err(Error::Query(QueryError::InvalidCursor(
+                "cursor was made for another document type",
+            )));
+        }
+        if cursor.index_properties != self.index_key_path_properties()? {
+            return Err(Error::Query(QueryError::InvalidCursor(
+                "cursor was made for another index",
+            )));
+        }
+        Ok(())
     }
 
     pub fn from_sql_expr(sql_string: &str, contract: &'a Contract) -> Result<Self, Error> {
@@ -338,12 +581,31 @@ impl<'a> DriveQuery<'a> {
             defaults::DEFAULT_QUERY_LIMIT
         };
 
+        let offset: u16 = if let Some(offset) = &query.offset {
+            match &offset.value {
+                ast::Expr::Value(Number(num_string, _)) => num_string
+                    .parse::<u16>()
+                    .ok()
+                    .filter(|offset| *offset <= defaults::MAX_QUERY_OFFSET),
+                _ => None,
+            }
+            .ok_or(Error::Query(QueryError::InvalidOffset(
+                "Issue parsing sql: invalid offset value",
+            )))?
+        } else {
+            0
+        };
+
         let order_by: IndexMap<String, OrderClause> = query
             .order_by
             .iter()
             .map(|order_exp: &OrderByExpr| {
                 let ascending = order_exp.asc.is_none() || order_exp.asc.unwrap();
-                let field = order_exp.expr.to_string();
+                // quoted identifiers such as "$id" are ordered by their unquoted name
+                let field = match &order_exp.expr {
+                    ast::Expr::Identifier(ident) => ident.value.clone(),
+                    expr => expr.to_string(),
+                };
                 (field.clone(), OrderClause { field, ascending })
             })
             .collect::<IndexMap<String, OrderClause>>();
@@ -355,6 +617,17 @@ impl<'a> DriveQuery<'a> {
         }
         .ok_or(Error::Query(QueryError::InvalidSQL("Issue parsing sql")))?;
 
+        // Only select * and select count(*) are supported
+        let query_select = match select.projection.as_slice() {
+            [ast::SelectItem::Wildcard] => Ok(QuerySelect::Documents),
+            [select_item] if select_item.to_string().to_lowercase() == "count(*)" => {
+                Ok(QuerySelect::Count)
+            }
+            _ => Err(Error::Query(QueryError::InvalidSelect(
+                "Issue parsing sql: only * and count(*) can be selected",
+            ))),
+        }?;
+
         // Get the document type from the 'from' section
         let document_type_name = match &select
             .from
@@ -393,37 +666,46 @@ impl<'a> DriveQuery<'a> {
         // i.e other where clauses
         // e.g. firstname = wisdom and lastname = ogwu
         // if op is not [and] then [left] or [right] must not be a binary operation or list description
-        let mut all_where_clauses: Vec<WhereClause> = Vec::new();
-        let selection_tree = select.selection.as_ref();
+        // [or] splits the where clauses into several conjunctions that are queried separately
+        // a "$id" cursor in the direction of the query is used as startAt/startAfter
+        let ascending = Self::order_is_ascending(&order_by);
+        let (selection_tree, start_at_option) = match select.selection.as_ref() {
+            Some(selection_tree) => extract_sql_start_clause(selection_tree, ascending)?,
+            None => (None, None),
+        };
+
+        let mut disjunctive_where_clauses: Vec<Vec<WhereClause>> = vec![vec![]];
 
         // Where clauses are optional
-        if let Some(selection_tree) = selection_tree {
-            WhereClause::build_where_clauses_from_operations(
-                selection_tree,
-                &mut all_where_clauses,
-            )?;
+        if let Some(selection_tree) = selection_tree.as_ref() {
+            disjunctive_where_clauses =
+                WhereClause::build_disjunctive_where_clauses_from_operations(selection_tree)?;
         }
 
-        let internal_clauses = InternalClauses::extract_from_clauses(all_where_clauses)?;
+        let (internal_clauses, or_internal_clauses) =
+            InternalClauses::extract_from_disjunctive_clauses(disjunctive_where_clauses)?;
 
-        let start_at_option = None;
-        let start_at_included = true;
-        let start_at: Option<Vec<u8>> = if start_at_option.is_some() {
-            bytes_for_system_value(start_at_option.unwrap())?
-        } else {
-            None
+        let start_at_included = start_at_option
+            .as_ref()
+            .map_or(true, |(_, included)| *included);
+        let start_at: Option<Vec<u8>> = match start_at_option {
+            Some((start_value, _)) => bytes_for_system_value(&start_value)?,
+            None => None,
         };
 
         Ok(DriveQuery {
             contract,
             document_type,
             internal_clauses,
-            offset: 0,
+            or_internal_clauses,
+            offset,
             limit,
             order_by,
             start_at,
             start_at_included,
+            start_cursor: None,
             block_time: None,
+            select: query_select,
         })
     }
 
@@ -433,16 +715,35 @@ impl<'a> DriveQuery<'a> {
         transaction: TransactionArg,
         drive_operations: &mut Vec<DriveOperation>,
     ) -> Result<PathQuery, Error> {
-        // First we should get the overall document_type_path
-        let document_type_path = self
-            .contract
-            .document_type_path(self.document_type.name.as_str())
-            .into_iter()
-            .map(|a| a.to_vec())
-            .collect::<Vec<Vec<u8>>>();
+        if drive
+            .fetch_contract_archival_time(
+                self.contract.id.as_bytes(),
+                transaction,
+                drive_operations,
+            )?
+            .is_some()
+        {
+            return Err(Error::Query(QueryError::ContractArchived(
+                "archived contracts can not be queried",
+            )));
+        }
 
-        let starts_at_document: Option<(Document, bool)> = match &self.start_at {
+        if !self.is_for_primary_key() {
+            drive.check_index_is_backfilled(
+                self.contract.id.as_bytes(),
+                self.document_type.name.as_str(),
+                self.find_best_index()?,
+                transaction,
+                drive_operations,
+            )?;
+        }
+
+        let starts_at_key_path: Option<(IndexKeyPath, bool)> = match &self.start_at {
             None => Ok(None),
+            Some(_) if self.start_cursor.is_some() => Ok(self
+                .start_cursor
+                .as_ref()
+                .map(|cursor| (cursor.index_key_path.clone(), self.start_at_included))),
             Some(starts_at) => {
                 // First if we have a startAt or or startsAfter we must get the element
                 // from the backing store
@@ -491,7 +792,10 @@ impl<'a> DriveQuery<'a> {
 
                 if let Element::Item(item, _) = start_at_document {
                     let document = Document::from_cbor(item.as_slice(), None, None)?;
-                    Ok(Some((document, self.start_at_included)))
+                    Ok(Some((
+                        self.index_key_path_for_document(&document)?,
+                        self.start_at_included,
+                    )))
                 } else {
                     Err(Error::Drive(DriveError::CorruptedDocumentPath(
                         "Holding paths should only have items",
@@ -499,17 +803,67 @@ impl<'a> DriveQuery<'a> {
                 }
             }
         }?;
+        self.construct_path_query(starts_at_key_path)
+    }
+
+    /// The properties of the index the query is made on, empty for the primary key
+    fn index_key_path_properties(&self) -> Result<Vec<String>, Error> {
         if self.is_for_primary_key() {
-            self.get_primary_key_path_query(document_type_path, starts_at_document)
+            Ok(vec![])
+        } else {
+            Ok(self
+                .find_best_index()?
+                .properties
+                .iter()
+                .map(|index_property| index_property.name.clone())
+                .collect())
+        }
+    }
+
+    /// The key path of the document in the index the query is made on
+    pub fn index_key_path_for_document(&self, document: &Document) -> Result<IndexKeyPath, Error> {
+        let properties = self.index_key_path_properties()?;
+        IndexKeyPath::for_document(
+            document,
+            self.document_type,
+            properties.iter().map(|property| property.as_str()),
+        )
+    }
+
+    /// Builds the path query without touching the state, the index key path of the
+    /// start document must be provided if the query has a startAt or startAfter
+    pub fn construct_path_query(
+        &self,
+        starts_at_key_path: Option<(IndexKeyPath, bool)>,
+    ) -> Result<PathQuery, Error> {
+        // First we should get the overall document_type_path
+        let document_type_path = self
+            .contract
+            .document_type_path(self.document_type.name.as_str())
+            .into_iter()
+            .map(|a| a.to_vec())
+            .collect::<Vec<Vec<u8>>>();
+
+        let mut path_query = if self.is_for_primary_key() {
+            self.get_primary_key_path_query(document_type_path, starts_at_key_path)
         } else {
-            self.get_non_primary_key_path_query(document_type_path, starts_at_document)
+            self.get_non_primary_key_path_query(document_type_path, starts_at_key_path)
+        }?;
+
+        // Counts are bounded by the maximum count instead of the query limit, one more
+        // element is scanned to know if the bound was exceeded
+        if self.select == QuerySelect::Count {
+            path_query.query.limit = Some(defaults::MAX_COUNT_LIMIT + 1);
+            path_query.query.offset = None;
         }
+
+        Ok(path_query)
     }
 
     pub fn get_primary_key_path_query(
         &self,
         document_type_path: Vec<Vec<u8>>,
-        starts_at_document: Option<(Document, bool)>,
+        starts_at_key_path: Option<(IndexKeyPath, bool)>,
     ) -> Result<PathQuery, Error> {
         let mut path = document_type_path;
 
@@ -526,9 +880,11 @@ impl<'a> DriveQuery<'a> {
             if self.document_type.documents_keep_history {
                 // if the documents keep history then we should insert a subquery
                 if let Some(block_time) = self.block_time {
+                    // the latest revision at or before the block time, [0] is a reference
+                    // to the current revision so it must not be returned
                     let encoded_block_time = encode_float(block_time)?;
                     let mut sub_query = Query::new_with_direction(false);
-                    sub_query.insert_range_to_inclusive(..=encoded_block_time);
+                    sub_query.insert_range_after_to_inclusive(vec![0]..=encoded_block_time);
                     query.set_subquery(sub_query);
                 } else {
                     query.set_subquery_key(vec![0]);
@@ -553,17 +909,9 @@ impl<'a> DriveQuery<'a> {
             };
 
             let mut query = Query::new_with_direction(left_to_right);
-            // If there is a start_at_document, we need to get the value that it has for the
-            // current field.
-            let starts_at_key_option = match starts_at_document {
-                None => None,
-                Some((document, included)) => {
-                    // if the key doesn't exist then we should ignore the starts at key
-                    document
-                        .get_raw_for_document_type("$id", self.document_type, None)?
-                        .map(|raw_value_option| (raw_value_option, included))
-                }
-            };
+            // If there is a start at key path, its id is where the range starts
+            let starts_at_key_option =
+                starts_at_key_path.map(|(key_path, included)| (key_path.id.to_vec(), included));
 
             if let Some(primary_key_in_clause) = &self.internal_clauses.primary_key_in_clause {
                 let in_values = primary_key_in_clause.in_values()?;
@@ -593,7 +941,9 @@ impl<'a> DriveQuery<'a> {
                     // if the documents keep history then we should insert a subquery
                     if let Some(_block_time) = self.block_time {
                         //todo
-                        return Err(Error::Query(QueryError::Unsupported("Not yet implemented")));
+                        return Err(Error::Query(QueryError::Unsupported(
+                            "block time queries are only supported for a single $id, use fetch_document_as_of",
+                        )));
                         // in order to be able to do this we would need limited subqueries
                         // as we only want the first element before the block_time
 
@@ -631,7 +981,9 @@ impl<'a> DriveQuery<'a> {
                 if self.document_type.documents_keep_history {
                     // if the documents keep history then we should insert a subquery
                     if let Some(_block_time) = self.block_time {
-                        return Err(Error::Query(QueryError::Unsupported("Not yet implemented")));
+                        return Err(Error::Query(QueryError::Unsupported(
+                            "block time queries are only supported for a single $id, use fetch_document_as_of",
+                        )));
                         // in order to be able to do this we would need limited subqueries
                         // as we only want the first element before the block_time
 
@@ -652,7 +1004,8 @@ impl<'a> DriveQuery<'a> {
         }
     }
 
-    pub fn find_best_index(&self) -> Result<&Index, Error> {
+    /// The fields an index must have, the in field and the order by fields of the query
+    pub(crate) fn index_selection_fields(&self) -> (Vec<&str>, Option<&str>, Vec<&str>) {
         let equal_fields = self
             .internal_clauses
             .equal_clauses
@@ -690,6 +1043,12 @@ impl<'a> DriveQuery<'a> {
             })
             .collect();
 
+        (fields, in_field, order_by_keys)
+    }
+
+    pub fn find_best_index(&self) -> Result<&Index, Error> {
+        let (fields, in_field, order_by_keys) = self.index_selection_fields();
+
         let (index, difference) = self
             .document_type
             .index_for_types(fields.as_slice(), in_field, order_by_keys.as_slice())
@@ -713,14 +1072,14 @@ impl<'a> DriveQuery<'a> {
     }
 
     fn inner_query_from_starts_at_for_id(
-        starts_at_document: &Option<(Document, &DocumentType, &IndexProperty, bool)>,
+        starts_at_key_path: &Option<(IndexKeyPath, &IndexProperty, bool)>,
         left_to_right: bool,
     ) -> Query {
         // We only need items after the start at document
         let mut inner_query = Query::new_with_direction(left_to_right);
 
-        if let Some((document, _, _, included)) = starts_at_document {
-            let start_at_key = document.id.to_vec();
+        if let Some((key_path, _, included)) = starts_at_key_path {
+            let start_at_key = key_path.id.to_vec();
             if *included {
                 inner_query.insert_range_from(start_at_key..)
             } else {
@@ -756,23 +1115,18 @@ impl<'a> DriveQuery<'a> {
         inner_query
     }
 
-    // We are passing in starts_at_document 4 parameters
-    // The document
-    // The document type (borrowed)
+    // We are passing in starts_at_key_path 3 parameters
+    // The index key path of the start document
     // The index property (borrowed)
     // if the element itself should be included. ie StartAt vs StartAfter
     fn inner_query_from_starts_at(
-        starts_at_document: &Option<(Document, &DocumentType, &IndexProperty, bool)>,
+        starts_at_key_path: &Option<(IndexKeyPath, &IndexProperty, bool)>,
         left_to_right: bool,
     ) -> Result<Query, Error> {
         let mut inner_query = Query::new_with_direction(left_to_right);
-        if let Some((document, document_type, indexed_property, included)) = starts_at_document {
+        if let Some((key_path, indexed_property, included)) = starts_at_key_path {
             // We only need items after the start at document
-            let start_at_key = document.get_raw_for_document_type(
-                indexed_property.name.as_str(),
-                &document_type,
-                None,
-            )?;
+            let start_at_key = key_path.key_for_property(indexed_property.name.as_str());
             // We want to get items starting at the start key
             if let Some(start_at_key) = start_at_key {
                 if left_to_right {
@@ -802,7 +1156,7 @@ impl<'a> DriveQuery<'a> {
         query: Option<&mut Query>,
         left_over_index_properties: &[&IndexProperty],
         unique: bool,
-        starts_at_document: &Option<(Document, &DocumentType, &IndexProperty, bool)>, //for key level, included
+        starts_at_key_path: &Option<(IndexKeyPath, &IndexProperty, bool)>, //for key level, included
         default_left_to_right: bool,
         order_by: Option<&IndexMap<String, OrderClause>>,
     ) -> Result<Option<Query>, Error> {
@@ -815,7 +1169,7 @@ impl<'a> DriveQuery<'a> {
 
                             // In the case things are NULL we allow to have multiple values
                             let inner_query = Self::inner_query_from_starts_at_for_id(
-                                starts_at_document,
+                                starts_at_key_path,
                                 true, //for ids we always go left to right
                             );
                             query.add_conditional_subquery(
@@ -834,7 +1188,7 @@ impl<'a> DriveQuery<'a> {
                             query.set_subquery(full_query);
 
                             let inner_query = Self::inner_query_from_starts_at_for_id(
-                                starts_at_document,
+                                starts_at_key_path,
                                 default_left_to_right,
                             );
 
@@ -861,25 +1215,21 @@ impl<'a> DriveQuery<'a> {
                 match query {
                     None => {
                         let mut inner_query =
-                            Self::inner_query_from_starts_at(starts_at_document, left_to_right)?;
+                            Self::inner_query_from_starts_at(starts_at_key_path, left_to_right)?;
                         DriveQuery::recursive_insert_on_query(
                             Some(&mut inner_query),
                             left_over,
                             unique,
-                            starts_at_document,
+                            starts_at_key_path,
                             left_to_right,
                             order_by,
                         )?;
                         Ok(Some(inner_query))
                     }
                     Some(query) => {
-                        if let Some((document, document_type, _indexed_property, included)) =
-                            starts_at_document
-                        {
-                            let start_at_key = document
-                                .get_raw_for_document_type(first.name.as_str(), document_type, None)
-                                .ok()
-                                .flatten()
+                        if let Some((key_path, _indexed_property, included)) = starts_at_key_path {
+                            let start_at_key = key_path
+                                .key_for_property(first.name.as_str())
                                 .unwrap_or_default();
 
                             // We should always include if we have left_over
@@ -895,7 +1245,7 @@ impl<'a> DriveQuery<'a> {
                                 Some(&mut non_conditional_query),
                                 left_over,
                                 unique,
-                                starts_at_document,
+                                starts_at_key_path,
                                 left_to_right,
                                 order_by,
                             )?;
@@ -908,7 +1258,7 @@ impl<'a> DriveQuery<'a> {
                                 Some(&mut inner_query),
                                 left_over,
                                 unique,
-                                starts_at_document,
+                                starts_at_key_path,
                                 left_to_right,
                                 order_by,
                             )?;
@@ -925,7 +1275,7 @@ impl<'a> DriveQuery<'a> {
     pub fn get_non_primary_key_path_query(
         &self,
         document_type_path: Vec<Vec<u8>>,
-        starts_at_document: Option<(Document, bool)>,
+        starts_at_key_path: Option<(IndexKeyPath, bool)>,
     ) -> Result<PathQuery, Error> {
         let index = self.find_best_index()?;
         let ordered_clauses: Vec<&WhereClause> = index
@@ -996,9 +1346,8 @@ impl<'a> DriveQuery<'a> {
                     None,
                     left_over_index_properties.as_slice(),
                     index.unique,
-                    &starts_at_document.map(|(document, included)| {
-                        (document, self.document_type, first_index, included)
-                    }),
+                    &starts_at_key_path
+                        .map(|(key_path, included)| (key_path, first_index, included)),
                     first_index.ascending,
                     None,
                 )?
@@ -1021,15 +1370,15 @@ impl<'a> DriveQuery<'a> {
                 // We should set the starts at document to be included for the query if there are
                 // left over index properties.
 
-                let query_starts_at_document = if left_over_index_properties.is_empty() {
-                    &starts_at_document
+                let query_starts_at_key_path = if left_over_index_properties.is_empty() {
+                    &starts_at_key_path
                 } else {
                     &None
                 };
 
                 let mut query = where_clause.to_path_query(
                     self.document_type,
-                    query_starts_at_document,
+                    query_starts_at_key_path,
                     left_to_right,
                 )?;
 
@@ -1048,8 +1397,8 @@ impl<'a> DriveQuery<'a> {
                             Some(&mut query),
                             left_over_index_properties.as_slice(),
                             index.unique,
-                            &starts_at_document.map(|(document, included)| {
-                                (document, self.document_type, last_index_property, included)
+                            &starts_at_key_path.map(|(key_path, included)| {
+                                (key_path, last_index_property, included)
                             }),
                             left_to_right,
                             Some(&self.order_by),
@@ -1064,7 +1413,7 @@ impl<'a> DriveQuery<'a> {
                             )))?;
                         let mut subquery = subquery_where_clause.to_path_query(
                             self.document_type,
-                            &starts_at_document,
+                            &starts_at_key_path,
                             order_clause.ascending,
                         )?;
                         let last_index_property = index
@@ -1078,8 +1427,8 @@ impl<'a> DriveQuery<'a> {
                             Some(&mut subquery),
                             left_over_index_properties.as_slice(),
                             index.unique,
-                            &starts_at_document.map(|(document, included)| {
-                                (document, self.document_type, last_index_property, included)
+                            &starts_at_key_path.map(|(key_path, included)| {
+                                (key_path, last_index_property, included)
                             }),
                             left_to_right,
                             Some(&self.order_by),
@@ -1119,6 +1468,119 @@ impl<'a> DriveQuery<'a> {
         ))
     }
 
+    fn order_is_ascending(order_by: &IndexMap<String, OrderClause>) -> bool {
+        order_by
+            .values()
+            .next()
+            .map_or(true, |order_clause| order_clause.ascending)
+    }
+
+    /// The cursor to continue the query after the given document, it keeps the key
+    /// path of the document in the index the query is made on
+    pub fn cursor_after(&self, document: &Document) -> Result<QueryCursor, Error> {
+        // each conjunction of an or query is made on its own index
+        if self.is_disjunctive() {
+            return Err(Error::Query(QueryError::InvalidCursor(
+                "cursors can not be used with or clauses",
+            )));
+        }
+
+        Ok(QueryCursor {
+            contract_id: self.contract.id.buffer,
+            document_type_name: self.document_type.name.clone(),
+            index_properties: self.index_key_path_properties()?,
+            index_key_path: self.index_key_path_for_document(document)?,
+            ascending: Self::order_is_ascending(&self.order_by),
+        })
+    }
+
+    pub fn is_disjunctive(&self) -> bool {
+        !self.or_internal_clauses.is_empty()
+    }
+
+    /// Splits an OR query into a query for each of its conjunctions
+    fn conjunctive_queries(&self) -> Vec<DriveQuery<'a>> {
+        if !self.is_disjunctive() {
+            return vec![self.clone()];
+        }
+        std::iter::once(&self.internal_clauses)
+            .chain(self.or_internal_clauses.iter())
+            .map(|internal_clauses| DriveQuery {
+                internal_clauses: internal_clauses.clone(),
+                or_internal_clauses: vec![],
+                // the offset can only be applied once the conjunctions are merged
+                offset: 0,
+                limit: self.limit.saturating_add(self.offset),
+                ..self.clone()
+            })
+            .collect()
+    }
+
+    /// Deduplicates the documents returned for each conjunction and orders them by
+    /// the order by clauses, then by id, before applying the offset and limit.
+    /// The number of skipped documents is returned with them.
+    fn merge_conjunctive_results<T>(
+        &self,
+        results: Vec<(Document, T)>,
+    ) -> Result<(Vec<(Document, T)>, u16), Error> {
+        let mut known_ids: BTreeSet<[u8; 32]> = BTreeSet::new();
+
+        let mut keyed_results = results
+            .into_iter()
+            .filter(|(document, _)| known_ids.insert(document.id))
+            .map(|(document, value)| {
+                let sort_keys = self
+                    .order_by
+                    .keys()
+                    .map(|field| {
+                        document.get_raw_for_document_type(field.as_str(), self.document_type, None)
+                    })
+                    .collect::<Result<Vec<Option<Vec<u8>>>, Error>>()?;
+                Ok((sort_keys, document, value))
+            })
+            .collect::<Result<Vec<(Vec<Option<Vec<u8>>>, Document, T)>, Error>>()?;
+
+        keyed_results.sort_by(
+            |(left_keys, left_document, _), (right_keys, right_document, _)| {
+                self.order_by
+                    .values()
+                    .zip(left_keys.iter().zip(right_keys.iter()))
+                    .map(|(order_clause, (left_key, right_key))| {
+                        if order_clause.ascending {
+                            left_key.cmp(right_key)
+                        } else {
+                            right_key.cmp(left_key)
+                        }
+                    })
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or_else(|| left_document.id.cmp(&right_document.id))
+            },
+        );
+
+        let skipped = keyed_results.len().min(self.offset as usize);
+
+        Ok((
+            keyed_results
+                .into_iter()
+                .skip(skipped)
+                .take(self.limit as usize)
+                .map(|(_, document, value)| (document, value))
+                .collect(),
+            skipped as u16,
+        ))
+    }
+
+    fn merge_conjunctive_items(&self, items: Vec<Vec<u8>>) -> Result<(Vec<Vec<u8>>, u16), Error> {
+        let results = items
+            .into_iter()
+            .map(|item| Ok((Document::from_cbor(item.as_slice(), None, None)?, item)))
+            .collect::<Result<Vec<(Document, Vec<u8>)>, Error>>()?;
+
+        let (results, skipped) = self.merge_conjunctive_results(results)?;
+
+        Ok((results.into_iter().map(|(_, item)| item).collect(), skipped))
+    }
+
     pub fn execute_with_proof(
         self,
         drive: &Drive,
@@ -1126,7 +1588,9 @@ impl<'a> DriveQuery<'a> {
     ) -> Result<(Vec<u8>, u64), Error> {
         let mut drive_operations = vec![];
         let items = self.execute_with_proof_internal(drive, transaction, &mut drive_operations)?;
-        let (_, cost) = calculate_fee(None, Some(drive_operations))?;
+        let cost = drive
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
         Ok((items, cost))
     }
 
@@ -1136,9 +1600,87 @@ impl<'a> DriveQuery<'a> {
         transaction: TransactionArg,
         drive_operations: &mut Vec<DriveOperation>,
     ) -> Result<Vec<u8>, Error> {
+        if self.select == QuerySelect::Count {
+            return Err(Error::Query(QueryError::InvalidSelect(
+                "count queries must be executed with execute_count",
+            )));
+        }
+        if self.is_disjunctive() {
+            return Err(Error::Query(QueryError::Unsupported(
+                "queries with or clauses have a proof for each conjunction, use execute_with_proofs",
+            )));
+        }
+        let (_, proof) = self.prove_path_query_internal(drive, transaction, drive_operations)?;
+        Ok(proof)
+    }
+
+    fn prove_path_query_internal(
+        &self,
+        drive: &Drive,
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<(PathQuery, Vec<u8>), Error> {
         let path_query =
             self.construct_path_query_operations(drive, transaction, drive_operations)?;
-        drive.grove_get_proved_path_query(&path_query, transaction, drive_operations)
+        // the skipped elements are in the proof, grovedb charges them
+        let proof =
+            drive.grove_get_proved_path_query(&path_query, transaction, drive_operations)?;
+        Ok((path_query, proof))
+    }
+
+    /// Executes the query and returns its proof with a cursor to the next page, there
+    /// is no cursor when the page is not full. The cursor is made from the last proved
+    /// document, so clients can rebuild it with `cursor_after` once the proof is verified.
+    pub fn execute_with_proof_and_cursor(
+        &self,
+        drive: &Drive,
+        transaction: TransactionArg,
+    ) -> Result<(Vec<u8>, Option<Vec<u8>>, u64), Error> {
+        if self.select == QuerySelect::Count {
+            return Err(Error::Query(QueryError::InvalidSelect(
+                "count queries must be executed with execute_count",
+            )));
+        }
+        if self.is_disjunctive() {
+            return Err(Error::Query(QueryError::Unsupported(
+                "queries with or clauses have a proof for each conjunction, use execute_with_proofs",
+            )));
+        }
+        let mut drive_operations = vec![];
+        let (path_query, proof) =
+            self.prove_path_query_internal(drive, transaction, &mut drive_operations)?;
+        let (_, documents) = Self::documents_from_proof(proof.as_slice(), &path_query)?;
+        let cursor = match documents.last() {
+            Some(last_document) if documents.len() == self.limit as usize => {
+                Some(self.cursor_after(last_document)?.to_bytes()?)
+            }
+            _ => None,
+        };
+        let cost = drive
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
+        Ok((proof, cursor, cost))
+    }
+
+    /// Returns a proof for each conjunction of the query, there is only one
+    /// if the query has no or clauses
+    pub fn execute_with_proofs(
+        self,
+        drive: &Drive,
+        transaction: TransactionArg,
+    ) -> Result<(Vec<Vec<u8>>, u64), Error> {
+        let mut drive_operations = vec![];
+        let proofs = self
+            .conjunctive_queries()
+            .into_iter()
+            .map(|query| {
+                query.execute_with_proof_internal(drive, transaction, &mut drive_operations)
+            })
+            .collect::<Result<Vec<Vec<u8>>, Error>>()?;
+        let cost = drive
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
+        Ok((proofs, cost))
     }
 
     pub fn execute_with_proof_only_get_elements(
@@ -1152,7 +1694,9 @@ impl<'a> DriveQuery<'a> {
             transaction,
             &mut drive_operations,
         )?;
-        let (_, cost) = calculate_fee(None, Some(drive_operations))?;
+        let cost = drive
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
         Ok((root_hash, items, cost))
     }
 
@@ -1162,9 +1706,30 @@ impl<'a> DriveQuery<'a> {
         transaction: TransactionArg,
         drive_operations: &mut Vec<DriveOperation>,
     ) -> Result<([u8; 32], Vec<Vec<u8>>), Error> {
+        if self.is_disjunctive() {
+            let mut root_hash = None;
+            let mut items = vec![];
+            for query in self.conjunctive_queries() {
+                let (query_root_hash, query_items) = query
+                    .execute_with_proof_only_get_elements_internal(
+                        drive,
+                        transaction,
+                        drive_operations,
+                    )?;
+                if *root_hash.get_or_insert(query_root_hash) != query_root_hash {
+                    return Err(Error::Query(QueryError::InvalidProof(
+                        "proofs of the or clauses have different root hashes",
+                    )));
+                }
+                items.extend(query_items);
+            }
+            let (items, _) = self.merge_conjunctive_items(items)?;
+            return Ok((root_hash.unwrap_or_default(), items));
+        }
+
         let path_query =
             self.construct_path_query_operations(drive, transaction, drive_operations)?;
-
+        // the skipped elements are in the proof, grovedb charges them
         let proof =
             drive.grove_get_proved_path_query(&path_query, transaction, drive_operations)?;
         let (root_hash, mut key_value_elements) =
@@ -1186,6 +1751,149 @@ impl<'a> DriveQuery<'a> {
         Ok((root_hash, values))
     }
 
+    /// Verifies a proof produced by `execute_with_proof` without needing the state.
+    /// For primary key queries the startAt/startAfter id is enough, otherwise the
+    /// start document (usually the last document of the previous page) or a cursor
+    /// is required.
+    pub fn verify_proof(
+        &self,
+        proof: &[u8],
+        start_document: Option<&Document>,
+    ) -> Result<([u8; 32], Vec<Document>), Error> {
+        if self.is_disjunctive() {
+            return Err(Error::Query(QueryError::Unsupported(
+                "queries with or clauses have a proof for each conjunction, use verify_proofs",
+            )));
+        }
+
+        let start_key_path = match start_document {
+            Some(document) => Some(self.index_key_path_for_document(document)?),
+            None => self
+                .start_cursor
+                .as_ref()
+                .map(|cursor| cursor.index_key_path.clone()),
+        };
+
+        let starts_at_key_path = match &self.start_at {
+            None => None,
+            Some(starts_at) => {
+                let key_path = match start_key_path {
+                    Some(key_path) => {
+                        if key_path.id.as_slice() != starts_at.as_slice() {
+                            return Err(Error::Query(QueryError::InvalidStartDocument(
+                                "start document id must be equal to the startAt id",
+                            )));
+                        }
+                        key_path
+                    }
+                    None if self.is_for_primary_key() => IndexKeyPath {
+                        property_keys: BTreeMap::new(),
+                        id: starts_at.as_slice().try_into().map_err(|_| {
+                            Error::Query(QueryError::InvalidStartDocument(
+                                "startAt id must be 32 bytes",
+                            ))
+                        })?,
+                    },
+                    None => {
+                        return Err(Error::Query(QueryError::StartDocumentNotFound(
+                            "start document is required to verify a non primary key query",
+                        )))
+                    }
+                };
+                Some((key_path, self.start_at_included))
+            }
+        };
+
+        let path_query = self.construct_path_query(starts_at_key_path)?;
+
+        Self::documents_from_proof(proof, &path_query)
+    }
+
+    fn documents_from_proof(
+        proof: &[u8],
+        path_query: &PathQuery,
+    ) -> Result<([u8; 32], Vec<Document>), Error> {
+        let (root_hash, key_value_elements) =
+            GroveDb::verify_query(proof, path_query).map_err(Error::GroveDB)?;
+
+        let documents = key_value_elements
+            .into_iter()
+            .map(|(_, value)| {
+                let element = Element::deserialize(&value).map_err(Error::GroveDB)?;
+                match element {
+                    Element::Item(val, _) => Document::from_cbor(val.as_slice(), None, None),
+                    Element::Tree(..) | Element::Reference(..) => {
+                        Err(Error::GroveDB(GroveError::InvalidQuery(
+                            "path query should only point to items: got trees",
+                        )))
+                    }
+                }
+            })
+            .collect::<Result<Vec<Document>, Error>>()?;
+
+        Ok((root_hash, documents))
+    }
+
+    /// Verifies the proofs returned by `execute_with_proofs`
+    pub fn verify_proofs(
+        &self,
+        proofs: &[Vec<u8>],
+        start_document: Option<&Document>,
+    ) -> Result<([u8; 32], Vec<Document>), Error> {
+        let queries = self.conjunctive_queries();
+
+        if proofs.len() != queries.len() {
+            return Err(Error::Query(QueryError::InvalidProof(
+                "there must be a proof for each conjunction of the query",
+            )));
+        }
+
+        let mut root_hash = None;
+        let mut documents = vec![];
+        for (query, proof) in queries.iter().zip(proofs.iter()) {
+            let (proof_root_hash, proved_documents) =
+                query.verify_proof(proof.as_slice(), start_document)?;
+            if *root_hash.get_or_insert(proof_root_hash) != proof_root_hash {
+                return Err(Error::Query(QueryError::InvalidProof(
+                    "proofs of the or clauses have different root hashes",
+                )));
+            }
+            documents.extend(proved_documents.into_iter().map(|document| (document, ())));
+        }
+
+        let documents = if self.is_disjunctive() {
+            self.merge_conjunctive_results(documents)?.0
+        } else {
+            documents
+        };
+
+        Ok((
+            root_hash.unwrap_or_default(),
+            documents
+                .into_iter()
+                .map(|(document, _)| document)
+                .collect(),
+        ))
+    }
+
+    /// Executes the query and returns a cursor to the next page, there is no cursor
+    /// when the page is not full
+    pub fn execute_no_proof_with_cursor(
+        &self,
+        drive: &Drive,
+        transaction: TransactionArg,
+    ) -> Result<(Vec<Vec<u8>>, Option<Vec<u8>>, u64), Error> {
+        let (items, _, cost) = self.execute_no_proof(drive, transaction)?;
+        let cursor = match items.last() {
+            Some(last_item) if items.len() == self.limit as usize => {
+                let last_document = Document::from_cbor(last_item.as_slice(), None, None)?;
+                Some(self.cursor_after(&last_document)?.to_bytes()?)
+            }
+            _ => None,
+        };
+        Ok((items, cursor, cost))
+    }
+
     pub fn execute_no_proof(
         &self,
         drive: &Drive,
@@ -1194,7 +1902,9 @@ impl<'a> DriveQuery<'a> {
         let mut drive_operations = vec![];
         let (items, skipped) =
             self.execute_no_proof_internal(drive, transaction, &mut drive_operations)?;
-        let (_, cost) = calculate_fee(None, Some(drive_operations))?;
+        let cost = drive
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
         Ok((items, skipped, cost))
     }
 
@@ -1204,19 +1914,87 @@ impl<'a> DriveQuery<'a> {
         transaction: TransactionArg,
         drive_operations: &mut Vec<DriveOperation>,
     ) -> Result<(Vec<Vec<u8>>, u16), Error> {
+        if self.select == QuerySelect::Count {
+            return Err(Error::Query(QueryError::InvalidSelect(
+                "count queries must be executed with execute_count",
+            )));
+        }
+        if self.is_disjunctive() {
+            let mut items = vec![];
+            for query in self.conjunctive_queries() {
+                let (query_items, _) =
+                    query.execute_no_proof_internal(drive, transaction, drive_operations)?;
+                items.extend(query_items);
+            }
+            // the documents skipped by the offset were fetched and charged by the conjunctions
+            return self.merge_conjunctive_items(items);
+        }
+
         let path_query =
             self.construct_path_query_operations(drive, transaction, drive_operations)?;
         let query_result = drive.grove_get_path_query(&path_query, transaction, drive_operations);
         match query_result {
             Err(GroveDB(GroveError::PathKeyNotFound(_)))
             | Err(GroveDB(GroveError::PathNotFound(_))) => Ok((Vec::new(), 0)),
+            // grovedb walks the skipped elements and charges them with the query
+            _ => query_result,
+        }
+    }
+
+    /// Counts the documents matching the query by counting the raw elements of the
+    /// index, referenced documents are not loaded. The index subtree read is charged
+    /// and at most `MAX_COUNT_LIMIT` documents can be counted.
+    pub fn execute_count(
+        &self,
+        drive: &Drive,
+        transaction: TransactionArg,
+    ) -> Result<(u64, u64), Error> {
+        let mut drive_operations = vec![];
+        let count = self.execute_count_internal(drive, transaction, &mut drive_operations)?;
+        let cost = drive
+            .calculate_fee(None, Some(drive_operations), false, transaction)?
+            .processing_fee;
+        Ok((count, cost))
+    }
+
+    pub(crate) fn execute_count_internal(
+        &self,
+        drive: &Drive,
+        transaction: TransactionArg,
+        drive_operations: &mut Vec<DriveOperation>,
+    ) -> Result<u64, Error> {
+        if self.is_disjunctive() {
+            return Err(Error::Query(QueryError::Unsupported(
+                "count queries with or clauses are not supported",
+            )));
+        }
+        let path_query = DriveQuery {
+            select: QuerySelect::Count,
+            ..self.clone()
+        }
+        .construct_path_query_operations(drive, transaction, drive_operations)?;
+        // the raw query does not follow the references of the index, only the index
+        // subtree itself is read and the real cost of reading it is charged
+        let query_result = drive.grove_get_raw_path_query(
+            &path_query,
+            transaction,
+            QueryKeyElementPairResultType,
+            drive_operations,
+        );
+        let count = match query_result {
+            Err(GroveDB(GroveError::PathKeyNotFound(_)))
+            | Err(GroveDB(GroveError::PathNotFound(_))) => 0,
             _ => {
-                let (data, skipped) = query_result?;
-                {
-                    Ok((data, skipped))
-                }
+                let (elements, _) = query_result?;
+                elements.to_key_elements().len()
             }
+        };
+        if count > defaults::MAX_COUNT_LIMIT as usize {
+            return Err(Error::Query(QueryError::InvalidLimit(
+                "count queries can't count more than 10000 documents",
+            )));
         }
+        Ok(count as u64)
     }
 }
 
@@ -1231,6 +2009,8 @@ mod tests {
     use crate::contract::{Contract, DocumentType};
     use crate::drive::flags::StorageFlags;
     use crate::drive::Drive;
+    use crate::error::query::QueryError;
+    use crate::error::Error;
     use crate::query::DriveQuery;
     use serde_json::Value::Null;
 
@@ -1257,6 +2037,7 @@ mod tests {
                 contract_cbor.clone(),
                 0f64,
                 true,
+                false,
                 storage_flags,
                 None,
             )
@@ -1287,6 +2068,7 @@ mod tests {
                 contract_cbor.clone(),
                 0f64,
                 true,
+                false,
                 storage_flags,
                 None,
             )
@@ -1680,4 +2462,50 @@ mod tests {
         DriveQuery::from_cbor(where_cbor.as_slice(), &contract, &document_type)
             .expect_err("starts with can not start with an empty string");
     }
+
+    #[test]
+    fn test_invalid_query_offset_too_high() {
+        let query_value = json!({
+            "where": [
+                ["firstName", "startsWith", "a"],
+            ],
+            "offset": 1001,
+            "orderBy": [
+                ["firstName", "asc"],
+            ],
+        });
+
+        let contract = Contract::default();
+        let document_type = DocumentType::default();
+
+        let where_cbor = common::value_to_cbor(query_value, None);
+        let result = DriveQuery::from_cbor(where_cbor.as_slice(), &contract, &document_type);
+        assert!(matches!(
+            result,
+            Err(Error::Query(QueryError::InvalidOffset(_)))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_query_offset_negative() {
+        let query_value = json!({
+            "where": [
+                ["firstName", "startsWith", "a"],
+            ],
+            "offset": -1,
+            "orderBy": [
+                ["firstName", "asc"],
+            ],
+        });
+
+        let contract = Contract::default();
+        let document_type = DocumentType::default();
+
+        let where_cbor = common::value_to_cbor(query_value, None);
+        let result = DriveQuery::from_cbor(where_cbor.as_slice(), &contract, &document_type);
+        assert!(matches!(
+            result,
+            Err(Error::Query(QueryError::InvalidOffset(_)))
+        ));
+    }
 }
@@ -1,11 +1,13 @@
 #[cfg(test)]
 mod tests {
+    use rust_decimal::Decimal;
     use serde_json::json;
 
     use crate::common;
     use crate::contract::{Contract, DocumentType, Index, IndexProperty};
     use crate::error::{query::QueryError, Error};
-    use crate::query::DriveQuery;
+    use crate::fee::fee_schedule::FeeSchedule;
+    use crate::query::{DriveQuery, IndexRejectionReason};
 
     fn construct_indexed_document_type() -> DocumentType {
         DocumentType {
@@ -112,4 +114,33 @@ mod tests {
             matches!(error, Error::Query(QueryError::WhereClauseOnNonIndexedProperty(message)) if message == "query must be for valid indexes")
         )
     }
+
+    #[test]
+    fn test_explain_without_matching_index() {
+        let document_type = construct_indexed_document_type();
+        let contract = Contract::default();
+
+        let query_value = json!({
+            "where": [
+                ["c", "==", "1"]
+            ]
+        });
+        let where_cbor = common::value_to_cbor(query_value, None);
+        let query = DriveQuery::from_cbor(where_cbor.as_slice(), &contract, &document_type)
+            .expect("query should be valid");
+        let explanation = query
+            .explain(&FeeSchedule::default(), Decimal::ONE)
+            .expect("expected to explain the query");
+
+        assert!(explanation.index.is_none());
+        assert!(explanation.path_query.is_none());
+        assert_eq!(
+            explanation.rejected_indexes.len(),
+            document_type.indices.len()
+        );
+        assert!(explanation
+            .rejected_indexes
+            .iter()
+            .all(|rejected_index| rejected_index.reason == IndexRejectionReason::NotMatching));
+    }
 }
@@ -106,6 +106,7 @@ pub fn add_domains_to_contract(
                 true,
                 0f64,
                 true,
+                false,
                 transaction,
             )
             .expect("document should be inserted");
@@ -361,6 +362,7 @@ fn test_root_hash_with_batches(drive: &Drive, db_transaction: &Transaction) {
             None,
             0f64,
             true,
+            false,
             StorageFlags::default(),
             Some(db_transaction),
         )
@@ -1,5 +1,5 @@
 use grovedb::TransactionArg;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::option::Option::None;
@@ -23,7 +23,7 @@ use rs_drive::drive::object_size_info::DocumentAndContractInfo;
 use rs_drive::drive::object_size_info::DocumentInfo::DocumentAndSerialization;
 use rs_drive::drive::Drive;
 use rs_drive::error::{query::QueryError, Error};
-use rs_drive::query::DriveQuery;
+use rs_drive::query::{DriveQuery, QueryCursor, QuerySelect};
 
 use dpp::data_contract::extra::DriveContractExt;
 
@@ -175,6 +175,7 @@ pub fn setup_family_tests(count: u32, with_batching: bool, seed: u64) -> (Drive,
                 true,
                 0f64,
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("document should be inserted");
@@ -248,6 +249,7 @@ pub fn setup_family_tests_with_nulls(
                 true,
                 0f64,
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("document should be inserted");
@@ -349,6 +351,7 @@ pub fn add_domains_to_contract(
                 true,
                 0f64,
                 true,
+                false,
                 transaction,
             )
             .expect("document should be inserted");
@@ -444,6 +447,7 @@ pub fn setup_dpns_test_with_data(path: &str) -> (Drive, Contract) {
                     false,
                     0f64,
                     true,
+                    false,
                     Some(&db_transaction),
                 )
                 .expect("expected to insert a document successfully");
@@ -492,6 +496,7 @@ fn test_query_many() {
                 true,
                 0f64,
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("document should be inserted");
@@ -1276,6 +1281,7 @@ fn test_family_basic_queries() {
             true,
             0f64,
             true,
+            false,
             Some(&db_transaction),
         )
         .expect("document should be inserted");
@@ -1319,6 +1325,7 @@ fn test_family_basic_queries() {
             true,
             0f64,
             true,
+            false,
             Some(&db_transaction),
         )
         .expect("document should be inserted");
@@ -1565,6 +1572,7 @@ fn test_family_basic_queries() {
             None,
             0f64,
             true,
+            false,
             StorageFlags::default(),
             Some(&db_transaction),
         )
@@ -2167,6 +2175,7 @@ fn test_family_with_nulls_query() {
                 "person",
                 None,
                 true,
+                false,
                 Some(&db_transaction),
             )
             .expect("expected to be able to delete the document");
@@ -3138,6 +3147,7 @@ fn test_dpns_query_start_at_with_null_id() {
             true,
             0f64,
             true,
+            false,
             Some(&db_transaction),
         )
         .expect("document should be inserted");
@@ -3179,6 +3189,7 @@ fn test_dpns_query_start_at_with_null_id() {
             true,
             0f64,
             true,
+            false,
             Some(&db_transaction),
         )
         .expect("document should be inserted");
@@ -3330,6 +3341,7 @@ fn test_dpns_query_start_after_with_null_id() {
             true,
             0f64,
             true,
+            false,
             Some(&db_transaction),
         )
         .expect("document should be inserted");
@@ -3372,6 +3384,7 @@ fn test_dpns_query_start_after_with_null_id() {
             true,
             0f64,
             true,
+            false,
             Some(&db_transaction),
         )
         .expect("document should be inserted");
@@ -3525,6 +3538,7 @@ fn test_dpns_query_start_after_with_null_id_desc() {
             true,
             0f64,
             true,
+            false,
             Some(&db_transaction),
         )
         .expect("document should be inserted");
@@ -3567,6 +3581,7 @@ fn test_dpns_query_start_after_with_null_id_desc() {
             true,
             0f64,
             true,
+            false,
             Some(&db_transaction),
         )
         .expect("document should be inserted");
@@ -3767,9 +3782,980 @@ fn test_dpns_query_start_after_with_null_id_desc() {
     assert_eq!(results, proof_results);
 }
 
+#[test]
+fn test_family_query_proof_verification() {
+    let (drive, contract) = setup_family_tests(10, true, 73509);
+
+    let root_hash = drive
+        .grove
+        .root_hash(None)
+        .unwrap()
+        .expect("there is always a root hash");
+
+    let person_document_type = contract
+        .document_types()
+        .get("person")
+        .expect("contract should have a person document type");
+
+    let kevina_encoded_id = "B4zLoYmSGz5SyD7QjAvcjAWtzGCfnQDCti3o7V2ZBDNo".to_string();
+
+    // A primary key query can be verified with the startAfter id only
+
+    let query_value = json!({
+        "startAfter": kevina_encoded_id, //Kevina
+        "limit": 100,
+        "orderBy": [
+            ["$id", "asc"]
+        ]
+    });
+    let where_cbor = common::value_to_cbor(query_value, None);
+    let query = DriveQuery::from_cbor(where_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+    let (results, _, _) = query
+        .execute_no_proof(&drive, None)
+        .expect("query should be executed");
+    let expected_documents: Vec<Document> = results
+        .iter()
+        .map(|result| {
+            Document::from_cbor(result.as_slice(), None, None)
+                .expect("we should be able to deserialize the cbor")
+        })
+        .collect();
+
+    let (proof, _) = DriveQuery::from_cbor(where_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built")
+        .execute_with_proof(&drive, None)
+        .expect("we should be able to a proof");
+
+    let (proof_root_hash, documents) = query
+        .verify_proof(proof.as_slice(), None)
+        .expect("we should be able to verify the proof");
+    assert_eq!(root_hash, proof_root_hash);
+    assert_eq!(documents, expected_documents);
+
+    // A non primary key query needs the start document
+
+    let query_value = json!({
+        "where": [
+            ["firstName", ">", "Chris"],
+            ["firstName", "<=", "Noellyn"]
+        ],
+        "startAt": kevina_encoded_id, //Kevina
+        "limit": 100,
+        "orderBy": [
+            ["firstName", "asc"]
+        ]
+    });
+    let where_cbor = common::value_to_cbor(query_value, None);
+    let query = DriveQuery::from_cbor(where_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+    let (results, _, _) = query
+        .execute_no_proof(&drive, None)
+        .expect("query should be executed");
+    let kevina = Document::from_cbor(results[0].as_slice(), None, None)
+        .expect("we should be able to deserialize the cbor");
+
+    let query_value = json!({
+        "where": [
+            ["firstName", ">", "Chris"],
+            ["firstName", "<=", "Noellyn"]
+        ],
+        "startAfter": kevina_encoded_id, //Kevina
+        "limit": 100,
+        "orderBy": [
+            ["firstName", "asc"]
+        ]
+    });
+    let where_cbor = common::value_to_cbor(query_value, None);
+    let query = DriveQuery::from_cbor(where_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+
+    let (proof, _) = DriveQuery::from_cbor(where_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built")
+        .execute_with_proof(&drive, None)
+        .expect("we should be able to a proof");
+
+    let result = query.verify_proof(proof.as_slice(), None);
+    assert!(matches!(
+        result,
+        Err(Error::Query(QueryError::StartDocumentNotFound(_)))
+    ));
+
+    let (proof_root_hash, documents) = query
+        .verify_proof(proof.as_slice(), Some(&kevina))
+        .expect("we should be able to verify the proof");
+    assert_eq!(root_hash, proof_root_hash);
+
+    let names: Vec<&str> = documents
+        .iter()
+        .map(|document| {
+            document
+                .properties
+                .get("firstName")
+                .expect("we should be able to get the first name")
+                .as_text()
+                .expect("the first name should be a string")
+        })
+        .collect();
+    assert_eq!(names, ["Meta", "Noellyn"]);
+}
+
+#[test]
+fn test_family_or_queries() {
+    let (drive, contract) = setup_family_tests(10, true, 73509);
+
+    let root_hash = drive
+        .grove
+        .root_hash(None)
+        .unwrap()
+        .expect("there is always a root hash");
+
+    let person_document_type = contract
+        .document_types()
+        .get("person")
+        .expect("contract should have a person document type");
+
+    // The last two clauses overlap on Prissie, she should only be returned once
+    let query_value = json!({
+        "or": [
+            [["firstName", "<", "Briney"]],
+            [["firstName", ">", "Noellyn"]],
+            [["firstName", ">=", "Prissie"]]
+        ],
+        "limit": 100,
+        "orderBy": [
+            ["firstName", "desc"]
+        ]
+    });
+    let where_cbor = common::value_to_cbor(query_value, None);
+    let query = DriveQuery::from_cbor(where_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+
+    assert!(query.is_disjunctive());
+
+    let sql_string = "select * from person where firstName < 'Briney' or (firstName > 'Noellyn' or firstName >= 'Prissie') order by firstName desc limit 100";
+    let sql_query = DriveQuery::from_sql_expr(sql_string, &contract).expect("should build query");
+
+    assert_eq!(query, sql_query);
+
+    let (results, _, _) = query
+        .execute_no_proof(&drive, None)
+        .expect("query should be executed");
+
+    let names: Vec<String> = results
+        .iter()
+        .map(|result| {
+            let document = Document::from_cbor(result.as_slice(), None, None)
+                .expect("we should be able to deserialize the cbor");
+            let first_name_value = document
+                .properties
+                .get("firstName")
+                .expect("we should be able to get the first name");
+            let first_name = first_name_value
+                .as_text()
+                .expect("the first name should be a string");
+            String::from(first_name)
+        })
+        .collect();
+
+    assert_eq!(names, ["Prissie".to_string(), "Adey".to_string()]);
+
+    let (proof_root_hash, proof_results, _) = query
+        .clone()
+        .execute_with_proof_only_get_elements(&drive, None)
+        .expect("we should be able to a proof");
+    assert_eq!(root_hash, proof_root_hash);
+    assert_eq!(results, proof_results);
+
+    let (proofs, _) = query
+        .clone()
+        .execute_with_proofs(&drive, None)
+        .expect("we should be able to get proofs");
+
+    assert_eq!(proofs.len(), 3);
+
+    let (proof_root_hash, documents) = query
+        .verify_proofs(proofs.as_slice(), None)
+        .expect("we should be able to verify the proofs");
+    assert_eq!(root_hash, proof_root_hash);
+
+    let expected_documents: Vec<Document> = results
+        .iter()
+        .map(|result| {
+            Document::from_cbor(result.as_slice(), None, None)
+                .expect("we should be able to deserialize the cbor")
+        })
+        .collect();
+    assert_eq!(documents, expected_documents);
+
+    let result = query.execute_with_proof(&drive, None);
+    assert!(matches!(
+        result,
+        Err(Error::Query(QueryError::Unsupported(_)))
+    ));
+}
+
+#[test]
+fn test_family_count_queries() {
+    let (drive, contract) = setup_family_tests(10, true, 73509);
+
+    let person_document_type = contract
+        .document_types()
+        .get("person")
+        .expect("contract should have a person document type");
+
+    // Counting all documents uses the primary key tree
+    let query_cbor = common::value_to_cbor(json!({ "select": "count" }), None);
+    let query = DriveQuery::from_cbor(query_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+
+    let (count, count_cost) = query
+        .execute_count(&drive, None)
+        .expect("count should be executed");
+
+    assert_eq!(count, 10);
+
+    // the primary key tree holds the documents, so they are read and charged
+    let documents_query = DriveQuery {
+        select: QuerySelect::Documents,
+        ..query.clone()
+    };
+    let (results, _, documents_cost) = documents_query
+        .execute_no_proof(&drive, None)
+        .expect("query should be executed");
+
+    assert_eq!(results.len() as u64, count);
+    assert!(count_cost > 0);
+    assert!(count_cost <= documents_cost);
+
+    // Counting on an index is not limited by the query limit
+    let query_cbor = common::value_to_cbor(
+        json!({
+            "select": "count",
+            "where": [
+                ["firstName", ">", "Chris"],
+                ["firstName", "<=", "Noellyn"]
+            ],
+            "limit": 2,
+            "orderBy": [
+                ["firstName", "asc"]
+            ]
+        }),
+        None,
+    );
+    let query = DriveQuery::from_cbor(query_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+
+    let (count, count_cost) = query
+        .execute_count(&drive, None)
+        .expect("count should be executed");
+
+    assert_eq!(count, 5);
+
+    let result = query.execute_no_proof(&drive, None);
+    assert!(matches!(
+        result,
+        Err(Error::Query(QueryError::InvalidSelect(_)))
+    ));
+
+    let documents_query = DriveQuery {
+        select: QuerySelect::Documents,
+        limit: 100,
+        ..query.clone()
+    };
+    let (results, _, documents_cost) = documents_query
+        .execute_no_proof(&drive, None)
+        .expect("query should be executed");
+
+    assert_eq!(results.len() as u64, count);
+    assert!(count_cost < documents_cost);
+
+    let sql_string = "select count(*) from person where firstName > 'Chris' and firstName <= 'Noellyn' order by firstName asc limit 2";
+    let sql_query = DriveQuery::from_sql_expr(sql_string, &contract).expect("should build query");
+
+    assert_eq!(query, sql_query);
+}
+
 #[test]
 #[ignore]
 fn pwd() {
     let working_dir = std::env::current_dir().unwrap();
     println!("{}", working_dir.display());
 }
+
+#[test]
+fn test_family_negated_queries() {
+    let (drive, contract) = setup_family_tests(10, true, 73509);
+
+    let person_document_type = contract
+        .document_types()
+        .get("person")
+        .expect("contract should have a person document type");
+
+    let first_names = |results: Vec<Vec<u8>>| -> Vec<String> {
+        results
+            .iter()
+            .map(|result| {
+                let document = Document::from_cbor(result.as_slice(), None, None)
+                    .expect("we should be able to deserialize the cbor");
+                let first_name_value = document
+                    .properties
+                    .get("firstName")
+                    .expect("we should be able to get the first name");
+                let first_name = first_name_value
+                    .as_text()
+                    .expect("the first name should be a string");
+                String::from(first_name)
+            })
+            .collect()
+    };
+
+    let all_query_cbor = common::value_to_cbor(
+        json!({
+            "where": [["firstName", ">=", "A"]],
+            "limit": 100,
+            "orderBy": [["firstName", "asc"]]
+        }),
+        None,
+    );
+    let all_query =
+        DriveQuery::from_cbor(all_query_cbor.as_slice(), &contract, person_document_type)
+            .expect("query should be built");
+    let (all_results, _, _) = all_query
+        .execute_no_proof(&drive, None)
+        .expect("query should be executed");
+    let all_names = first_names(all_results);
+
+    assert_eq!(all_names.len(), 10);
+
+    // Not equal is planned as the two ranges around the value
+    let query_cbor = common::value_to_cbor(
+        json!({
+            "where": [["firstName", "!=", "Prissie"]],
+            "limit": 100,
+            "orderBy": [["firstName", "asc"]]
+        }),
+        None,
+    );
+    let query = DriveQuery::from_cbor(query_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+
+    assert!(query.is_disjunctive());
+
+    let sql_string =
+        "select * from person where firstName != 'Prissie' order by firstName asc limit 100";
+    let sql_query = DriveQuery::from_sql_expr(sql_string, &contract).expect("should build query");
+
+    assert_eq!(query, sql_query);
+
+    let (results, _, _) = query
+        .execute_no_proof(&drive, None)
+        .expect("query should be executed");
+
+    let expected_names: Vec<String> = all_names
+        .iter()
+        .filter(|name| name.as_str() != "Prissie")
+        .cloned()
+        .collect();
+    assert_eq!(first_names(results), expected_names);
+
+    // Not in is planned as the ranges below, between and above the values
+    let query_cbor = common::value_to_cbor(
+        json!({
+            "where": [["firstName", "notIn", ["Prissie", "Adey"]]],
+            "limit": 100,
+            "orderBy": [["firstName", "asc"]]
+        }),
+        None,
+    );
+    let query = DriveQuery::from_cbor(query_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+
+    assert_eq!(query.or_internal_clauses.len(), 2);
+
+    let sql_string = "select * from person where firstName not in ('Prissie', 'Adey') order by firstName asc limit 100";
+    let sql_query = DriveQuery::from_sql_expr(sql_string, &contract).expect("should build query");
+
+    assert_eq!(query, sql_query);
+
+    let (results, _, _) = query
+        .execute_no_proof(&drive, None)
+        .expect("query should be executed");
+
+    let expected_names: Vec<String> = all_names
+        .iter()
+        .filter(|name| name.as_str() != "Prissie" && name.as_str() != "Adey")
+        .cloned()
+        .collect();
+    assert_eq!(first_names(results), expected_names);
+
+    // Not in values each add a range, at most 9 values fit in the or clauses limit
+    let names = ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J"];
+    let query_cbor = common::value_to_cbor(
+        json!({
+            "where": [["firstName", "notIn", names[..9]]],
+            "limit": 100,
+            "orderBy": [["firstName", "asc"]]
+        }),
+        None,
+    );
+    let query = DriveQuery::from_cbor(query_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+
+    assert_eq!(query.or_internal_clauses.len(), 9);
+
+    let query_cbor = common::value_to_cbor(
+        json!({
+            "where": [["firstName", "notIn", names]],
+            "limit": 100,
+            "orderBy": [["firstName", "asc"]]
+        }),
+        None,
+    );
+    let result = DriveQuery::from_cbor(query_cbor.as_slice(), &contract, person_document_type);
+    assert!(matches!(
+        result,
+        Err(Error::Query(QueryError::InvalidInClause(_)))
+    ));
+}
+
+#[test]
+fn test_family_query_with_offset() {
+    let (drive, contract) = setup_family_tests(10, true, 73509);
+
+    let root_hash = drive
+        .grove
+        .root_hash(None)
+        .unwrap()
+        .expect("there is always a root hash");
+
+    let person_document_type = contract
+        .document_types()
+        .get("person")
+        .expect("contract should have a person document type");
+
+    let all_query_cbor = common::value_to_cbor(
+        json!({
+            "where": [["firstName", ">=", "A"]],
+            "limit": 100,
+            "orderBy": [["firstName", "asc"]]
+        }),
+        None,
+    );
+    let all_query =
+        DriveQuery::from_cbor(all_query_cbor.as_slice(), &contract, person_document_type)
+            .expect("query should be built");
+    let (all_results, skipped, _) = all_query
+        .execute_no_proof(&drive, None)
+        .expect("query should be executed");
+
+    assert_eq!(skipped, 0);
+
+    let query_cbor = common::value_to_cbor(
+        json!({
+            "where": [["firstName", ">=", "A"]],
+            "limit": 3,
+            "offset": 4,
+            "orderBy": [["firstName", "asc"]]
+        }),
+        None,
+    );
+    let query = DriveQuery::from_cbor(query_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+
+    let sql_string =
+        "select * from person where firstName >= 'A' order by firstName asc limit 3 offset 4";
+    let sql_query = DriveQuery::from_sql_expr(sql_string, &contract).expect("should build query");
+
+    assert_eq!(query, sql_query);
+
+    let (results, skipped, cost) = query
+        .execute_no_proof(&drive, None)
+        .expect("query should be executed");
+
+    assert_eq!(skipped, 4);
+    assert_eq!(results, all_results[4..7].to_vec());
+    assert!(cost > 0);
+
+    // The skipped documents are walked by grovedb and only charged once, so skipping
+    // them doesn't cost more than returning them
+    let unskipped_query_cbor = common::value_to_cbor(
+        json!({
+            "where": [["firstName", ">=", "A"]],
+            "limit": 7,
+            "orderBy": [["firstName", "asc"]]
+        }),
+        None,
+    );
+    let unskipped_query = DriveQuery::from_cbor(
+        unskipped_query_cbor.as_slice(),
+        &contract,
+        person_document_type,
+    )
+    .expect("query should be built");
+    let (_, _, unskipped_cost) = unskipped_query
+        .execute_no_proof(&drive, None)
+        .expect("query should be executed");
+
+    assert!(cost <= unskipped_cost);
+
+    let (proof, _) = query
+        .clone()
+        .execute_with_proof(&drive, None)
+        .expect("we should be able to a proof");
+
+    let start_document = None;
+    let (proof_root_hash, documents) = query
+        .verify_proof(proof.as_slice(), start_document)
+        .expect("we should be able to verify the proof");
+    assert_eq!(root_hash, proof_root_hash);
+
+    let expected_documents: Vec<Document> = results
+        .iter()
+        .map(|result| {
+            Document::from_cbor(result.as_slice(), None, None)
+                .expect("we should be able to deserialize the cbor")
+        })
+        .collect();
+    assert_eq!(documents, expected_documents);
+
+    let sql_string =
+        "select * from person where firstName >= 'A' order by firstName asc limit 3 offset 1001";
+    let result = DriveQuery::from_sql_expr(sql_string, &contract);
+    assert!(matches!(
+        result,
+        Err(Error::Query(QueryError::InvalidOffset(_)))
+    ));
+}
+
+#[test]
+fn test_family_sql_start_at_queries() {
+    let (drive, contract) = setup_family_tests(10, true, 73509);
+
+    let person_document_type = contract
+        .document_types()
+        .get("person")
+        .expect("contract should have a person document type");
+
+    let kevina_encoded_id = "B4zLoYmSGz5SyD7QjAvcjAWtzGCfnQDCti3o7V2ZBDNo".to_string();
+
+    let query_value = json!({
+        "startAfter": kevina_encoded_id, //Kevina
+        "limit": 100,
+        "orderBy": [
+            ["$id", "asc"]
+        ]
+    });
+    let where_cbor = common::value_to_cbor(query_value, None);
+    let query = DriveQuery::from_cbor(where_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+
+    let sql_string = format!(
+        "select * from person where \"$id\" > '{}' order by \"$id\" asc limit 100",
+        kevina_encoded_id
+    );
+    let sql_query =
+        DriveQuery::from_sql_expr(sql_string.as_str(), &contract).expect("should build query");
+
+    assert_eq!(query, sql_query);
+
+    let query_value = json!({
+        "where": [
+            ["firstName", ">", "Chris"],
+            ["firstName", "<=", "Noellyn"]
+        ],
+        "startAt": kevina_encoded_id, //Kevina
+        "limit": 100,
+        "orderBy": [
+            ["firstName", "asc"]
+        ]
+    });
+    let where_cbor = common::value_to_cbor(query_value, None);
+    let query = DriveQuery::from_cbor(where_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+
+    let sql_string = format!(
+        "select * from person where firstName > 'Chris' and \"$id\" >= '{}' and firstName <= 'Noellyn' order by firstName asc limit 100",
+        kevina_encoded_id
+    );
+    let sql_query =
+        DriveQuery::from_sql_expr(sql_string.as_str(), &contract).expect("should build query");
+
+    assert_eq!(query, sql_query);
+
+    let (results, _, _) = query
+        .execute_no_proof(&drive, None)
+        .expect("query should be executed");
+    let (sql_results, _, _) = sql_query
+        .execute_no_proof(&drive, None)
+        .expect("query should be executed");
+
+    assert_eq!(results, sql_results);
+
+    let sql_string = format!(
+        "select * from person where \"$id\" > '{}' and \"$id\" >= '{}' order by \"$id\" asc",
+        kevina_encoded_id, kevina_encoded_id
+    );
+    let result = DriveQuery::from_sql_expr(sql_string.as_str(), &contract);
+    assert!(matches!(
+        result,
+        Err(Error::Query(QueryError::DuplicateStartConditions(_)))
+    ));
+}
+
+#[test]
+fn test_family_query_with_cursor() {
+    let (drive, contract) = setup_family_tests(10, true, 73509);
+
+    let root_hash = drive
+        .grove
+        .root_hash(None)
+        .unwrap()
+        .expect("there is always a root hash");
+
+    let person_document_type = contract
+        .document_types()
+        .get("person")
+        .expect("contract should have a person document type");
+
+    let all_query_cbor = common::value_to_cbor(
+        json!({
+            "where": [["firstName", ">=", "A"]],
+            "limit": 100,
+            "orderBy": [["firstName", "asc"]]
+        }),
+        None,
+    );
+    let all_query =
+        DriveQuery::from_cbor(all_query_cbor.as_slice(), &contract, person_document_type)
+            .expect("query should be built");
+    let (all_results, _, _) = all_query
+        .execute_no_proof(&drive, None)
+        .expect("query should be executed");
+
+    let first_page_cbor = common::value_to_cbor(
+        json!({
+            "where": [["firstName", ">=", "A"]],
+            "limit": 4,
+            "orderBy": [["firstName", "asc"]]
+        }),
+        None,
+    );
+    let first_page_query =
+        DriveQuery::from_cbor(first_page_cbor.as_slice(), &contract, person_document_type)
+            .expect("query should be built");
+    let (first_page, cursor, _) = first_page_query
+        .execute_no_proof_with_cursor(&drive, None)
+        .expect("query should be executed");
+
+    assert_eq!(first_page, all_results[..4].to_vec());
+
+    let cursor = cursor.expect("a full page should have a cursor");
+
+    // Proved pages come with the same cursor, clients can rebuild it from the proof
+    let (first_page_proof, proved_cursor, _) = first_page_query
+        .execute_with_proof_and_cursor(&drive, None)
+        .expect("query should be executed");
+
+    assert_eq!(proved_cursor, Some(cursor.clone()));
+
+    let (_, proved_documents) = first_page_query
+        .verify_proof(first_page_proof.as_slice(), None)
+        .expect("we should be able to verify the proof");
+    let rebuilt_cursor = first_page_query
+        .cursor_after(proved_documents.last().expect("the page should be full"))
+        .expect("expected to make a cursor")
+        .to_bytes()
+        .expect("expected to serialize the cursor");
+
+    assert_eq!(rebuilt_cursor, cursor);
+
+    // The cursor is the key path of the last proved document in the index of the query
+    let decoded_cursor =
+        QueryCursor::from_bytes(cursor.as_slice()).expect("expected to decode the cursor");
+    let last_proved_document = proved_documents.last().expect("the page should be full");
+    assert_eq!(decoded_cursor.contract_id, contract.id.buffer);
+    assert_eq!(
+        decoded_cursor.index_properties,
+        vec!["firstName".to_string()]
+    );
+    assert_eq!(decoded_cursor.index_key_path.id, last_proved_document.id);
+    assert_eq!(
+        decoded_cursor.index_key_path.key_for_property("firstName"),
+        Some(
+            person_document_type
+                .serialize_value_for_key(
+                    "firstName",
+                    last_proved_document
+                        .properties
+                        .get("firstName")
+                        .expect("a person has a first name"),
+                )
+                .expect("expected to serialize the first name")
+        )
+    );
+
+    // The last document of the first page is deleted, the cursor doesn't need it
+    let last_document = Document::from_cbor(first_page[3].as_slice(), None, None)
+        .expect("we should be able to deserialize the cbor");
+    drive
+        .delete_document_for_contract(
+            &last_document.id,
+            &contract,
+            "person",
+            None,
+            true,
+            false,
+            None,
+        )
+        .expect("expected to be able to delete the document");
+
+    let root_hash_after_delete = drive
+        .grove
+        .root_hash(None)
+        .unwrap()
+        .expect("there is always a root hash");
+    assert_ne!(root_hash, root_hash_after_delete);
+
+    let next_page_value: serde_json::Value = json!({
+        "where": [["firstName", ">=", "A"]],
+        "limit": 100,
+        "orderBy": [["firstName", "asc"]]
+    });
+    let mut next_page_cbor_map: BTreeMap<String, ciborium::value::Value> =
+        ciborium::de::from_reader(common::value_to_cbor(next_page_value, None).as_slice())
+            .expect("expected a cbor map");
+    next_page_cbor_map.insert(
+        "cursor".to_string(),
+        ciborium::value::Value::Bytes(cursor.clone()),
+    );
+    let mut next_page_cbor = vec![];
+    ciborium::ser::into_writer(&next_page_cbor_map, &mut next_page_cbor)
+        .expect("expected to serialize the query");
+
+    let next_page_query =
+        DriveQuery::from_cbor(next_page_cbor.as_slice(), &contract, person_document_type)
+            .expect("query should be built");
+    let (next_page, _, _) = next_page_query
+        .execute_no_proof(&drive, None)
+        .expect("query should be executed");
+
+    assert_eq!(next_page, all_results[4..].to_vec());
+
+    let (proof, _) = next_page_query
+        .clone()
+        .execute_with_proof(&drive, None)
+        .expect("we should be able to a proof");
+
+    let (proof_root_hash, documents) = next_page_query
+        .verify_proof(proof.as_slice(), None)
+        .expect("we should be able to verify the proof with the cursor");
+    assert_eq!(root_hash_after_delete, proof_root_hash);
+
+    let expected_documents: Vec<Document> = next_page
+        .iter()
+        .map(|result| {
+            Document::from_cbor(result.as_slice(), None, None)
+                .expect("we should be able to deserialize the cbor")
+        })
+        .collect();
+    assert_eq!(documents, expected_documents);
+
+    // The cursor already skips the previous pages, it can't be used with an offset
+    let mut offset_cbor_map = next_page_cbor_map.clone();
+    offset_cbor_map.insert(
+        "offset".to_string(),
+        ciborium::value::Value::Integer(1.into()),
+    );
+    let mut offset_cbor = vec![];
+    ciborium::ser::into_writer(&offset_cbor_map, &mut offset_cbor)
+        .expect("expected to serialize the query");
+    let result = DriveQuery::from_cbor(offset_cbor.as_slice(), &contract, person_document_type);
+    assert!(matches!(
+        result,
+        Err(Error::Query(QueryError::InvalidOffset(_)))
+    ));
+
+    // The cursor only works in the direction it was made for
+    next_page_cbor_map.insert(
+        "orderBy".to_string(),
+        ciborium::value::Value::Array(vec![ciborium::value::Value::Array(vec![
+            ciborium::value::Value::Text("firstName".to_string()),
+            ciborium::value::Value::Text("desc".to_string()),
+        ])]),
+    );
+    let mut descending_cbor = vec![];
+    ciborium::ser::into_writer(&next_page_cbor_map, &mut descending_cbor)
+        .expect("expected to serialize the query");
+    let result = DriveQuery::from_cbor(descending_cbor.as_slice(), &contract, person_document_type);
+    assert!(matches!(
+        result,
+        Err(Error::Query(QueryError::InvalidCursor(_)))
+    ));
+
+    let query_cbor_with_cursor = |query_value: serde_json::Value, cursor: &[u8]| {
+        let mut query_cbor_map: BTreeMap<String, ciborium::value::Value> =
+            ciborium::de::from_reader(common::value_to_cbor(query_value, None).as_slice())
+                .expect("expected a cbor map");
+        query_cbor_map.insert(
+            "cursor".to_string(),
+            ciborium::value::Value::Bytes(cursor.to_vec()),
+        );
+        let mut query_cbor = vec![];
+        ciborium::ser::into_writer(&query_cbor_map, &mut query_cbor)
+            .expect("expected to serialize the query");
+        query_cbor
+    };
+
+    // The cursor only works on the index it was made for
+    let other_index_cbor = query_cbor_with_cursor(
+        json!({
+            "where": [["middleName", ">=", "A"]],
+            "limit": 100,
+            "orderBy": [["middleName", "asc"]]
+        }),
+        cursor.as_slice(),
+    );
+    let result =
+        DriveQuery::from_cbor(other_index_cbor.as_slice(), &contract, person_document_type);
+    assert!(matches!(
+        result,
+        Err(Error::Query(QueryError::InvalidCursor(_)))
+    ));
+
+    // Each conjunction of an or query is made on its own index, so there are no cursors
+    let or_query_value = json!({
+        "or": [
+            [["firstName", "<", "Briney"]],
+            [["firstName", ">", "Noellyn"]]
+        ],
+        "limit": 100,
+        "orderBy": [["firstName", "asc"]]
+    });
+    let or_cbor = query_cbor_with_cursor(or_query_value.clone(), cursor.as_slice());
+    let result = DriveQuery::from_cbor(or_cbor.as_slice(), &contract, person_document_type);
+    assert!(matches!(
+        result,
+        Err(Error::Query(QueryError::InvalidCursor(_)))
+    ));
+
+    let or_query = DriveQuery::from_cbor(
+        common::value_to_cbor(or_query_value, None).as_slice(),
+        &contract,
+        person_document_type,
+    )
+    .expect("query should be built");
+    let result = or_query.cursor_after(last_proved_document);
+    assert!(matches!(
+        result,
+        Err(Error::Query(QueryError::InvalidCursor(_)))
+    ));
+
+    // A cursor made for another contract is rejected
+    let forged_cursor = QueryCursor {
+        contract_id: [0; 32],
+        ..decoded_cursor
+    }
+    .to_bytes()
+    .expect("expected to serialize the cursor");
+    let forged_cbor = query_cbor_with_cursor(
+        json!({
+            "where": [["firstName", ">=", "A"]],
+            "limit": 100,
+            "orderBy": [["firstName", "asc"]]
+        }),
+        forged_cursor.as_slice(),
+    );
+    let result = DriveQuery::from_cbor(forged_cbor.as_slice(), &contract, person_document_type);
+    assert!(matches!(
+        result,
+        Err(Error::Query(QueryError::InvalidCursor(_)))
+    ));
+}
+
+#[test]
+fn test_family_query_explain() {
+    let (drive, contract) = setup_family_tests(10, true, 73509);
+
+    let (fee_schedule, fee_multiplier) = drive
+        .get_current_epoch_fees(None)
+        .expect("expected to get the current epoch fees");
+
+    let person_document_type = contract
+        .document_types()
+        .get("person")
+        .expect("contract should have a person document type");
+
+    let query_value = json!({
+        "where": [
+            ["firstName", ">", "Chris"],
+            ["firstName", "<=", "Noellyn"]
+        ],
+        "limit": 100,
+        "orderBy": [
+            ["firstName", "asc"]
+        ]
+    });
+    let where_cbor = common::value_to_cbor(query_value, None);
+    let query = DriveQuery::from_cbor(where_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+
+    let explanation = query
+        .explain(&fee_schedule, fee_multiplier)
+        .expect("expected to explain the query");
+
+    let index = query.find_best_index().expect("expected to find an index");
+    assert_eq!(explanation.index, Some(index));
+    assert_eq!(
+        explanation.rejected_indexes.len(),
+        person_document_type.indices.len() - 1
+    );
+
+    let path_query = explanation
+        .path_query
+        .expect("expected a path query for an indexed query");
+    let executed_path_query = query
+        .construct_path_query_operations(&drive, None, &mut vec![])
+        .expect("expected to construct the path query");
+    assert_eq!(path_query.path, executed_path_query.path);
+    assert_eq!(
+        path_query.query.query.items,
+        executed_path_query.query.query.items
+    );
+
+    // A query on a property without an index is explained instead of failing
+    let query_value = json!({
+        "where": [
+            ["nickname", "==", "Kev"]
+        ],
+    });
+    let where_cbor = common::value_to_cbor(query_value, None);
+    let query = DriveQuery::from_cbor(where_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+
+    let explanation = query
+        .explain(&fee_schedule, fee_multiplier)
+        .expect("expected to explain the query");
+    assert!(explanation.index.is_none());
+    assert!(explanation.path_query.is_none());
+
+    // Without a cursor the start document of an indexed query is unknown
+    let query_value = json!({
+        "where": [
+            ["firstName", ">", "Chris"]
+        ],
+        "startAfter": String::from("6A8SGgdmj2NtWCYoYDPDpbsYkq2MCbgi6Lx4ALLfF178"),
+        "orderBy": [
+            ["firstName", "asc"]
+        ]
+    });
+    let where_cbor = common::value_to_cbor(query_value, None);
+    let query = DriveQuery::from_cbor(where_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+
+    let result = query.explain(&fee_schedule, fee_multiplier);
+    assert!(matches!(
+        result,
+        Err(Error::Query(QueryError::Unsupported(_)))
+    ));
+}
@@ -189,6 +189,7 @@ pub fn setup(
                     true,
                     block_time as f64,
                     true,
+                    false,
                     Some(&db_transaction),
                 )
                 .expect("expected to add document");
@@ -967,6 +968,7 @@ fn test_query_historical() {
             true,
             0f64,
             true,
+            false,
             Some(&db_transaction),
         )
         .expect("document should be inserted");
@@ -1011,6 +1013,7 @@ fn test_query_historical() {
             true,
             0f64,
             true,
+            false,
             Some(&db_transaction),
         )
         .expect("document should be inserted");
@@ -1354,6 +1357,7 @@ fn test_query_historical() {
             None,
             0f64,
             true,
+            false,
             StorageFlags::default(),
             Some(&db_transaction),
         )
@@ -1450,3 +1454,133 @@ fn test_query_historical() {
         ]
     );
 }
+
+#[test]
+fn test_document_as_of_and_history() {
+    let (drive, contract) = setup(10, None, true, 73509);
+
+    let query_value = json!({
+        "where": [
+            ["firstName", "==", "Kevina"]
+        ],
+    });
+    let where_cbor = common::value_to_cbor(query_value, None);
+    let person_document_type = contract
+        .document_types()
+        .get("person")
+        .expect("contract should have a person document type");
+    let query = DriveQuery::from_cbor(where_cbor.as_slice(), &contract, person_document_type)
+        .expect("query should be built");
+    let (results, _, _) = query
+        .execute_no_proof(&drive, None)
+        .expect("proof should be executed");
+    assert_eq!(results.len(), 1);
+
+    let current_document = Document::from_cbor(results.first().unwrap().as_slice(), None, None)
+        .expect("we should be able to deserialize the cbor");
+    let contract_id = contract.id.buffer;
+
+    // all revisions
+
+    let (revisions, _) = drive
+        .fetch_document_history(
+            &contract,
+            "person",
+            current_document.id,
+            0.0..=1000.0,
+            None,
+            None,
+        )
+        .expect("expected to fetch the document history");
+
+    let block_times: Vec<f64> = revisions
+        .iter()
+        .map(|(block_time, _)| *block_time)
+        .collect();
+    assert_eq!(block_times, vec![0.0, 15.0, 100.0, 1000.0]);
+    assert_eq!(revisions.last().unwrap().1, current_document);
+
+    let (proof, _) = drive
+        .prove_document_history(
+            &contract,
+            "person",
+            current_document.id,
+            0.0..=1000.0,
+            None,
+            None,
+        )
+        .expect("expected to prove the document history");
+    let (root_hash, proved_revisions) = Drive::verify_document_history(
+        proof.as_slice(),
+        contract_id,
+        "person",
+        current_document.id,
+        0.0..=1000.0,
+        None,
+    )
+    .expect("expected to verify the document history");
+    let expected_root_hash = drive
+        .grove
+        .root_hash(None)
+        .unwrap()
+        .expect("there is always a root hash");
+    assert_eq!(root_hash, expected_root_hash);
+    assert_eq!(proved_revisions, revisions);
+
+    // revisions in a range with a limit
+
+    let (revisions_between, _) = drive
+        .fetch_document_history(
+            &contract,
+            "person",
+            current_document.id,
+            10.0..=1000.0,
+            Some(2),
+            None,
+        )
+        .expect("expected to fetch the document history");
+    assert_eq!(revisions_between, revisions[1..3].to_vec());
+
+    // as of a block time between revisions
+
+    let (revision, _) = drive
+        .fetch_document_as_of(&contract, "person", current_document.id, 300.0, None)
+        .expect("expected to fetch the document as of the block time");
+    assert_eq!(revision, Some(revisions[2].clone()));
+
+    let (proof, _) = drive
+        .prove_document_as_of(&contract, "person", current_document.id, 300.0, None)
+        .expect("expected to prove the document as of the block time");
+    let (root_hash, proved_revision) = Drive::verify_document_as_of(
+        proof.as_slice(),
+        contract_id,
+        "person",
+        current_document.id,
+        300.0,
+    )
+    .expect("expected to verify the document as of the block time");
+    assert_eq!(root_hash, expected_root_hash);
+    assert_eq!(proved_revision, revision);
+
+    // as of a block time before the document existed
+
+    let (revision, _) = drive
+        .fetch_document_as_of(&contract, "person", current_document.id, -1.0, None)
+        .expect("expected to fetch the document as of the block time");
+    assert_eq!(revision, None);
+
+    // a reversed time range is not allowed
+
+    let result = drive.fetch_document_history(
+        &contract,
+        "person",
+        current_document.id,
+        100.0..=0.0,
+        None,
+        None,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::Query(QueryError::InvalidBetweenClause(_)))
+    ));
+}
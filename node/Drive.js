@@ -84,15 +84,23 @@ class Drive {
    * @param {Date} blockTime
    * @param {boolean} [useTransaction=false]
    * @param {boolean} [dryRun=false]
+   * @param {boolean} [withFeeBreakdown=false] - also return the work done by operation kind
    *
-   * @returns {Promise<[number, number]>}
+   * @returns {Promise<[number, number, FeeBreakdown?]>}
    */
-  async applyContract(dataContract, blockTime, useTransaction = false, dryRun = false) {
+  async applyContract(
+    dataContract,
+    blockTime,
+    useTransaction = false,
+    dryRun = false,
+    withFeeBreakdown = false,
+  ) {
     return driveApplyContractAsync.call(
       this.drive,
       dataContract.toBuffer(),
       blockTime,
       !dryRun,
+      withFeeBreakdown,
       useTransaction,
     );
   }
@@ -102,10 +110,17 @@ class Drive {
    * @param {Date} blockTime
    * @param {boolean} [useTransaction=false]
    * @param {boolean} [dryRun=false]
+   * @param {boolean} [withFeeBreakdown=false] - also return the work done by operation kind
    *
-   * @returns {Promise<[number, number]>}
+   * @returns {Promise<[number, number, FeeBreakdown?]>}
    */
-  async createDocument(document, blockTime, useTransaction = false, dryRun = false) {
+  async createDocument(
+    document,
+    blockTime,
+    useTransaction = false,
+    dryRun = false,
+    withFeeBreakdown = false,
+  ) {
     return driveCreateDocumentAsync.call(
       this.drive,
       document.toBuffer(),
@@ -115,6 +130,7 @@ class Drive {
       true,
       blockTime,
       !dryRun,
+      withFeeBreakdown,
       useTransaction,
     );
   }
@@ -124,10 +140,17 @@ class Drive {
    * @param {Date} blockTime
    * @param {boolean} [useTransaction=false]
    * @param {boolean} [dryRun=false]
+   * @param {boolean} [withFeeBreakdown=false] - also return the work done by operation kind
    *
-   * @returns {Promise<[number, number]>}
+   * @returns {Promise<[number, number, Array<[number, number]>, FeeBreakdown?]>}
    */
-  async updateDocument(document, blockTime, useTransaction = false, dryRun = false) {
+  async updateDocument(
+    document,
+    blockTime,
+    useTransaction = false,
+    dryRun = false,
+    withFeeBreakdown = false,
+  ) {
     return driveUpdateDocumentAsync.call(
       this.drive,
       document.toBuffer(),
@@ -136,6 +159,7 @@ class Drive {
       document.getOwnerId().toBuffer(),
       blockTime,
       !dryRun,
+      withFeeBreakdown,
       useTransaction,
     );
   }
@@ -146,8 +170,9 @@ class Drive {
    * @param {Identifier} documentId
    * @param {boolean} [useTransaction=false]
    * @param {boolean} [dryRun=false]
+   * @param {boolean} [withFeeBreakdown=false] - also return the work done by operation kind
    *
-   * @returns {Promise<[number, number]>}
+   * @returns {Promise<[number, number, Array<[number, number]>, FeeBreakdown?]>}
    */
   async deleteDocument(
     dataContract,
@@ -155,6 +180,7 @@ class Drive {
     documentId,
     useTransaction = false,
     dryRun = false,
+    withFeeBreakdown = false,
   ) {
     return driveDeleteDocumentAsync.call(
       this.drive,
@@ -162,6 +188,7 @@ class Drive {
       dataContract.toBuffer(),
       documentType,
       !dryRun,
+      withFeeBreakdown,
       useTransaction,
     );
   }
@@ -236,14 +263,21 @@ class Drive {
    * @param {Identity} identity
    * @param {boolean} [useTransaction=false]
    * @param {boolean} [dryRun=false]
+   * @param {boolean} [withFeeBreakdown=false] - also return the work done by operation kind
    *
-   * @returns {Promise<[number, number]>}
+   * @returns {Promise<[number, number, FeeBreakdown?]>}
    */
-  async insertIdentity(identity, useTransaction = false, dryRun = false) {
+  async insertIdentity(
+    identity,
+    useTransaction = false,
+    dryRun = false,
+    withFeeBreakdown = false,
+  ) {
     return driveInsertIdentityAsync.call(
       this.drive,
       identity.toBuffer(),
       !dryRun,
+      withFeeBreakdown,
       useTransaction,
     );
   }
@@ -340,6 +374,7 @@ class Drive {
  * @property {number} blockTimeMs - timestamp in milliseconds
  * @property {number} [previousBlockTimeMs] - timestamp in milliseconds
  * @property {Buffer} proposerProTxHash
+ * @property {number} [protocolVersion] - set when the block activates a new protocol version
  */
 
 /**
@@ -355,6 +390,24 @@ class Drive {
  * @typedef Fees
  * @property {number} processingFees
  * @property {number} storageFees
+ * @property {Array<[number, number]>} refundsByEpoch
+ */
+
+/**
+ * @typedef FeeBreakdown - work done by operation kind
+ * @property {OperationCostBreakdown} calculatedCost
+ * @property {OperationCostBreakdown} contractFetch
+ * @property {OperationCostBreakdown} storageRemoval
+ */
+
+/**
+ * @typedef OperationCostBreakdown
+ * @property {number} seekCount
+ * @property {number} storageLoadedBytes
+ * @property {number} storageWrittenBytes
+ * @property {number} storageFreedBytes
+ * @property {number} hashByteCalls
+ * @property {number} hashNodeCalls
  */
 
 /**
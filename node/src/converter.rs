@@ -1,6 +1,9 @@
 use neon::prelude::*;
 use neon::types::buffer::TypedArray;
 use rs_drive::drive::flags::StorageFlags;
+use rs_drive::fee::op::DriveOperationKind;
+use rs_drive::fee::result::FeeBreakdown;
+use rs_drive::fee::EpochRefund;
 use rs_drive::grovedb::{Element, PathQuery, Query, SizedQuery};
 use std::borrow::Borrow;
 
@@ -103,6 +106,61 @@ pub fn nested_vecs_to_js<'a, C: Context<'a>>(
     Ok(js_array.upcast())
 }
 
+/// Refunds are [epoch index, credits] pairs
+pub fn epoch_refunds_to_js<'a, C: Context<'a>>(
+    refunds: Vec<EpochRefund>,
+    cx: &mut C,
+) -> NeonResult<Handle<'a, JsValue>> {
+    let js_array: Handle<JsArray> = cx.empty_array();
+
+    for (index, (epoch_index, refund)) in refunds.into_iter().enumerate() {
+        let js_refund: Handle<JsArray> = cx.empty_array();
+        let epoch_index_value = cx.number(epoch_index);
+        let refund_value = cx.number(refund as f64);
+        js_refund.set(cx, 0, epoch_index_value)?;
+        js_refund.set(cx, 1, refund_value)?;
+        js_array.set(cx, index as u32, js_refund)?;
+    }
+
+    Ok(js_array.upcast())
+}
+
+fn drive_operation_kind_to_string(kind: DriveOperationKind) -> &'static str {
+    match kind {
+        DriveOperationKind::CalculatedCost => "calculatedCost",
+        DriveOperationKind::ContractFetch => "contractFetch",
+        DriveOperationKind::StorageRemoval => "storageRemoval",
+    }
+}
+
+pub fn fee_breakdown_to_js<'a, C: Context<'a>>(
+    breakdown: FeeBreakdown,
+    cx: &mut C,
+) -> NeonResult<Handle<'a, JsValue>> {
+    let js_breakdown: Handle<JsObject> = cx.empty_object();
+
+    for (kind, cost) in breakdown {
+        let js_cost: Handle<JsObject> = cx.empty_object();
+
+        let seek_count = cx.number(cost.seek_count as f64);
+        js_cost.set(cx, "seekCount", seek_count)?;
+        let storage_loaded_bytes = cx.number(cost.storage_loaded_bytes as f64);
+        js_cost.set(cx, "storageLoadedBytes", storage_loaded_bytes)?;
+        let storage_written_bytes = cx.number(cost.storage_written_bytes as f64);
+        js_cost.set(cx, "storageWrittenBytes", storage_written_bytes)?;
+        let storage_freed_bytes = cx.number(cost.storage_freed_bytes as f64);
+        js_cost.set(cx, "storageFreedBytes", storage_freed_bytes)?;
+        let hash_byte_calls = cx.number(cost.hash_byte_calls as f64);
+        js_cost.set(cx, "hashByteCalls", hash_byte_calls)?;
+        let hash_node_calls = cx.number(cost.hash_node_calls as f64);
+        js_cost.set(cx, "hashNodeCalls", hash_node_calls)?;
+
+        js_breakdown.set(cx, drive_operation_kind_to_string(kind), js_cost)?;
+    }
+
+    Ok(js_breakdown.upcast())
+}
+
 pub fn js_buffer_to_vec_u8<'a, C: Context<'a>>(js_buffer: Handle<JsBuffer>, cx: &mut C) -> Vec<u8> {
     // let guard = cx.lock();
 
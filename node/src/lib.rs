@@ -11,6 +11,7 @@ use neon::prelude::*;
 use neon::types::JsDate;
 use rs_drive::dpp::identity::Identity;
 use rs_drive::drive::flags::StorageFlags;
+use rs_drive::fee::result::FeeResult;
 use rs_drive::grovedb::{PathQuery, Transaction, TransactionArg};
 
 const READONLY_MSG: &str =
@@ -264,8 +265,9 @@ impl DriveWrapper {
         let js_contract_cbor = cx.argument::<JsBuffer>(0)?;
         let js_block_time = cx.argument::<JsDate>(1)?;
         let js_apply = cx.argument::<JsBoolean>(2)?;
-        let js_using_transaction = cx.argument::<JsBoolean>(3)?;
-        let js_callback = cx.argument::<JsFunction>(4)?.root(&mut cx);
+        let js_with_fee_breakdown = cx.argument::<JsBoolean>(3)?;
+        let js_using_transaction = cx.argument::<JsBoolean>(4)?;
+        let js_callback = cx.argument::<JsFunction>(5)?.root(&mut cx);
 
         let drive = cx
             .this()
@@ -273,6 +275,7 @@ impl DriveWrapper {
 
         let contract_cbor = converter::js_buffer_to_vec_u8(js_contract_cbor, &mut cx);
         let apply = js_apply.value(&mut cx);
+        let with_fee_breakdown = js_with_fee_breakdown.value(&mut cx);
         let using_transaction = js_using_transaction.value(&mut cx);
         let block_time = js_block_time.value(&mut cx);
 
@@ -283,6 +286,7 @@ impl DriveWrapper {
                     None,
                     block_time,
                     apply,
+                    with_fee_breakdown,
                     StorageFlags::default(),
                     using_transaction.then(|| transaction).flatten(),
                 );
@@ -292,7 +296,12 @@ impl DriveWrapper {
                     let this = task_context.undefined();
 
                     let callback_arguments: Vec<Handle<JsValue>> = match result {
-                        Ok((storage_fee, processing_fee)) => {
+                        Ok(FeeResult {
+                            storage_fee,
+                            processing_fee,
+                            breakdown,
+                            ..
+                        }) => {
                             let js_array: Handle<JsArray> = task_context.empty_array();
 
                             let storage_fee_value =
@@ -304,6 +313,12 @@ impl DriveWrapper {
                             js_array.set(&mut task_context, 0, storage_fee_value)?;
                             js_array.set(&mut task_context, 1, processing_fee_value)?;
 
+                            if let Some(breakdown) = breakdown {
+                                let js_breakdown =
+                                    converter::fee_breakdown_to_js(breakdown, &mut task_context)?;
+                                js_array.set(&mut task_context, 2, js_breakdown)?;
+                            }
+
                             // First parameter of JS callbacks is error, which is null in this case
                             vec![task_context.null().upcast(), js_array.upcast()]
                         }
@@ -330,8 +345,9 @@ impl DriveWrapper {
         let js_override_document = cx.argument::<JsBoolean>(4)?;
         let js_block_time = cx.argument::<JsDate>(5)?;
         let js_apply = cx.argument::<JsBoolean>(6)?;
-        let js_using_transaction = cx.argument::<JsBoolean>(7)?;
-        let js_callback = cx.argument::<JsFunction>(8)?.root(&mut cx);
+        let js_with_fee_breakdown = cx.argument::<JsBoolean>(7)?;
+        let js_using_transaction = cx.argument::<JsBoolean>(8)?;
+        let js_callback = cx.argument::<JsFunction>(9)?.root(&mut cx);
 
         let drive = cx
             .this()
@@ -344,6 +360,7 @@ impl DriveWrapper {
         let override_document = js_override_document.value(&mut cx);
         let block_time = js_block_time.value(&mut cx);
         let apply = js_apply.value(&mut cx);
+        let with_fee_breakdown = js_with_fee_breakdown.value(&mut cx);
         let using_transaction = js_using_transaction.value(&mut cx);
 
         drive
@@ -358,6 +375,7 @@ impl DriveWrapper {
                         override_document,
                         block_time,
                         apply,
+                        with_fee_breakdown,
                         StorageFlags::default(),
                         using_transaction.then(|| transaction).flatten(),
                     );
@@ -367,7 +385,12 @@ impl DriveWrapper {
                     let this = task_context.undefined();
 
                     let callback_arguments: Vec<Handle<JsValue>> = match result {
-                        Ok((storage_fee, processing_fee)) => {
+                        Ok(FeeResult {
+                            storage_fee,
+                            processing_fee,
+                            breakdown,
+                            ..
+                        }) => {
                             let js_array: Handle<JsArray> = task_context.empty_array();
 
                             let storage_fee_value =
@@ -379,6 +402,12 @@ impl DriveWrapper {
                             js_array.set(&mut task_context, 0, storage_fee_value)?;
                             js_array.set(&mut task_context, 1, processing_fee_value)?;
 
+                            if let Some(breakdown) = breakdown {
+                                let js_breakdown =
+                                    converter::fee_breakdown_to_js(breakdown, &mut task_context)?;
+                                js_array.set(&mut task_context, 2, js_breakdown)?;
+                            }
+
                             // First parameter of JS callbacks is error, which is null in this case
                             vec![task_context.null().upcast(), js_array.upcast()]
                         }
@@ -404,8 +433,9 @@ impl DriveWrapper {
         let js_owner_id = cx.argument::<JsBuffer>(3)?;
         let js_block_time = cx.argument::<JsDate>(4)?;
         let js_apply = cx.argument::<JsBoolean>(5)?;
-        let js_using_transaction = cx.argument::<JsBoolean>(6)?;
-        let js_callback = cx.argument::<JsFunction>(7)?.root(&mut cx);
+        let js_with_fee_breakdown = cx.argument::<JsBoolean>(6)?;
+        let js_using_transaction = cx.argument::<JsBoolean>(7)?;
+        let js_callback = cx.argument::<JsFunction>(8)?.root(&mut cx);
 
         let drive = cx
             .this()
@@ -417,6 +447,7 @@ impl DriveWrapper {
         let owner_id = converter::js_buffer_to_vec_u8(js_owner_id, &mut cx);
         let block_time = js_block_time.value(&mut cx);
         let apply = js_apply.value(&mut cx);
+        let with_fee_breakdown = js_with_fee_breakdown.value(&mut cx);
         let using_transaction = js_using_transaction.value(&mut cx);
 
         drive
@@ -428,6 +459,7 @@ impl DriveWrapper {
                     Some(&owner_id),
                     block_time,
                     apply,
+                    with_fee_breakdown,
                     StorageFlags::default(),
                     using_transaction.then(|| transaction).flatten(),
                 );
@@ -437,7 +469,13 @@ impl DriveWrapper {
                     let this = task_context.undefined();
 
                     let callback_arguments: Vec<Handle<JsValue>> = match result {
-                        Ok((storage_fee, processing_fee)) => {
+                        Ok(FeeResult {
+                            storage_fee,
+                            processing_fee,
+                            refunds_by_epoch: refunds,
+                            breakdown,
+                            ..
+                        }) => {
                             let js_array: Handle<JsArray> = task_context.empty_array();
 
                             let storage_fee_value =
@@ -449,6 +487,16 @@ impl DriveWrapper {
                             js_array.set(&mut task_context, 0, storage_fee_value)?;
                             js_array.set(&mut task_context, 1, processing_fee_value)?;
 
+                            let js_refunds =
+                                converter::epoch_refunds_to_js(refunds, &mut task_context)?;
+                            js_array.set(&mut task_context, 2, js_refunds)?;
+
+                            if let Some(breakdown) = breakdown {
+                                let js_breakdown =
+                                    converter::fee_breakdown_to_js(breakdown, &mut task_context)?;
+                                js_array.set(&mut task_context, 3, js_breakdown)?;
+                            }
+
                             // First parameter of JS callbacks is error, which is null in this case
                             vec![task_context.null().upcast(), js_array.upcast()]
                         }
@@ -472,8 +520,9 @@ impl DriveWrapper {
         let js_contract_cbor = cx.argument::<JsBuffer>(1)?;
         let js_document_type_name = cx.argument::<JsString>(2)?;
         let js_apply = cx.argument::<JsBoolean>(3)?;
-        let js_using_transaction = cx.argument::<JsBoolean>(4)?;
-        let js_callback = cx.argument::<JsFunction>(5)?.root(&mut cx);
+        let js_with_fee_breakdown = cx.argument::<JsBoolean>(4)?;
+        let js_using_transaction = cx.argument::<JsBoolean>(5)?;
+        let js_callback = cx.argument::<JsFunction>(6)?.root(&mut cx);
 
         let drive = cx
             .this()
@@ -483,6 +532,7 @@ impl DriveWrapper {
         let contract_cbor = converter::js_buffer_to_vec_u8(js_contract_cbor, &mut cx);
         let document_type_name = js_document_type_name.value(&mut cx);
         let apply = js_apply.value(&mut cx);
+        let with_fee_breakdown = js_with_fee_breakdown.value(&mut cx);
         let using_transaction = js_using_transaction.value(&mut cx);
 
         drive
@@ -504,6 +554,7 @@ impl DriveWrapper {
                         &document_type_name,
                         None,
                         apply,
+                        with_fee_breakdown,
                         using_transaction.then(|| transaction).flatten(),
                     );
 
@@ -512,7 +563,13 @@ impl DriveWrapper {
                         let this = task_context.undefined();
 
                         let callback_arguments: Vec<Handle<JsValue>> = match result {
-                            Ok((storage_fee, processing_fee)) => {
+                            Ok(FeeResult {
+                                storage_fee,
+                                processing_fee,
+                                refunds_by_epoch: refunds,
+                                breakdown,
+                                ..
+                            }) => {
                                 let js_array: Handle<JsArray> = task_context.empty_array();
 
                                 let storage_fee_value =
@@ -524,6 +581,18 @@ impl DriveWrapper {
                                 js_array.set(&mut task_context, 0, storage_fee_value)?;
                                 js_array.set(&mut task_context, 1, processing_fee_value)?;
 
+                                let js_refunds =
+                                    converter::epoch_refunds_to_js(refunds, &mut task_context)?;
+                                js_array.set(&mut task_context, 2, js_refunds)?;
+
+                                if let Some(breakdown) = breakdown {
+                                    let js_breakdown = converter::fee_breakdown_to_js(
+                                        breakdown,
+                                        &mut task_context,
+                                    )?;
+                                    js_array.set(&mut task_context, 3, js_breakdown)?;
+                                }
+
                                 // First parameter of JS callbacks is error, which is null in this case
                                 vec![task_context.null().upcast(), js_array.upcast()]
                             }
@@ -546,8 +615,9 @@ impl DriveWrapper {
     fn js_insert_identity_cbor(mut cx: FunctionContext) -> JsResult<JsUndefined> {
         let js_identity_cbor = cx.argument::<JsBuffer>(0)?;
         let js_apply = cx.argument::<JsBoolean>(1)?;
-        let js_using_transaction = cx.argument::<JsBoolean>(2)?;
-        let js_callback = cx.argument::<JsFunction>(3)?.root(&mut cx);
+        let js_with_fee_breakdown = cx.argument::<JsBoolean>(2)?;
+        let js_using_transaction = cx.argument::<JsBoolean>(3)?;
+        let js_callback = cx.argument::<JsFunction>(4)?.root(&mut cx);
 
         let drive = cx
             .this()
@@ -555,6 +625,7 @@ impl DriveWrapper {
 
         let identity_cbor = converter::js_buffer_to_vec_u8(js_identity_cbor, &mut cx);
         let apply = js_apply.value(&mut cx);
+        let with_fee_breakdown = js_with_fee_breakdown.value(&mut cx);
         let using_transaction = js_using_transaction.value(&mut cx);
 
         let identity =
@@ -565,6 +636,7 @@ impl DriveWrapper {
                 let result = platform.drive.insert_identity(
                     identity,
                     apply,
+                    with_fee_breakdown,
                     StorageFlags::default(),
                     using_transaction.then(|| transaction).flatten(),
                 );
@@ -574,7 +646,12 @@ impl DriveWrapper {
                     let this = task_context.undefined();
 
                     let callback_arguments: Vec<Handle<JsValue>> = match result {
-                        Ok((storage_fee, processing_fee)) => {
+                        Ok(FeeResult {
+                            storage_fee,
+                            processing_fee,
+                            breakdown,
+                            ..
+                        }) => {
                             let js_array: Handle<JsArray> = task_context.empty_array();
 
                             let storage_fee_value =
@@ -586,6 +663,12 @@ impl DriveWrapper {
                             js_array.set(&mut task_context, 0, storage_fee_value)?;
                             js_array.set(&mut task_context, 1, processing_fee_value)?;
 
+                            if let Some(breakdown) = breakdown {
+                                let js_breakdown =
+                                    converter::fee_breakdown_to_js(breakdown, &mut task_context)?;
+                                js_array.set(&mut task_context, 2, js_breakdown)?;
+                            }
+
                             // First parameter of JS callbacks is error, which is null in this case
                             vec![task_context.null().upcast(), js_array.upcast()]
                         }
@@ -98,6 +98,15 @@ describe('Drive', () => {
 
       expect(await drive.getGroveDB().getRootHash()).to.deep.equals(initialRootHash);
     });
+
+    it('should return the fee breakdown if asked for', async () => {
+      const result = await drive.applyContract(dataContract, blockTime, false, false, true);
+
+      expect(result).to.have.lengthOf(3);
+      expect(result[2]).to.have.all.keys('calculatedCost', 'contractFetch', 'storageRemoval');
+      expect(result[2].calculatedCost.seekCount).to.be.greaterThan(0);
+      expect(result[2].calculatedCost.storageWrittenBytes).to.be.greaterThan(0);
+    });
   });
 
   describe('#createDocument', () => {
@@ -171,9 +180,10 @@ describe('Drive', () => {
 
         const result = await drive.updateDocument(documentWithoutIndices, blockTime);
 
-        expect(result).to.have.lengthOf(2);
+        expect(result).to.have.lengthOf(3);
         expect(result[0]).to.be.greaterThan(0);
         expect(result[1]).to.be.greaterThan(0);
+        expect(result[2]).to.be.an('array').that.is.not.empty();
 
         expect(await drive.getGroveDB().getRootHash()).to.not.deep.equals(initialRootHash);
       });
@@ -191,9 +201,10 @@ describe('Drive', () => {
 
         const result = await drive.updateDocument(documentWithIndices, blockTime);
 
-        expect(result).to.have.lengthOf(2);
+        expect(result).to.have.lengthOf(3);
         expect(result[0]).to.be.greaterThan(0);
         expect(result[1]).to.be.greaterThan(0);
+        expect(result[2]).to.be.an('array').that.is.not.empty();
 
         expect(await drive.getGroveDB().getRootHash()).to.not.deep.equals(initialRootHash);
       });
@@ -206,7 +217,7 @@ describe('Drive', () => {
 
       const result = await drive.updateDocument(documentWithoutIndices, blockTime, false, true);
 
-      expect(result).to.have.lengthOf(2);
+      expect(result).to.have.lengthOf(3);
       // expect(result[0]).to.be.greaterThan(0);
       // expect(result[1]).to.be.greaterThan(0);
 
@@ -238,9 +249,10 @@ describe('Drive', () => {
           documentWithoutIndices.getId(),
         );
 
-        expect(result).to.have.lengthOf(2);
+        expect(result).to.have.lengthOf(3);
         expect(result[0]).to.be.greaterThan(0);
         expect(result[1]).to.be.greaterThan(0);
+        expect(result[2]).to.be.an('array').that.is.not.empty();
 
         expect(await drive.getGroveDB().getRootHash()).to.not.deep.equals(initialRootHash);
       });
@@ -261,9 +273,10 @@ describe('Drive', () => {
           documentWithIndices.getId(),
         );
 
-        expect(result).to.have.lengthOf(2);
+        expect(result).to.have.lengthOf(3);
         expect(result[0]).to.be.greaterThan(0);
         expect(result[1]).to.be.greaterThan(0);
+        expect(result[2]).to.be.an('array').that.is.not.empty();
 
         expect(await drive.getGroveDB().getRootHash()).to.not.deep.equals(initialRootHash);
       });
@@ -285,7 +298,7 @@ describe('Drive', () => {
         true,
       );
 
-      expect(result).to.have.lengthOf(2);
+      expect(result).to.have.lengthOf(3);
       // expect(result[0]).to.be.greaterThan(0);
       // expect(result[1]).to.be.greaterThan(0);
 
@@ -458,6 +471,7 @@ describe('Drive', () => {
           fees: {
             storageFees: 100,
             processingFees: 100,
+            refundsByEpoch: [],
           },
         };
 